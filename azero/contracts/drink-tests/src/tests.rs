@@ -1,6 +1,9 @@
 use crate::utils::*;
 use assert2::assert;
-use most::MostError;
+use most::{
+    ConfigValue::{Account, Amount, Flag},
+    MostError,
+};
 
 use drink::session::Session;
 use shared::hash_request_data;
@@ -578,6 +581,61 @@ fn outdated_oracle_price(mut session: Session) {
     );
 }
 
+#[drink::test]
+fn base_fee_breakdown_multiplies_out_to_base_fee(mut session: Session) {
+    mint_to_default_accounts(&mut session);
+
+    let most = most::setup(
+        &mut session,
+        guardian_accounts(),
+        DEFAULT_THRESHOLD,
+        POCKET_MONEY,
+        RELAY_GAS_USAGE,
+        MIN_GAS_PRICE,
+        MAX_GAS_PRICE,
+        DEFAULT_GAS_PRICE,
+        GAS_ORACLE_MAX_AGE,
+        ORACLE_CALL_GAS_LIMIT,
+        BASE_FEE_BUFFER_PERCENTAGE,
+        None,
+        owner(),
+        BOB,
+        DEFAULT_ETH_TRANSFER_GAS_USAGE,
+    );
+
+    let multiplied_out = |(gas_price, relay_gas_usage, markup_bps, _): (u128, u128, u128, bool)| {
+        gas_price * relay_gas_usage * markup_bps / 10000
+    };
+
+    let breakdown = most::get_base_fee_breakdown(&mut session, &most)
+        .expect("Base fee breakdown should be available");
+    assert_eq!(
+        breakdown,
+        (
+            DEFAULT_GAS_PRICE,
+            RELAY_GAS_USAGE,
+            (100 + BASE_FEE_BUFFER_PERCENTAGE) * 100,
+            false
+        )
+    );
+    assert_eq!(
+        most::get_base_fee(&mut session, &most),
+        Ok(multiplied_out(breakdown))
+    );
+
+    let oracle = gas_price_oracle::setup(&mut session, alice(), 2 * MIN_GAS_PRICE, BOB);
+    most::set_gas_price_oracle(&mut session, &most, oracle.into(), OWNER)
+        .expect("Set gas price oracle should succeed");
+
+    let breakdown = most::get_base_fee_breakdown(&mut session, &most)
+        .expect("Base fee breakdown should be available");
+    assert_eq!((breakdown.0, breakdown.3), (2 * MIN_GAS_PRICE, true));
+    assert_eq!(
+        most::get_base_fee(&mut session, &most),
+        Ok(multiplied_out(breakdown))
+    );
+}
+
 /// Reproduction of https://github.com/hats-finance/Most--Aleph-Zero-Bridge-0xab7c1d45ae21e7133574746b2985c58e0ae2e61d/issues/63
 #[drink::test]
 fn receive_request_after_switching_to_higher_threshold(mut session: Session) {
@@ -650,10 +708,20 @@ fn receive_request_after_switching_to_higher_threshold(mut session: Session) {
     .expect("Set committee should succeed");
     most::set_halted(&mut session, &most, false, OWNER).expect("Unhalt should succeed");
 
+    let current_committee_id = most::get_current_committee_id(&mut session, &most)
+        .expect("Get current committee id should succeed");
+    assert_eq!(
+        most::get_committee_threshold(&mut session, &most, current_committee_id),
+        Some(new_threshold)
+    );
+    let historical_threshold = most::get_committee_threshold(&mut session, &most, old_committee_id)
+        .expect("Threshold of a past committee should be kept");
+    assert_eq!(historical_threshold, old_threshold);
+
     let alice_balance_before = token::balance_of(&mut session, &token, alice());
     GUARDIANS
         .iter()
-        .take(old_threshold as usize)
+        .take(historical_threshold as usize)
         .for_each(|guardian| {
             let result = most::receive_request(
                 &mut session,
@@ -672,3 +740,568 @@ fn receive_request_after_switching_to_higher_threshold(mut session: Session) {
 
     assert!(token::balance_of(&mut session, &token, alice()) == alice_balance_before + amount);
 }
+
+#[drink::test]
+fn frozen_signatures_reject_new_signature(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+
+    let committee_id: u128 = 0;
+    let amount: u128 = 100;
+    let nonce: u128 = 1;
+    let request_hash = hash_request_data(committee_id, token_address, amount, alice(), nonce);
+
+    most::set_signatures_frozen(&mut session, &most, true, OWNER)
+        .expect("Freezing signatures should succeed");
+
+    let result = most::receive_request(
+        &mut session,
+        &most,
+        request_hash,
+        committee_id,
+        *token_address.as_ref(),
+        amount,
+        *alice().as_ref(),
+        nonce,
+        GUARDIANS[0].clone(),
+    );
+
+    assert_eq!(result, Err(MostError::SignaturesFrozen()));
+}
+
+#[drink::test]
+fn frozen_signatures_do_not_block_execution_of_request_at_threshold(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+
+    let committee_id: u128 = 0;
+    let amount: u128 = 100;
+    let nonce: u128 = 1;
+    let request_hash = hash_request_data(committee_id, token_address, amount, alice(), nonce);
+    let alice_balance_before = token::balance_of(&mut session, &token, alice());
+
+    let receive_request = |session: &mut Session, guardian: &drink::AccountId32| {
+        most::receive_request(
+            session,
+            &most,
+            request_hash,
+            committee_id,
+            *token_address.as_ref(),
+            amount,
+            *alice().as_ref(),
+            nonce,
+            guardian.clone(),
+        )
+    };
+
+    GUARDIANS
+        .iter()
+        .take(DEFAULT_THRESHOLD as usize - 1)
+        .for_each(|guardian| assert_eq!(receive_request(&mut session, guardian), Ok(())));
+
+    most::set_signatures_frozen(&mut session, &most, true, OWNER)
+        .expect("Freezing signatures should succeed");
+
+    assert_eq!(
+        receive_request(&mut session, &GUARDIANS[DEFAULT_THRESHOLD as usize - 1]),
+        Ok(())
+    );
+    assert_eq!(
+        token::balance_of(&mut session, &token, alice()),
+        alice_balance_before + amount
+    );
+}
+
+#[drink::test]
+fn request_status_follows_signature_collection(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+
+    let committee_id: u128 = 0;
+    let amount: u128 = 100;
+    let nonce: u128 = 1;
+    let request_hash = hash_request_data(committee_id, token_address, amount, alice(), nonce);
+
+    assert_eq!(
+        most::request_status(&mut session, &most, request_hash),
+        most::RequestStatus::RequestHashNotKnown
+    );
+
+    for (signed, guardian) in GUARDIANS
+        .iter()
+        .enumerate()
+        .take(DEFAULT_THRESHOLD as usize)
+    {
+        assert_eq!(
+            most::receive_request(
+                &mut session,
+                &most,
+                request_hash,
+                committee_id,
+                *token_address.as_ref(),
+                amount,
+                *alice().as_ref(),
+                nonce,
+                guardian.clone(),
+            ),
+            Ok(())
+        );
+
+        let expected = if signed + 1 < DEFAULT_THRESHOLD as usize {
+            most::RequestStatus::Pending {
+                collected_signatures: signed as u32 + 1,
+            }
+        } else {
+            most::RequestStatus::Processed
+        };
+        assert_eq!(
+            most::request_status(&mut session, &most, request_hash),
+            expected
+        );
+    }
+}
+
+#[drink::test]
+fn receive_request_rejects_zero_receiver(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+
+    let committee_id: u128 = 0;
+    let amount: u128 = 100;
+    let nonce: u128 = 1;
+    let zero_receiver = ink_primitives::AccountId::from([0u8; 32]);
+    let request_hash = hash_request_data(committee_id, token_address, amount, zero_receiver, nonce);
+
+    let result = most::receive_request(
+        &mut session,
+        &most,
+        request_hash,
+        committee_id,
+        *token_address.as_ref(),
+        amount,
+        [0u8; 32],
+        nonce,
+        GUARDIANS[0].clone(),
+    );
+
+    assert_eq!(result, Err(MostError::InvalidReceiver()));
+    assert_eq!(
+        most::request_status(&mut session, &most, request_hash),
+        most::RequestStatus::RequestHashNotKnown
+    );
+}
+
+#[drink::test]
+fn receive_request_rejects_amount_above_maximum(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+    let committee_id: u128 = 0;
+    let max_amount: u128 = 100;
+
+    most::set_max_receive_amount(
+        &mut session,
+        &most,
+        *token_address.as_ref(),
+        Some(max_amount),
+        OWNER,
+    )
+    .expect("Owner should be able to set max receive amount");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![(
+            "max_receive_amount".to_string(),
+            Amount(u128::MAX),
+            Amount(max_amount)
+        )]
+    );
+
+    for (amount, nonce, expected) in [
+        (max_amount, 1, Ok(())),
+        (max_amount + 1, 2, Err(MostError::AmountAboveMaximum())),
+    ] {
+        let request_hash = hash_request_data(committee_id, token_address, amount, alice(), nonce);
+        let result = most::receive_request(
+            &mut session,
+            &most,
+            request_hash,
+            committee_id,
+            *token_address.as_ref(),
+            amount,
+            *alice().as_ref(),
+            nonce,
+            GUARDIANS[0].clone(),
+        );
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[drink::test]
+fn only_owner_sets_max_receive_amount(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+
+    let result =
+        most::set_max_receive_amount(&mut session, &most, *token_address.as_ref(), Some(1), BOB);
+
+    assert!(matches!(result, Err(MostError::Ownable(_))));
+}
+
+#[drink::test]
+fn ownership_transfer_is_readable(mut session: Session) {
+    let (most, _token) = setup_most_with_token(&mut session);
+
+    assert_eq!(most::get_owner(&mut session, &most), Ok(owner()));
+    assert_eq!(
+        most::get_pending_owner(&mut session, &most),
+        Err(most::Ownable2StepError::NoPendingOwner())
+    );
+
+    most::transfer_ownership(&mut session, &most, bob(), OWNER)
+        .expect("Transfer ownership should succeed");
+    assert_eq!(most::get_owner(&mut session, &most), Ok(owner()));
+    assert_eq!(most::get_pending_owner(&mut session, &most), Ok(bob()));
+
+    most::accept_ownership(&mut session, &most, BOB).expect("Accept ownership should succeed");
+    assert_eq!(most::get_owner(&mut session, &most), Ok(bob()));
+    assert_eq!(
+        most::get_pending_owner(&mut session, &most),
+        Err(most::Ownable2StepError::NoPendingOwner())
+    );
+}
+
+#[drink::test]
+fn set_committee_requires_halt(mut session: Session) {
+    let (most, _token) = setup_most_with_token(&mut session);
+    let committee_id = most::get_current_committee_id(&mut session, &most)
+        .expect("Get current committee id should succeed");
+
+    let result = most::set_committee(
+        &mut session,
+        &most,
+        guardian_accounts(),
+        DEFAULT_THRESHOLD,
+        OWNER,
+    );
+    assert_eq!(result, Err(MostError::HaltRequired()));
+    assert_eq!(
+        most::get_current_committee_id(&mut session, &most),
+        Ok(committee_id)
+    );
+
+    most::set_halted(&mut session, &most, true, OWNER).expect("Halt should succeed");
+    most::set_committee(
+        &mut session,
+        &most,
+        guardian_accounts(),
+        DEFAULT_THRESHOLD,
+        OWNER,
+    )
+    .expect("Set committee should succeed while halted");
+    assert_eq!(
+        most::get_current_committee_id(&mut session, &most),
+        Ok(committee_id + 1)
+    );
+}
+
+#[drink::test]
+fn set_committee_rejects_unreachable_threshold(mut session: Session) {
+    let (most, _token) = setup_most_with_token(&mut session);
+    most::set_halted(&mut session, &most, true, OWNER).expect("Halt should succeed");
+    let committee_id = most::get_current_committee_id(&mut session, &most)
+        .expect("Get current committee id should succeed");
+    let committee_size = guardian_accounts().len() as u128;
+
+    for threshold in [0, committee_size + 1] {
+        let result =
+            most::set_committee(&mut session, &most, guardian_accounts(), threshold, OWNER);
+        assert_eq!(result, Err(MostError::InvalidThreshold()));
+    }
+    assert_eq!(
+        most::get_current_committee_id(&mut session, &most),
+        Ok(committee_id)
+    );
+
+    most::set_committee(
+        &mut session,
+        &most,
+        guardian_accounts(),
+        committee_size,
+        OWNER,
+    )
+    .expect("Set committee should succeed with the threshold equal to its size");
+    assert_eq!(
+        most::get_committee_threshold(&mut session, &most, committee_id + 1),
+        Some(committee_size)
+    );
+}
+
+#[drink::test]
+fn net_bridged_tracks_sends_and_receives(mut session: Session) {
+    let (most, token) = setup_most_with_token(&mut session);
+    let token_address: ink_primitives::AccountId = token.into();
+
+    let wazero = wrapped_azero::setup(&mut session, BOB);
+    most::set_halted(&mut session, &most, true, OWNER).expect("Halt should succeed");
+    most::set_wazero(&mut session, &most, wazero.into(), OWNER).expect("Set wazero should succeed");
+    most::set_halted(&mut session, &most, false, OWNER).expect("Unhalt should succeed");
+
+    assert_eq!(most::get_net_bridged(&mut session, &most, token_address), 0);
+
+    let committee_id: u128 = 0;
+    let received_amount: u128 = 100;
+    let nonce: u128 = 1;
+    let request_hash =
+        hash_request_data(committee_id, token_address, received_amount, alice(), nonce);
+
+    GUARDIANS
+        .iter()
+        .take(DEFAULT_THRESHOLD as usize)
+        .for_each(|guardian| {
+            let result = most::receive_request(
+                &mut session,
+                &most,
+                request_hash,
+                committee_id,
+                *token_address.as_ref(),
+                received_amount,
+                *alice().as_ref(),
+                nonce,
+                guardian.clone(),
+            );
+
+            assert_eq!(result, Ok(()));
+        });
+
+    assert_eq!(
+        most::get_net_bridged(&mut session, &most, token_address),
+        received_amount as i128
+    );
+
+    let sent_amount: u128 = 30;
+    let base_fee = most::get_base_fee(&mut session, &most).expect("Base fee should be available");
+    token::increase_allowance(&mut session, &token, most.into(), sent_amount, BOB)
+        .expect("Increase allowance should succeed");
+    most::send_request(
+        &mut session,
+        &most,
+        *token_address.as_ref(),
+        sent_amount,
+        REMOTE_RECEIVER,
+        base_fee,
+        BOB,
+    )
+    .expect("Send request should succeed");
+
+    assert_eq!(
+        most::get_net_bridged(&mut session, &most, token_address),
+        (received_amount - sent_amount) as i128
+    );
+}
+
+#[drink::test]
+fn setting_max_pocket_money_emits_config_changed(mut session: Session) {
+    let (most, _) = setup_most_with_token(&mut session);
+
+    most::set_max_pocket_money(&mut session, &most, 2 * POCKET_MONEY, OWNER)
+        .expect("Owner should be able to set max pocket money");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![(
+            "max_pocket_money".to_string(),
+            Amount(POCKET_MONEY),
+            Amount(2 * POCKET_MONEY)
+        )]
+    );
+}
+
+#[drink::test]
+fn setting_unchanged_value_emits_no_config_changed(mut session: Session) {
+    let (most, _) = setup_most_with_token(&mut session);
+
+    most::set_eth_transfer_gas_usage(&mut session, &most, DEFAULT_ETH_TRANSFER_GAS_USAGE, OWNER)
+        .expect("Owner should be able to set eth transfer gas usage");
+    assert!(most::config_changed_events(&mut session).is_empty());
+
+    most::set_eth_transfer_gas_usage(
+        &mut session,
+        &most,
+        DEFAULT_ETH_TRANSFER_GAS_USAGE + 1,
+        OWNER,
+    )
+    .expect("Owner should be able to set eth transfer gas usage");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![(
+            "eth_transfer_gas_usage".to_string(),
+            Amount(DEFAULT_ETH_TRANSFER_GAS_USAGE),
+            Amount(DEFAULT_ETH_TRANSFER_GAS_USAGE + 1)
+        )]
+    );
+}
+
+#[drink::test]
+fn setting_base_fee_constraints_emits_config_changed_per_field(mut session: Session) {
+    let (most, _) = setup_most_with_token(&mut session);
+
+    most::set_base_fee_constraints(
+        &mut session,
+        &most,
+        2 * RELAY_GAS_USAGE,
+        MIN_GAS_PRICE,
+        2 * MAX_GAS_PRICE,
+        DEFAULT_GAS_PRICE,
+        GAS_ORACLE_MAX_AGE,
+        ORACLE_CALL_GAS_LIMIT,
+        BASE_FEE_BUFFER_PERCENTAGE,
+        OWNER,
+    )
+    .expect("Owner should be able to set base fee constraints");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![
+            (
+                "relay_gas_usage".to_string(),
+                Amount(RELAY_GAS_USAGE),
+                Amount(2 * RELAY_GAS_USAGE)
+            ),
+            (
+                "max_gas_price".to_string(),
+                Amount(MAX_GAS_PRICE),
+                Amount(2 * MAX_GAS_PRICE)
+            ),
+        ]
+    );
+}
+
+#[drink::test]
+fn setting_gas_price_oracle_emits_config_changed(mut session: Session) {
+    let (most, _) = setup_most_with_token(&mut session);
+
+    most::set_gas_price_oracle(&mut session, &most, alice(), OWNER)
+        .expect("Owner should be able to set the gas price oracle");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![(
+            "gas_price_oracle".to_string(),
+            Account(None),
+            Account(Some(alice()))
+        )]
+    );
+
+    most::set_gas_price_oracle(&mut session, &most, bob(), OWNER)
+        .expect("Owner should be able to replace the gas price oracle");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![(
+            "gas_price_oracle".to_string(),
+            Account(Some(alice())),
+            Account(Some(bob()))
+        )]
+    );
+}
+
+#[drink::test]
+fn setting_wrapped_tokens_emits_config_changed(mut session: Session) {
+    let (most, _) = setup_most_with_token(&mut session);
+    most::set_halted(&mut session, &most, true, OWNER).expect("Halt should succeed");
+
+    most::set_weth(&mut session, &most, alice(), OWNER).expect("Owner should be able to set weth");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![("weth".to_string(), Account(None), Account(Some(alice())))]
+    );
+
+    most::set_wazero(&mut session, &most, bob(), OWNER)
+        .expect("Owner should be able to set wazero");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![("wazero".to_string(), Account(None), Account(Some(bob())))]
+    );
+}
+
+#[drink::test]
+fn freezing_signatures_emits_config_changed(mut session: Session) {
+    let (most, _) = setup_most_with_token(&mut session);
+
+    most::set_signatures_frozen(&mut session, &most, true, OWNER)
+        .expect("Owner should be able to freeze signatures");
+    assert_eq!(
+        most::config_changed_events(&mut session),
+        vec![("signatures_frozen".to_string(), Flag(false), Flag(true))]
+    );
+
+    most::set_signatures_frozen(&mut session, &most, true, OWNER)
+        .expect("Owner should be able to freeze signatures again");
+    assert!(most::config_changed_events(&mut session).is_empty());
+}
+
+fn setup_most_with_token(session: &mut Session) -> (most::Most, token::Token) {
+    mint_to_default_accounts(session);
+
+    let most = most::setup(
+        session,
+        guardian_accounts(),
+        DEFAULT_THRESHOLD,
+        POCKET_MONEY,
+        RELAY_GAS_USAGE,
+        MIN_GAS_PRICE,
+        MAX_GAS_PRICE,
+        DEFAULT_GAS_PRICE,
+        GAS_ORACLE_MAX_AGE,
+        ORACLE_CALL_GAS_LIMIT,
+        BASE_FEE_BUFFER_PERCENTAGE,
+        None,
+        owner(),
+        BOB,
+        DEFAULT_ETH_TRANSFER_GAS_USAGE,
+    );
+    let token = token::setup(session, "TestToken".to_string(), most.into(), BOB);
+
+    let token_address: ink_primitives::AccountId = token.into();
+    most::add_pair(
+        session,
+        &most,
+        *token_address.as_ref(),
+        REMOTE_TOKEN,
+        false,
+        OWNER,
+    )
+    .expect("Add pair should succeed");
+
+    most::set_halted(session, &most, false, OWNER).expect("Unhalt should succeed");
+    token::transfer(session, &token, most.into(), 1000, BOB).expect("Transfer should succeed");
+
+    (most, token)
+}
+
+#[test]
+fn request_hash_matches_the_golden_vectors() {
+    let bytes32 = |hex: &str| -> [u8; 32] {
+        let hex = hex.trim_start_matches("0x");
+        let bytes: Vec<u8> = (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect();
+        bytes.try_into().unwrap()
+    };
+
+    let vectors: Vec<Vec<&str>> = include_str!("../../../../cfg/request_hash_vectors.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_whitespace().collect())
+        .filter(|fields: &Vec<&str>| fields[0] == "eth_to_azero")
+        .collect();
+    assert!(!vectors.is_empty());
+
+    for fields in vectors {
+        let request_hash = hash_request_data(
+            fields[1].parse().unwrap(),
+            bytes32(fields[2]).into(),
+            fields[3].parse().unwrap(),
+            bytes32(fields[4]).into(),
+            fields[5].parse().unwrap(),
+        );
+        assert_eq!(request_hash, bytes32(fields[6]), "{fields:?}");
+    }
+}
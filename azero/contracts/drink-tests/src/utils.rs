@@ -81,8 +81,11 @@ pub const DEFAULT_ETH_TRANSFER_GAS_USAGE: u128 = 60_000;
 
 pub mod most {
     use super::*;
-    use wrappers::most;
-    pub use wrappers::most::{Instance as Most, MostError};
+    use scale::Decode;
+    use wrappers::most::{self, Ownable2Step};
+    pub use wrappers::most::{
+        ConfigValue, Instance as Most, MostError, Ownable2StepError, RequestStatus,
+    };
 
     pub fn setup(
         session: &mut Session,
@@ -166,6 +169,21 @@ pub mod most {
         )
     }
 
+    pub fn set_weth(
+        session: &mut Session,
+        most: &Most,
+        weth: AccountId,
+        caller: drink::AccountId32,
+    ) -> Result<(), most::MostError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(most::Instance::set_weth(most, weth))
+                .unwrap(),
+        )
+    }
+
     pub fn set_halted(
         session: &mut Session,
         most: &Most,
@@ -181,6 +199,123 @@ pub mod most {
         )
     }
 
+    pub fn set_signatures_frozen(
+        session: &mut Session,
+        most: &Most,
+        frozen: bool,
+        caller: drink::AccountId32,
+    ) -> Result<(), most::MostError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(most::Instance::set_signatures_frozen(most, frozen))
+                .unwrap(),
+        )
+    }
+
+    pub fn set_max_pocket_money(
+        session: &mut Session,
+        most: &Most,
+        max_pocket_money: u128,
+        caller: drink::AccountId32,
+    ) -> Result<(), most::MostError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(most::Instance::set_max_pocket_money(most, max_pocket_money))
+                .unwrap(),
+        )
+    }
+
+    pub fn set_max_receive_amount(
+        session: &mut Session,
+        most: &Most,
+        dest_token_address: [u8; 32],
+        max_receive_amount: Option<u128>,
+        caller: drink::AccountId32,
+    ) -> Result<(), most::MostError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(most::Instance::set_max_receive_amount(
+                    most,
+                    dest_token_address,
+                    max_receive_amount,
+                ))
+                .unwrap(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn set_base_fee_constraints(
+        session: &mut Session,
+        most: &Most,
+        relay_gas_usage: u128,
+        min_gas_price: u128,
+        max_gas_price: u128,
+        default_gas_price: u128,
+        gas_oracle_max_age: u64,
+        oracle_call_gas_limit: u64,
+        base_fee_buffer_percentage: u128,
+        caller: drink::AccountId32,
+    ) -> Result<(), most::MostError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(most::Instance::set_base_fee_constraints(
+                    most,
+                    relay_gas_usage,
+                    min_gas_price,
+                    max_gas_price,
+                    default_gas_price,
+                    gas_oracle_max_age,
+                    oracle_call_gas_limit,
+                    base_fee_buffer_percentage,
+                ))
+                .unwrap(),
+        )
+    }
+
+    pub fn set_eth_transfer_gas_usage(
+        session: &mut Session,
+        most: &Most,
+        eth_transfer_gas_usage: u128,
+        caller: drink::AccountId32,
+    ) -> Result<(), most::MostError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(most::Instance::set_eth_transfer_gas_usage(
+                    most,
+                    eth_transfer_gas_usage,
+                ))
+                .unwrap(),
+        )
+    }
+
+    /// Returns `(field, old, new)` of every `ConfigChanged` event emitted by the last call
+    pub fn config_changed_events(
+        session: &mut Session,
+    ) -> Vec<(String, most::ConfigValue, most::ConfigValue)> {
+        session
+            .record()
+            .last_event_batch()
+            .contract_events()
+            .into_iter()
+            .filter_map(|data| match most::event::Event::decode(&mut &data[..]) {
+                Ok(most::event::Event::ConfigChanged { field, old, new }) => {
+                    Some((field, old, new))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
     pub fn send_request(
         session: &mut Session,
         most: &Most,
@@ -285,6 +420,25 @@ pub mod most {
         handle_ink_error(session.query(most::Instance::get_base_fee(&most)).unwrap())
     }
 
+    pub fn get_base_fee_breakdown(
+        session: &mut Session,
+        most: &Most,
+    ) -> Result<(u128, u128, u128, bool), MostError> {
+        handle_ink_error(
+            session
+                .query(most::Instance::get_base_fee_breakdown(&most))
+                .unwrap(),
+        )
+    }
+
+    pub fn get_net_bridged(session: &mut Session, most: &Most, token: AccountId) -> i128 {
+        handle_ink_error(
+            session
+                .query(most::Instance::get_net_bridged(&most, token))
+                .unwrap(),
+        )
+    }
+
     pub fn get_current_committee_id(session: &mut Session, most: &Most) -> Result<u128, MostError> {
         handle_ink_error(
             session
@@ -292,11 +446,79 @@ pub mod most {
                 .unwrap(),
         )
     }
+
+    pub fn get_committee_threshold(
+        session: &mut Session,
+        most: &Most,
+        committee_id: u128,
+    ) -> Option<u128> {
+        handle_ink_error(
+            session
+                .query(most::Instance::get_committee_threshold(&most, committee_id))
+                .unwrap(),
+        )
+    }
+
+    pub fn request_status(
+        session: &mut Session,
+        most: &Most,
+        request_hash: [u8; 32],
+    ) -> RequestStatus {
+        handle_ink_error(
+            session
+                .query(most::Instance::request_status(&most, request_hash))
+                .unwrap(),
+        )
+    }
+
+    pub fn get_owner(session: &mut Session, most: &Most) -> Result<AccountId, Ownable2StepError> {
+        handle_ink_error(session.query(Ownable2Step::get_owner(most)).unwrap())
+    }
+
+    pub fn get_pending_owner(
+        session: &mut Session,
+        most: &Most,
+    ) -> Result<AccountId, Ownable2StepError> {
+        handle_ink_error(
+            session
+                .query(Ownable2Step::get_pending_owner(most))
+                .unwrap(),
+        )
+    }
+
+    pub fn transfer_ownership(
+        session: &mut Session,
+        most: &Most,
+        new_owner: AccountId,
+        caller: drink::AccountId32,
+    ) -> Result<(), Ownable2StepError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(Ownable2Step::transfer_ownership(most, new_owner))
+                .unwrap(),
+        )
+    }
+
+    pub fn accept_ownership(
+        session: &mut Session,
+        most: &Most,
+        caller: drink::AccountId32,
+    ) -> Result<(), Ownable2StepError> {
+        let _ = session.set_actor(caller);
+
+        handle_ink_error(
+            session
+                .execute(Ownable2Step::accept_ownership(most))
+                .unwrap(),
+        )
+    }
 }
 
 pub mod token {
     use super::*;
-    use token::Instance as Token;
+    pub use token::Instance as Token;
     use wrappers::token::{self, PSP22};
 
     pub fn setup(
@@ -93,6 +93,22 @@ pub mod most_l2 {
         pub new_owner: AccountId,
     }
 
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct OwnershipTransferStarted {
+        pub previous_owner: AccountId,
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct OwnershipTransferred {
+        pub previous_owner: AccountId,
+        pub new_owner: AccountId,
+    }
+
     #[ink(event)]
     #[derive(Debug)]
     #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
@@ -778,6 +794,26 @@ pub mod most_l2 {
         }
     }
 
+    impl OwnershipTransferEvents for MostL2 {
+        fn emit_transfer_started(&self, previous_owner: AccountId, pending_owner: AccountId) {
+            self.env().emit_event(TransferOwnershipInitiated {
+                new_owner: pending_owner,
+            });
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner,
+                new_owner: pending_owner,
+            });
+        }
+
+        fn emit_transferred(&self, previous_owner: AccountId, new_owner: AccountId) {
+            self.env().emit_event(TransferOwnershipAccepted { new_owner });
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+        }
+    }
+
     impl Ownable2Step for MostL2 {
         #[ink(message)]
         fn get_owner(&self) -> Ownable2StepResult<AccountId> {
@@ -792,21 +828,27 @@ pub mod most_l2 {
         #[ink(message)]
         fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
             let mut ownable_data = self.ownable_data()?;
-            ownable_data.transfer_ownership(self.env().caller(), new_owner)?;
+            let transfer = ownable_data.transfer_ownership(self.env().caller(), new_owner)?;
             self.ownable_data.set(&ownable_data);
-            self.env()
-                .emit_event(TransferOwnershipInitiated { new_owner });
+            self.emit_ownership_transfer(transfer);
             Ok(())
         }
 
         #[ink(message)]
         fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
-            let new_owner = self.env().caller();
             let mut ownable_data = self.ownable_data()?;
-            ownable_data.accept_ownership(new_owner)?;
+            let transfer = ownable_data.accept_ownership(self.env().caller())?;
+            self.ownable_data.set(&ownable_data);
+            self.emit_ownership_transfer(transfer);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            let mut ownable_data = self.ownable_data()?;
+            let transfer = ownable_data.renounce_ownership(self.env().caller())?;
             self.ownable_data.set(&ownable_data);
-            self.env()
-                .emit_event(TransferOwnershipAccepted { new_owner });
+            self.emit_ownership_transfer(transfer);
             Ok(())
         }
 
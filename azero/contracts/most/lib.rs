@@ -93,6 +93,22 @@ pub mod most {
         pub new_owner: AccountId,
     }
 
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct OwnershipTransferStarted {
+        pub previous_owner: AccountId,
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct OwnershipTransferred {
+        pub previous_owner: AccountId,
+        pub new_owner: AccountId,
+    }
+
     #[ink(event)]
     #[derive(Debug)]
     #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
@@ -112,6 +128,25 @@ pub mod most {
         pub account: AccountId,
     }
 
+    /// Value of a parameter in [`ConfigChanged`]
+    #[derive(Debug, Encode, Decode, Clone, Copy, PartialEq, Eq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum ConfigValue {
+        Amount(u128),
+        Account(Option<AccountId>),
+        Flag(bool),
+    }
+
+    /// Emitted when the owner changes one of the parameters of the bridge
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct ConfigChanged {
+        pub field: String,
+        pub old: ConfigValue,
+        pub new: ConfigValue,
+    }
+
     #[derive(Default, Debug, Encode, Decode, Clone, Copy, PartialEq, Eq)]
     #[cfg_attr(
         feature = "std",
@@ -176,6 +211,8 @@ pub mod most {
         committees: Mapping<(CommitteeId, AccountId), (), ManualKey<0x434F4D4D>>,
         /// accounting helper
         committee_sizes: Mapping<CommitteeId, u128, ManualKey<0x53495A45>>,
+        /// committee members in the order they were set in, so that guardians can tell their position
+        committee_members: Mapping<CommitteeId, Vec<AccountId>, ManualKey<0x4D454D42>>,
         /// number of signatures required to reach a quorum and costsxecute a transfer
         signature_thresholds: Mapping<CommitteeId, u128, ManualKey<0x54485245>>,
         /// source - destination token pairs that can be transferred across the bridge
@@ -194,6 +231,12 @@ pub mod most {
         wazero: Lazy<AccountId, ManualKey<0x77617a65>>,
         /// How much gas does user use when requesting a bridging transfer.
         eth_transfer_gas_usage: Lazy<u128, ManualKey<0x6574685f>>,
+        /// Are guardians prevented from adding signatures to requests which are not one signature away from the threshold
+        signatures_frozen: Lazy<bool, ManualKey<0x66726f7a>>,
+        /// amount received through the bridge minus the amount sent through the bridge, per AlephZero token
+        net_bridged: Mapping<AccountId, i128, ManualKey<0x6e657462>>,
+        /// largest amount a single incoming request can transfer, per destination token. No limit if not set
+        max_receive_amounts: Mapping<[u8; 32], u128, ManualKey<0x6d617872>>,
     }
 
     #[derive(Debug, PartialEq, Eq, Encode, Decode)]
@@ -221,6 +264,11 @@ pub mod most {
         WrappedEthNotSet,
         WrappedAzeroNotSet,
         ValueTransferredLowerThanAmount,
+        SignaturesFrozen,
+        InvalidReceiver,
+        AmountAboveMaximum,
+        CommitteeMembersKnown,
+        InvalidCommitteeSize,
         Other,
     }
 
@@ -272,6 +320,9 @@ pub mod most {
             let mut committee_sizes = Mapping::new();
             committee_sizes.insert(committee_id, &(committee.len() as u128));
 
+            let mut committee_members = Mapping::new();
+            committee_members.insert(committee_id, &committee);
+
             let mut signature_thresholds = Mapping::new();
             signature_thresholds.insert(committee_id, &signature_threshold);
 
@@ -305,6 +356,7 @@ pub mod most {
                 signature_thresholds,
                 committees,
                 committee_sizes,
+                committee_members,
                 pending_requests: Mapping::new(),
                 signatures: Mapping::new(),
                 processed_requests: Mapping::new(),
@@ -316,6 +368,9 @@ pub mod most {
                 local_token: Mapping::new(),
                 wazero,
                 eth_transfer_gas_usage,
+                signatures_frozen: Lazy::new(),
+                net_bridged: Mapping::new(),
+                max_receive_amounts: Mapping::new(),
             })
         }
 
@@ -352,6 +407,8 @@ pub mod most {
                 self.transfer_from(src_token_address, sender, amount)?;
             } // if the transfer is done in native AZERO, then the tokens are already in the contract, so no action is needed
 
+            self.update_net_bridged(src_token_address, amount, false);
+
             let mut data = self.data()?;
             // NOTE: this allows the committee members to take a payout for requests that are not neccessarily finished
             // by that time (no signature threshold reached yet).
@@ -477,7 +534,9 @@ pub mod most {
                 .get(wrapped_azero_address_bytes)
                 .ok_or(MostError::UnsupportedPair)?;
 
-            let amount = amount_to_bridge.saturating_mul(RATIO);
+            let amount = amount_to_bridge
+                .checked_mul(RATIO)
+                .ok_or(MostError::Arithmetic)?;
 
             self._send_request(
                 wrapped_azero_address,
@@ -505,6 +564,11 @@ pub mod most {
             let caller = self.env().caller();
             self.only_committee_member(committee_id, caller)?;
 
+            // Minting or transferring to the zero account would burn the tokens for good
+            if dest_receiver_address == [0u8; 32] {
+                return Err(MostError::InvalidReceiver);
+            }
+
             // Don't revert if the request has already been processed as
             // such a call can be made during regular guardian operation.
             if self.processed_requests.contains(request_hash) {
@@ -535,8 +599,28 @@ pub mod most {
                 return Err(MostError::HashDoesNotMatchData);
             }
 
+            if self
+                .get_max_receive_amount(dest_token_address)
+                .is_some_and(|max_receive_amount| amount > max_receive_amount)
+            {
+                return Err(MostError::AmountAboveMaximum);
+            }
+
             let mut request = self.pending_requests.get(request_hash).unwrap_or_default();
 
+            let signature_threshold = self
+                .signature_thresholds
+                .get(committee_id)
+                .ok_or(MostError::InvalidThreshold)?;
+
+            // With frozen signatures only the last missing signature is accepted,
+            // so that the requests which already collected all the others can still be executed
+            if self.are_signatures_frozen()
+                && request.signature_count.saturating_add(1) < signature_threshold
+            {
+                return Err(MostError::SignaturesFrozen);
+            }
+
             // record vote
             request.signature_count = request
                 .signature_count
@@ -549,26 +633,27 @@ pub mod most {
                 request_hash,
             });
 
-            let signature_threshold = self
-                .signature_thresholds
-                .get(committee_id)
-                .ok_or(MostError::InvalidThreshold)?;
-
             if request.signature_count >= signature_threshold {
                 let is_local_token = self
                     .local_token
                     .contains::<AccountId>(dest_token_address.into());
 
                 if dest_token_address == NATIVE_MARKER_ADDRESS {
+                    let wrapped_azero_address =
+                        self.wazero.get().ok_or(MostError::WrappedAzeroNotSet)?;
+                    self.update_net_bridged(wrapped_azero_address, amount, true);
+
                     let amount = amount.saturating_div(RATIO);
                     self.unwrap_azero_to(dest_receiver_address.into(), amount)?;
                 } else if is_local_token {
+                    self.update_net_bridged(dest_token_address.into(), amount, true);
                     self.transfer(
                         dest_token_address.into(),
                         dest_receiver_address.into(),
                         amount,
                     )?;
                 } else {
+                    self.update_net_bridged(dest_token_address.into(), amount, true);
                     self.mint_to(
                         dest_token_address.into(),
                         dest_receiver_address.into(),
@@ -644,8 +729,13 @@ pub mod most {
             let max_pocket_money = data.max_pocket_money;
 
             // ~75% of the gas spend on eth side but no more than allowed max
-            let pocket_money =
-                u128::min(max_pocket_money, eth_transfer_gas_usage * gas_price * 3 / 4);
+            let pocket_money = u128::min(
+                max_pocket_money,
+                eth_transfer_gas_usage
+                    .saturating_mul(gas_price)
+                    .saturating_mul(3)
+                    / 4,
+            );
 
             u128::min(pocket_money, data.pocket_money_balance)
         }
@@ -704,6 +794,11 @@ pub mod most {
             self.ensure_owner()?;
             let mut data = self.data()?;
 
+            self.emit_config_changed(
+                "max_pocket_money",
+                data.max_pocket_money,
+                new_max_pocket_money,
+            );
             data.max_pocket_money = new_max_pocket_money;
             self.data.set(&data);
 
@@ -830,6 +925,20 @@ pub mod most {
             Ok(self.data()?.committee_id)
         }
 
+        /// Query signature threshold of the committee with `committee_id`, which may be a past committee
+        #[ink(message)]
+        pub fn get_committee_threshold(&self, committee_id: CommitteeId) -> Option<u128> {
+            self.signature_thresholds.get(committee_id)
+        }
+
+        /// Query the members of the committee with `committee_id`, in the order they were set in
+        ///
+        /// Empty for a committee which does not exist
+        #[ink(message)]
+        pub fn get_committee(&self, committee_id: CommitteeId) -> Vec<AccountId> {
+            self.committee_members.get(committee_id).unwrap_or_default()
+        }
+
         /// Query total rewards for this committee
         ///
         /// Denominated in AZERO
@@ -903,34 +1012,61 @@ pub mod most {
             Ok(None)
         }
 
-        fn get_gas_price_with_limits(&self) -> Result<Balance, MostError> {
+        /// Returns the gas price clamped to the configured limits and whether it comes from the oracle, as opposed to the default one
+        fn get_gas_price_with_limits(&self) -> Result<(Balance, bool), MostError> {
             let gas_price = match self.get_gas_price()? {
                 Some((gas_price, timestamp)) => {
-                    if timestamp + self.data()?.gas_oracle_max_age < self.env().block_timestamp() {
-                        self.data()?.default_gas_price
+                    if timestamp.saturating_add(self.data()?.gas_oracle_max_age)
+                        < self.env().block_timestamp()
+                    {
+                        (self.data()?.default_gas_price, false)
                     } else if gas_price < self.data()?.min_gas_price {
-                        self.data()?.min_gas_price
+                        (self.data()?.min_gas_price, true)
                     } else if gas_price > self.data()?.max_gas_price {
-                        self.data()?.max_gas_price
+                        (self.data()?.max_gas_price, true)
                     } else {
-                        gas_price
+                        (gas_price, true)
                     }
                 }
-                _ => self.data()?.default_gas_price,
+                _ => (self.data()?.default_gas_price, false),
             };
 
             Ok(gas_price)
         }
 
+        /// Query the components of the base fee: `(gas_price, relay_gas_usage, markup_bps, used_oracle)`
+        ///
+        /// The base fee equals `gas_price * relay_gas_usage * markup_bps / 10000`, rounded down.
+        /// `used_oracle` is `false` if the oracle is not set, not available or its price is outdated, in which case the default gas price is used.
+        #[ink(message)]
+        pub fn get_base_fee_breakdown(&self) -> Result<(u128, u128, u128, bool), MostError> {
+            let (gas_price, used_oracle) = self.get_gas_price_with_limits()?;
+            let markup_bps = 100u128
+                .checked_add(self.data()?.base_fee_buffer_percentage)
+                .and_then(|markup| markup.checked_mul(100))
+                .ok_or(MostError::Arithmetic)?;
+
+            Ok((
+                gas_price,
+                self.data()?.relay_gas_usage,
+                markup_bps,
+                used_oracle,
+            ))
+        }
+
         /// Queries a gas price oracle and returns the current base_fee charged per cross chain transfer denominated in AZERO
         #[ink(message)]
         pub fn get_base_fee(&self) -> Result<Balance, MostError> {
-            let gas_price = self.get_gas_price_with_limits()?;
+            let (gas_price, _) = self.get_gas_price_with_limits()?;
 
             let base_fee = gas_price
                 .checked_mul(self.data()?.relay_gas_usage)
                 .ok_or(MostError::Arithmetic)?
-                .checked_mul(100u128 + self.data()?.base_fee_buffer_percentage)
+                .checked_mul(
+                    100u128
+                        .checked_add(self.data()?.base_fee_buffer_percentage)
+                        .ok_or(MostError::Arithmetic)?,
+                )
                 .ok_or(MostError::Arithmetic)?
                 .checked_div(100u128)
                 .ok_or(MostError::Arithmetic)?;
@@ -992,6 +1128,26 @@ pub mod most {
             Ok(self.data()?.is_halted)
         }
 
+        /// Amount of the token received through the bridge minus the amount sent through the bridge.
+        ///
+        /// For tokens originating on Ethereum it should never exceed the amount locked in the Ethereum contract.
+        #[ink(message)]
+        pub fn get_net_bridged(&self, token: AccountId) -> i128 {
+            self.net_bridged.get(token).unwrap_or(0)
+        }
+
+        /// Largest amount of the token a single incoming request can transfer, `None` if there is no limit
+        #[ink(message)]
+        pub fn get_max_receive_amount(&self, dest_token_address: [u8; 32]) -> Option<u128> {
+            self.max_receive_amounts.get(dest_token_address)
+        }
+
+        /// Are guardians prevented from collecting new signatures?
+        #[ink(message)]
+        pub fn are_signatures_frozen(&self) -> bool {
+            self.signatures_frozen.get().unwrap_or(false)
+        }
+
         /// Returns the status of a given cross-chain transfer request
         #[ink(message)]
         pub fn request_status(&self, hashed_request: HashedRequest) -> RequestStatus {
@@ -1024,6 +1180,31 @@ pub mod most {
         ) -> Result<(), MostError> {
             self.ensure_owner()?;
             let mut data = self.data()?;
+
+            self.emit_config_changed("relay_gas_usage", data.relay_gas_usage, relay_gas_usage);
+            self.emit_config_changed("min_gas_price", data.min_gas_price, min_gas_price);
+            self.emit_config_changed("max_gas_price", data.max_gas_price, max_gas_price);
+            self.emit_config_changed(
+                "default_gas_price",
+                data.default_gas_price,
+                default_gas_price,
+            );
+            self.emit_config_changed(
+                "gas_oracle_max_age",
+                data.gas_oracle_max_age.into(),
+                gas_oracle_max_age.into(),
+            );
+            self.emit_config_changed(
+                "oracle_call_gas_limit",
+                data.oracle_call_gas_limit.into(),
+                oracle_call_gas_limit.into(),
+            );
+            self.emit_config_changed(
+                "base_fee_buffer_percentage",
+                data.base_fee_buffer_percentage,
+                base_fee_buffer_percentage,
+            );
+
             data.relay_gas_usage = relay_gas_usage;
             data.min_gas_price = min_gas_price;
             data.max_gas_price = max_gas_price;
@@ -1081,6 +1262,11 @@ pub mod most {
         pub fn set_weth(&mut self, weth_address: AccountId) -> Result<(), MostError> {
             self.ensure_owner()?;
             self.ensure_halted()?;
+            self.emit_config_value_changed(
+                "weth",
+                ConfigValue::Account(self.weth.get()),
+                ConfigValue::Account(Some(weth_address)),
+            );
             self.weth.set(&weth_address);
             Ok(())
         }
@@ -1092,6 +1278,11 @@ pub mod most {
         pub fn set_wazero(&mut self, wazero_address: AccountId) -> Result<(), MostError> {
             self.ensure_owner()?;
             self.ensure_halted()?;
+            self.emit_config_value_changed(
+                "wazero",
+                ConfigValue::Account(self.wazero.get()),
+                ConfigValue::Account(Some(wazero_address)),
+            );
             self.wazero.set(&wazero_address);
             Ok(())
         }
@@ -1129,6 +1320,11 @@ pub mod most {
         ) -> Result<(), MostError> {
             self.ensure_owner()?;
             let mut data = self.data()?;
+            self.emit_config_value_changed(
+                "gas_price_oracle",
+                ConfigValue::Account(data.gas_price_oracle),
+                ConfigValue::Account(Some(gas_price_oracle)),
+            );
             data.gas_price_oracle = Some(gas_price_oracle);
             self.data.set(&data);
             Ok(())
@@ -1143,6 +1339,11 @@ pub mod most {
             new_eth_transfer_gas_usage: u128,
         ) -> Result<(), crate::most::MostError> {
             self.ensure_owner()?;
+            self.emit_config_changed(
+                "eth_transfer_gas_usage",
+                self.eth_transfer_gas_usage.get().unwrap_or_default(),
+                new_eth_transfer_gas_usage,
+            );
             self.eth_transfer_gas_usage.set(&new_eth_transfer_gas_usage);
             Ok(())
         }
@@ -1163,9 +1364,13 @@ pub mod most {
 
             let mut data = self.data()?;
 
-            let committee_id = data.committee_id + 1;
+            let committee_id = data
+                .committee_id
+                .checked_add(1)
+                .ok_or(MostError::Arithmetic)?;
             self.committee_sizes
                 .insert(committee_id, &(committee.len() as u128));
+            self.committee_members.insert(committee_id, &committee);
             let mut committee_set = Mapping::new();
             committee.into_iter().for_each(|account| {
                 committee_set.insert((committee_id, account), &());
@@ -1182,6 +1387,47 @@ pub mod most {
             Ok(())
         }
 
+        /// Records the ordered members of a committee set before the contract kept them, e.g. one upgraded with `set_code`
+        /// Can only be called by the contracts owner
+        ///
+        /// `committee` has to be exactly the member set of the committee, and can be recorded only once
+        #[ink(message)]
+        pub fn backfill_committee_members(
+            &mut self,
+            committee_id: CommitteeId,
+            committee: Vec<AccountId>,
+        ) -> Result<(), MostError> {
+            self.ensure_owner()?;
+
+            if self.committee_members.contains(committee_id) {
+                return Err(MostError::CommitteeMembersKnown);
+            }
+
+            let size = self
+                .committee_sizes
+                .get(committee_id)
+                .ok_or(MostError::NoSuchCommittee)?;
+            let threshold = self
+                .signature_thresholds
+                .get(committee_id)
+                .ok_or(MostError::CorruptedStorage)?;
+            Self::check_committee(&committee, threshold)?;
+
+            if committee.len() as u128 != size {
+                return Err(MostError::InvalidCommitteeSize);
+            }
+            if !committee
+                .iter()
+                .all(|account| self.committees.contains((committee_id, *account)))
+            {
+                return Err(MostError::NotInCommittee);
+            }
+
+            self.committee_members.insert(committee_id, &committee);
+
+            Ok(())
+        }
+
         /// Halt/resume the bridge contract
         ///
         /// Can only be called by the contracts owner
@@ -1206,6 +1452,51 @@ pub mod most {
             Ok(())
         }
 
+        /// Freeze/unfreeze collecting of new signatures
+        ///
+        /// While frozen, the contract only accepts a signature which makes a request reach the signature threshold.
+        /// Can only be called by the contracts owner
+        #[ink(message)]
+        pub fn set_signatures_frozen(&mut self, frozen: bool) -> Result<(), MostError> {
+            self.ensure_owner()?;
+            self.emit_config_value_changed(
+                "signatures_frozen",
+                ConfigValue::Flag(self.signatures_frozen.get().unwrap_or(false)),
+                ConfigValue::Flag(frozen),
+            );
+            self.signatures_frozen.set(&frozen);
+            Ok(())
+        }
+
+        /// Sets the largest amount of the token a single incoming request can transfer, `None` removes the limit.
+        ///
+        /// Can only be called by the contracts owner
+        #[ink(message)]
+        pub fn set_max_receive_amount(
+            &mut self,
+            dest_token_address: [u8; 32],
+            max_receive_amount: Option<u128>,
+        ) -> Result<(), MostError> {
+            self.ensure_owner()?;
+
+            // no limit is reported as the largest possible amount
+            self.emit_config_changed(
+                "max_receive_amount",
+                self.get_max_receive_amount(dest_token_address)
+                    .unwrap_or(u128::MAX),
+                max_receive_amount.unwrap_or(u128::MAX),
+            );
+            match max_receive_amount {
+                Some(max_receive_amount) => {
+                    self.max_receive_amounts
+                        .insert(dest_token_address, &max_receive_amount);
+                }
+                None => self.max_receive_amounts.remove(dest_token_address),
+            }
+
+            Ok(())
+        }
+
         /// Transfer PSP22 tokens from the bridge contract to a given account.
         ///
         /// Can only be called by the contracts owner
@@ -1254,6 +1545,36 @@ pub mod most {
             }
         }
 
+        fn emit_config_changed(&self, field: &str, old: u128, new: u128) {
+            self.emit_config_value_changed(
+                field,
+                ConfigValue::Amount(old),
+                ConfigValue::Amount(new),
+            );
+        }
+
+        fn emit_config_value_changed(&self, field: &str, old: ConfigValue, new: ConfigValue) {
+            if old != new {
+                self.env().emit_event(ConfigChanged {
+                    field: field.into(),
+                    old,
+                    new,
+                });
+            }
+        }
+
+        /// Saturates instead of failing, the reconciliation counter must never block a transfer
+        fn update_net_bridged(&mut self, token: AccountId, amount: u128, received: bool) {
+            let amount = i128::try_from(amount).unwrap_or(i128::MAX);
+            let net_bridged = self.get_net_bridged(token);
+            let net_bridged = match received {
+                true => net_bridged.saturating_add(amount),
+                false => net_bridged.saturating_sub(amount),
+            };
+
+            self.net_bridged.insert(token, &net_bridged);
+        }
+
         fn check_committee(committee: &[AccountId], threshold: u128) -> Result<(), MostError> {
             if threshold == 0 || committee.len().lt(&(threshold as usize)) {
                 return Err(MostError::InvalidThreshold);
@@ -1335,6 +1656,26 @@ pub mod most {
         }
     }
 
+    impl OwnershipTransferEvents for Most {
+        fn emit_transfer_started(&self, previous_owner: AccountId, pending_owner: AccountId) {
+            self.env().emit_event(TransferOwnershipInitiated {
+                new_owner: pending_owner,
+            });
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner,
+                new_owner: pending_owner,
+            });
+        }
+
+        fn emit_transferred(&self, previous_owner: AccountId, new_owner: AccountId) {
+            self.env().emit_event(TransferOwnershipAccepted { new_owner });
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+        }
+    }
+
     impl Ownable2Step for Most {
         #[ink(message)]
         fn get_owner(&self) -> Ownable2StepResult<AccountId> {
@@ -1349,21 +1690,27 @@ pub mod most {
         #[ink(message)]
         fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
             let mut ownable_data = self.ownable_data()?;
-            ownable_data.transfer_ownership(self.env().caller(), new_owner)?;
+            let transfer = ownable_data.transfer_ownership(self.env().caller(), new_owner)?;
             self.ownable_data.set(&ownable_data);
-            self.env()
-                .emit_event(TransferOwnershipInitiated { new_owner });
+            self.emit_ownership_transfer(transfer);
             Ok(())
         }
 
         #[ink(message)]
         fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
-            let new_owner = self.env().caller();
             let mut ownable_data = self.ownable_data()?;
-            ownable_data.accept_ownership(new_owner)?;
+            let transfer = ownable_data.accept_ownership(self.env().caller())?;
             self.ownable_data.set(&ownable_data);
-            self.env()
-                .emit_event(TransferOwnershipAccepted { new_owner });
+            self.emit_ownership_transfer(transfer);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            let mut ownable_data = self.ownable_data()?;
+            let transfer = ownable_data.renounce_ownership(self.env().caller())?;
+            self.ownable_data.set(&ownable_data);
+            self.emit_ownership_transfer(transfer);
             Ok(())
         }
 
@@ -1459,6 +1806,48 @@ pub mod most {
             );
         }
 
+        #[ink::test]
+        fn base_fee_overflow_returns_error() {
+            let alice = default_accounts::<DefEnv>().alice;
+            set_caller::<DefEnv>(alice);
+
+            let most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                u128::MAX,
+                u128::MAX - 1,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            assert_eq!(most.get_base_fee(), Err(MostError::Arithmetic));
+
+            let most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                1,
+                1,
+                1,
+                1,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                u128::MAX - 1,
+                None,
+                alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            assert_eq!(most.get_base_fee(), Err(MostError::Arithmetic));
+        }
+
         #[ink::test]
         fn new_sets_caller_as_owner() {
             let alice = default_accounts::<DefEnv>().alice;
@@ -1563,6 +1952,141 @@ pub mod most {
             assert_eq!(most.ensure_owner(), Ok(()));
         }
 
+        #[ink::test]
+        fn ownership_transfer_emits_both_stages() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            assert_eq!(most.transfer_ownership(accounts.bob), Ok(()));
+            set_caller::<DefEnv>(accounts.bob);
+            assert_eq!(most.accept_ownership(), Ok(()));
+
+            let events: Vec<_> = ink::env::test::recorded_events()
+                .map(|event| <Event as Decode>::decode(&mut &event.data[..]).expect("ink event"))
+                .collect();
+            let [.., Event::TransferOwnershipInitiated(initiated), Event::OwnershipTransferStarted(started), Event::TransferOwnershipAccepted(accepted), Event::OwnershipTransferred(transferred)] =
+                &events[..]
+            else {
+                panic!(
+                    "no ownership transfer events in {} recorded events",
+                    events.len()
+                );
+            };
+            assert_eq!(
+                initiated,
+                &TransferOwnershipInitiated {
+                    new_owner: accounts.bob,
+                }
+            );
+            assert_eq!(
+                started,
+                &OwnershipTransferStarted {
+                    previous_owner: accounts.alice,
+                    new_owner: accounts.bob,
+                }
+            );
+            assert_eq!(
+                accepted,
+                &TransferOwnershipAccepted {
+                    new_owner: accounts.bob,
+                }
+            );
+            assert_eq!(
+                transferred,
+                &OwnershipTransferred {
+                    previous_owner: accounts.alice,
+                    new_owner: accounts.bob,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn owner_can_renounce_ownership() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            assert_eq!(most.transfer_ownership(accounts.bob), Ok(()));
+            assert_eq!(most.renounce_ownership(), Ok(()));
+
+            assert_eq!(most.get_owner(), Ok(renounced_owner()));
+            assert_eq!(
+                most.get_pending_owner(),
+                Err(Ownable2StepError::NoPendingOwner)
+            );
+            assert_eq!(
+                most.ensure_owner(),
+                Err(Ownable2StepError::CallerNotOwner(accounts.alice))
+            );
+            // the cancelled transfer can not be accepted anymore
+            set_caller::<DefEnv>(accounts.bob);
+            assert_eq!(
+                most.accept_ownership(),
+                Err(Ownable2StepError::NoPendingOwner)
+            );
+        }
+
+        #[ink::test]
+        fn non_owner_cannot_renounce_ownership() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            set_caller::<DefEnv>(accounts.bob);
+
+            assert_eq!(
+                most.renounce_ownership(),
+                Err(Ownable2StepError::CallerNotOwner(accounts.bob))
+            );
+            assert_eq!(most.get_owner(), Ok(accounts.alice));
+        }
+
         #[ink::test]
         fn add_guardian_works() {
             let accounts = default_accounts::<DefEnv>();
@@ -1591,6 +2115,160 @@ pub mod most {
             assert!(most.is_in_committee(most.get_current_committee_id().unwrap(), accounts.alice));
         }
 
+        #[ink::test]
+        fn past_committee_threshold_is_kept() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+
+            assert_eq!(most.set_committee(vec![accounts.alice], 1), Ok(()));
+
+            assert_eq!(most.get_committee_threshold(0), Some(THRESHOLD));
+            assert_eq!(most.get_committee_threshold(1), Some(1));
+            assert_eq!(most.get_committee_threshold(2), None);
+        }
+
+        #[ink::test]
+        fn committee_members_are_kept_in_order() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+
+            assert_eq!(
+                most.set_committee(vec![accounts.bob, accounts.alice], 1),
+                Ok(())
+            );
+
+            assert_eq!(most.get_committee(0), guardian_accounts());
+            assert_eq!(most.get_committee(1), vec![accounts.bob, accounts.alice]);
+            assert_eq!(most.get_committee(2), vec![]);
+        }
+
+        #[ink::test]
+        fn net_bridged_saturates() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            let token = accounts.django;
+
+            most.update_net_bridged(token, u128::MAX, true);
+            assert_eq!(most.get_net_bridged(token), i128::MAX);
+            most.update_net_bridged(token, 1, true);
+            assert_eq!(most.get_net_bridged(token), i128::MAX);
+
+            most.update_net_bridged(token, u128::MAX, false);
+            assert_eq!(most.get_net_bridged(token), 0);
+            most.update_net_bridged(token, u128::MAX, false);
+            most.update_net_bridged(token, u128::MAX, false);
+            assert_eq!(most.get_net_bridged(token), i128::MIN);
+        }
+
+        #[ink::test]
+        fn committee_members_are_backfilled_after_upgrade() {
+            let accounts = default_accounts::<DefEnv>();
+            set_caller::<DefEnv>(accounts.alice);
+            let mut most = Most::new(
+                guardian_accounts(),
+                THRESHOLD,
+                MAX_POCKET_MONEY,
+                RELAY_GAS_USAGE,
+                MIN_FEE,
+                MAX_FEE,
+                DEFAULT_FEE,
+                GAS_ORACLE_MAX_AGE,
+                ORACLE_CALL_GAS_LIMIT,
+                BASE_FEE_BUFFER_PERCENTAGE,
+                None,
+                accounts.alice,
+                ETH_GAS_USAGE,
+            )
+            .expect("Threshold is valid.");
+            // a committee set by code which did not record its members
+            most.committee_members.remove(0);
+            assert_eq!(most.get_committee(0), vec![]);
+
+            let mut committee = guardian_accounts();
+            committee.reverse();
+            assert_eq!(
+                most.backfill_committee_members(0, committee[1..].to_vec()),
+                Err(MostError::InvalidCommitteeSize)
+            );
+            let mut strangers = committee.clone();
+            strangers[0] = AccountId::from([0xAB; 32]);
+            assert_eq!(
+                most.backfill_committee_members(0, strangers),
+                Err(MostError::NotInCommittee)
+            );
+            assert_eq!(
+                most.backfill_committee_members(1, committee.clone()),
+                Err(MostError::NoSuchCommittee)
+            );
+
+            set_caller::<DefEnv>(accounts.bob);
+            assert_eq!(
+                most.backfill_committee_members(0, committee.clone()),
+                Err(MostError::Ownable(Ownable2StepError::CallerNotOwner(
+                    accounts.bob
+                )))
+            );
+
+            set_caller::<DefEnv>(accounts.alice);
+            assert_eq!(
+                most.backfill_committee_members(0, committee.clone()),
+                Ok(())
+            );
+            assert_eq!(most.get_committee(0), committee);
+            assert_eq!(
+                most.backfill_committee_members(0, guardian_accounts()),
+                Err(MostError::CommitteeMembersKnown)
+            );
+        }
+
         #[ink::test]
         fn remove_guardian_works() {
             let accounts = default_accounts::<DefEnv>();
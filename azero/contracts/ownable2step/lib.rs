@@ -11,7 +11,10 @@
 /// * `accept_owership`: callable only by the pending owner, removes the previous owner and makes them the sole owner of the contract
 /// * `get_pending_owner`: returns the pending owner, if the ownership change process is currently underway.  
 ///
+/// Finally, `renounce_ownership` lets the owner give up the ownership for good, handing it to [`renounced_owner`].
+///
 /// In order to use it in your contract, implement the methods of the `Ownable2Step` trait: in most cases, you can simply call the corresponding methods on the `Data` object.
+/// The ownership changing methods of `Data` return the `OwnershipTransfer` stage they reached, which the contract emits as its own events by implementing `OwnershipTransferEvents`.
 use ink::{prelude::string::String, primitives::AccountId};
 use scale::{Decode, Encode};
 
@@ -30,6 +33,46 @@ pub enum Ownable2StepError {
 
 pub type Ownable2StepResult<T> = Result<T, Ownable2StepError>;
 
+/// Owner of a contract whose ownership was renounced, an account no one holds the key of
+pub fn renounced_owner() -> AccountId {
+    AccountId::from([0; 32])
+}
+
+/// Stage of the ownership transfer reached by a call, for the contract to emit as its own event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnershipTransfer {
+    /// The owner appointed the pending owner
+    Started {
+        previous_owner: AccountId,
+        pending_owner: AccountId,
+    },
+    /// The pending owner accepted the ownership
+    Transferred {
+        previous_owner: AccountId,
+        new_owner: AccountId,
+    },
+}
+
+/// Emits the stages of an ownership transfer as events of the implementing contract
+pub trait OwnershipTransferEvents {
+    fn emit_transfer_started(&self, previous_owner: AccountId, pending_owner: AccountId);
+
+    fn emit_transferred(&self, previous_owner: AccountId, new_owner: AccountId);
+
+    fn emit_ownership_transfer(&self, transfer: OwnershipTransfer) {
+        match transfer {
+            OwnershipTransfer::Started {
+                previous_owner,
+                pending_owner,
+            } => self.emit_transfer_started(previous_owner, pending_owner),
+            OwnershipTransfer::Transferred {
+                previous_owner,
+                new_owner,
+            } => self.emit_transferred(previous_owner, new_owner),
+        }
+    }
+}
+
 #[derive(Debug)]
 #[ink::storage_item]
 pub struct Ownable2StepData {
@@ -49,13 +92,16 @@ impl Ownable2StepData {
         &mut self,
         caller: AccountId,
         new_owner: AccountId,
-    ) -> Ownable2StepResult<()> {
+    ) -> Ownable2StepResult<OwnershipTransfer> {
         self.ensure_owner(caller)?;
         self.pending_owner = Some(new_owner);
-        Ok(())
+        Ok(OwnershipTransfer::Started {
+            previous_owner: self.owner,
+            pending_owner: new_owner,
+        })
     }
 
-    pub fn accept_ownership(&mut self, caller: AccountId) -> Ownable2StepResult<()> {
+    pub fn accept_ownership(&mut self, caller: AccountId) -> Ownable2StepResult<OwnershipTransfer> {
         let pending_owner = self
             .pending_owner
             .ok_or(Ownable2StepError::NoPendingOwner)?;
@@ -64,10 +110,29 @@ impl Ownable2StepData {
             return Err(Ownable2StepError::CallerNotPendingOwner(caller));
         }
 
+        let previous_owner = self.owner;
         self.owner = pending_owner;
         self.pending_owner = None;
 
-        Ok(())
+        Ok(OwnershipTransfer::Transferred {
+            previous_owner,
+            new_owner: pending_owner,
+        })
+    }
+
+    /// Hands the ownership to [`renounced_owner`], cancelling the pending transfer if there is one
+    pub fn renounce_ownership(
+        &mut self,
+        caller: AccountId,
+    ) -> Ownable2StepResult<OwnershipTransfer> {
+        self.ensure_owner(caller)?;
+        self.owner = renounced_owner();
+        self.pending_owner = None;
+
+        Ok(OwnershipTransfer::Transferred {
+            previous_owner: caller,
+            new_owner: self.owner,
+        })
     }
 
     pub fn get_owner(&self) -> Ownable2StepResult<AccountId> {
@@ -114,6 +179,13 @@ pub trait Ownable2Step {
     #[ink(message)]
     fn accept_ownership(&mut self) -> Ownable2StepResult<()>;
 
+    /// Gives up the ownership of the contract for good, cancelling the pending transfer if there is one.
+    /// Can only be called by the current owner.
+    ///
+    /// Afterwards no one is the owner, so all the methods guarded by `ensure_owner` can not be called anymore.
+    #[ink(message)]
+    fn renounce_ownership(&mut self) -> Ownable2StepResult<()>;
+
     /// Return error if called by any account other than the owner.
     #[ink(message)]
     fn ensure_owner(&self) -> Ownable2StepResult<()>;
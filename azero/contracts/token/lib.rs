@@ -45,6 +45,22 @@ pub mod token {
         pub new_owner: AccountId,
     }
 
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct OwnershipTransferStarted {
+        pub previous_owner: AccountId,
+        pub new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    #[derive(Debug)]
+    #[cfg_attr(feature = "std", derive(Eq, PartialEq))]
+    pub struct OwnershipTransferred {
+        pub previous_owner: AccountId,
+        pub new_owner: AccountId,
+    }
+
     #[ink(storage)]
     pub struct Token {
         data: PSP22Data,
@@ -260,6 +276,26 @@ pub mod token {
         }
     }
 
+    impl OwnershipTransferEvents for Token {
+        fn emit_transfer_started(&self, previous_owner: AccountId, pending_owner: AccountId) {
+            self.env().emit_event(TransferOwnershipInitiated {
+                new_owner: pending_owner,
+            });
+            self.env().emit_event(OwnershipTransferStarted {
+                previous_owner,
+                new_owner: pending_owner,
+            });
+        }
+
+        fn emit_transferred(&self, previous_owner: AccountId, new_owner: AccountId) {
+            self.env().emit_event(TransferOwnershipAccepted { new_owner });
+            self.env().emit_event(OwnershipTransferred {
+                previous_owner,
+                new_owner,
+            });
+        }
+    }
+
     impl Ownable2Step for Token {
         #[ink(message)]
         fn get_owner(&self) -> Ownable2StepResult<AccountId> {
@@ -273,19 +309,24 @@ pub mod token {
 
         #[ink(message)]
         fn transfer_ownership(&mut self, new_owner: AccountId) -> Ownable2StepResult<()> {
-            self.ownable_data
+            let transfer = self
+                .ownable_data
                 .transfer_ownership(self.env().caller(), new_owner)?;
-            self.env()
-                .emit_event(TransferOwnershipInitiated { new_owner });
+            self.emit_ownership_transfer(transfer);
             Ok(())
         }
 
         #[ink(message)]
         fn accept_ownership(&mut self) -> Ownable2StepResult<()> {
-            let new_owner = self.env().caller();
-            self.ownable_data.accept_ownership(new_owner)?;
-            self.env()
-                .emit_event(TransferOwnershipAccepted { new_owner });
+            let transfer = self.ownable_data.accept_ownership(self.env().caller())?;
+            self.emit_ownership_transfer(transfer);
+            Ok(())
+        }
+
+        #[ink(message)]
+        fn renounce_ownership(&mut self) -> Ownable2StepResult<()> {
+            let transfer = self.ownable_data.renounce_ownership(self.env().caller())?;
+            self.emit_ownership_transfer(transfer);
             Ok(())
         }
 
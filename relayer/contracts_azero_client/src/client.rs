@@ -3,7 +3,7 @@ use std::{
     time::Duration,
 };
 
-use log::trace;
+use log::{debug, trace, warn};
 use pallet_contracts::ContractExecResult;
 use parity_scale_codec::Decode;
 use subxt::{
@@ -11,7 +11,7 @@ use subxt::{
         legacy::LegacyRpcMethods,
         rpc::reconnecting_rpc_client::{Client as RpcClient, ExponentialBackoff},
     },
-    config::DefaultExtrinsicParamsBuilder,
+    config::{DefaultExtrinsicParams, DefaultExtrinsicParamsBuilder, ExtrinsicParams},
     dynamic::Value,
     error::RpcError,
     ext::scale_value::value,
@@ -28,6 +28,26 @@ use crate::{
 
 const LOG_TARGET: &str = "AzeroClient";
 
+/// Number of blocks a signed extrinsic stays valid for, unless configured otherwise with [`ClientWithSigner::with_mortality`]
+pub const DEFAULT_MORTALITY_BLOCKS: u64 = 64;
+
+/// Block from which the mortality of an extrinsic is counted
+#[derive(Debug, Clone, Copy)]
+struct Checkpoint {
+    number: u64,
+    hash: BlockHash,
+}
+
+type TxParams = <DefaultExtrinsicParams<PolkadotConfig> as ExtrinsicParams<PolkadotConfig>>::Params;
+
+/// Params of an extrinsic with `nonce`, valid for `mortality_blocks` from the `checkpoint` on
+fn tx_params(nonce: u64, checkpoint: Checkpoint, mortality_blocks: u64) -> TxParams {
+    DefaultExtrinsicParamsBuilder::default()
+        .nonce(nonce)
+        .mortal_unchecked(checkpoint.number, checkpoint.hash, mortality_blocks)
+        .build()
+}
+
 fn get_args_for_runtime_call(args: ContractCallArgs) -> Vec<Value> {
     let gas_limit = match args.gas_limit {
         Some(w) => Value::unnamed_variant(
@@ -81,6 +101,18 @@ pub enum ClientError {
     Internal,
 }
 
+impl ClientError {
+    /// Whether the call failed as the connection to the node dropped. The client reconnects on its own,
+    /// so the call may be made again
+    pub fn is_disconnected(&self) -> bool {
+        matches!(
+            self,
+            Self::Rpc(RpcError::DisconnectedWillReconnect(_))
+                | Self::Subxt(Error::Rpc(RpcError::DisconnectedWillReconnect(_)))
+        )
+    }
+}
+
 pub type ClientResult<T> = Result<T, ClientError>;
 
 pub struct ClientConfig {
@@ -135,6 +167,13 @@ impl Client {
             .map(|d| d.block.header.number))
     }
 
+    pub async fn get_block_hash(&self, block_number: u32) -> ClientResult<Option<BlockHash>> {
+        Ok(self
+            .methods
+            .chain_get_block_hash(Some(block_number.into()))
+            .await?)
+    }
+
     pub async fn with_signer<S: Signer>(&self, signer: S) -> ClientResult<ClientWithSigner<S>> {
         ClientWithSigner::new(self.clone(), signer).await
     }
@@ -179,7 +218,10 @@ impl Client {
         let events_from_block = translate_events(events.iter(), contracts)
             .into_iter()
             .filter_map(|e| match e {
-                Ok(event) => Some(event),
+                Ok(event) => Some(ContractEvent {
+                    block_number: Some(at_block),
+                    ..event
+                }),
                 Err(error) => {
                     trace!(target: LOG_TARGET, "Decode event failed, {:?}", error);
                     None
@@ -195,6 +237,7 @@ pub struct ClientWithSigner<S: Signer> {
     client: Client,
     signer: S,
     nonce: AtomicU64,
+    mortality_blocks: u64,
 }
 
 impl<S: Signer> ClientWithSigner<S> {
@@ -205,16 +248,39 @@ impl<S: Signer> ClientWithSigner<S> {
             client,
             signer,
             nonce: AtomicU64::new(nonce),
+            mortality_blocks: DEFAULT_MORTALITY_BLOCKS,
         })
     }
+
+    /// Sets the number of blocks after which a submitted extrinsic expires, if not included.
+    ///
+    /// It is rounded up to a power of two between 4 and 65536 when encoded into the era of the extrinsic.
+    pub fn with_mortality(mut self, mortality_blocks: u64) -> Self {
+        self.mortality_blocks = mortality_blocks;
+        self
+    }
+
+    async fn get_checkpoint(&self) -> ClientResult<Checkpoint> {
+        let hash = self.client.get_finalized_block_hash().await?;
+        let number = self
+            .client
+            .get_block_number(hash)
+            .await?
+            .ok_or(ClientError::Internal)?;
+
+        Ok(Checkpoint {
+            number: number.into(),
+            hash,
+        })
+    }
+
     fn get_tx<Call: Payload>(
         &self,
         call: &Call,
+        checkpoint: Checkpoint,
     ) -> ClientResult<PartialExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
         let nonce = self.nonce.load(Ordering::Relaxed);
-        let params = DefaultExtrinsicParamsBuilder::default()
-            .nonce(nonce)
-            .build();
+        let params = tx_params(nonce, checkpoint, self.mortality_blocks);
 
         let tx = self
             .client
@@ -225,11 +291,15 @@ impl<S: Signer> ClientWithSigner<S> {
         Ok(tx)
     }
 
-    async fn sign_call<Call: Payload>(&self, call: &Call) -> ClientResult<MultiSignature> {
+    async fn sign_call<Call: Payload>(
+        &self,
+        call: &Call,
+        checkpoint: Checkpoint,
+    ) -> ClientResult<MultiSignature> {
         // PartialExtrinsic is not Send when Call is of type DynamicPayload,
         // so it cant live past any await.
         let payload = {
-            let tx = self.get_tx(call)?;
+            let tx = self.get_tx(call, checkpoint)?;
             tx.signer_payload()
         };
 
@@ -246,10 +316,18 @@ impl<S: Signer> ClientWithSigner<S> {
         &self,
         call: Call,
     ) -> ClientResult<SubmittableExtrinsic<PolkadotConfig, OnlineClient<PolkadotConfig>>> {
-        let signature = self.sign_call(&call).await?;
+        // the same checkpoint has to be used for signing and for the submitted extrinsic
+        let checkpoint = self.get_checkpoint().await?;
+        trace!(
+            target: LOG_TARGET,
+            "Signing extrinsic at checkpoint {checkpoint:?}, valid for {} blocks",
+            self.mortality_blocks
+        );
+
+        let signature = self.sign_call(&call, checkpoint).await?;
         let address = MultiAddress::Id(self.signer.account_id().clone());
 
-        let extr = self.get_tx(&call)?;
+        let extr = self.get_tx(&call, checkpoint)?;
 
         Ok(extr.sign_with_address_and_signature(&address, &signature))
     }
@@ -261,13 +339,42 @@ impl<S: Signer> ClientWithSigner<S> {
         let tx = self.get_submittable(tx).await?;
         self.inc_nonce();
 
-        let events = tx
-            .submit_and_watch()
-            .await?
-            .wait_for_finalized_success()
-            .await?;
+        let events = async {
+            tx.submit_and_watch()
+                .await?
+                .wait_for_finalized_success()
+                .await
+        };
+
+        match events.await {
+            Ok(events) => Ok(events.into()),
+            Err(why) => {
+                // an extrinsic which was not submitted or expired did not use up its nonce,
+                // which would leave the local one ahead of the chain
+                self.sync_nonce().await;
+                Err(why.into())
+            }
+        }
+    }
 
-        Ok(events.into())
+    /// Sets the nonce to the next one of the account on the chain, counting the extrinsics in the pool
+    async fn sync_nonce(&self) {
+        match self
+            .client
+            .methods
+            .system_account_next_index(self.signer.account_id())
+            .await
+        {
+            Ok(nonce) => {
+                let local = self.nonce.swap(nonce, Ordering::Relaxed);
+                if local != nonce {
+                    debug!(target: LOG_TARGET, "Nonce synced from {local} to {nonce}");
+                }
+            }
+            Err(why) => {
+                warn!(target: LOG_TARGET, "Could not sync the nonce, keeping {}: {why}", self.nonce.load(Ordering::Relaxed))
+            }
+        }
     }
 
     pub async fn contract_call(
@@ -295,4 +402,79 @@ impl<S: Signer> ClientWithSigner<S> {
     pub fn account_id(&self) -> &AccountId {
         self.signer.account_id()
     }
+
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use subxt::{
+        client::{ClientState, RuntimeVersion},
+        config::{signed_extensions::CheckMortality, ExtrinsicParamsEncoder},
+        utils::Era,
+        Metadata,
+    };
+
+    use super::*;
+
+    /// Metadata without any types, pallets or apis, which the extensions not looking into it are built with
+    fn client_state() -> ClientState<PolkadotConfig> {
+        // prefixed V15 metadata, whose extrinsic is of version 4
+        let encoded = [
+            b"meta".as_slice(),
+            &[15, 0, 0, 4, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+        ]
+        .concat();
+
+        ClientState {
+            genesis_hash: BlockHash::zero(),
+            runtime_version: RuntimeVersion {
+                spec_version: 0,
+                transaction_version: 0,
+            },
+            metadata: Metadata::decode(&mut &encoded[..]).expect("metadata is valid"),
+        }
+    }
+
+    /// Era which an extrinsic built by [`ClientWithSigner::get_tx`] is signed with
+    fn signed_era(mortality_blocks: u64, checkpoint_number: u64) -> Era {
+        let checkpoint = Checkpoint {
+            number: checkpoint_number,
+            hash: BlockHash::repeat_byte(1),
+        };
+        let params = tx_params(7, checkpoint, mortality_blocks);
+
+        let mortality = CheckMortality::<PolkadotConfig>::new(&client_state(), params.4)
+            .expect("mortality does not depend on the metadata");
+        let mut extra = Vec::new();
+        mortality.encode_extra_to(&mut extra);
+
+        Era::decode(&mut &extra[..]).expect("extra of the mortality is the era")
+    }
+
+    #[test]
+    fn configured_mortality_is_signed_in_era() {
+        assert_eq!(
+            signed_era(DEFAULT_MORTALITY_BLOCKS, 1000),
+            Era::Mortal(64, 40)
+        );
+        assert_eq!(signed_era(100, 1000), Era::Mortal(128, 104));
+    }
+
+    #[test]
+    fn mortality_period_is_bounded() {
+        assert_eq!(signed_era(1, 1001), Era::Mortal(4, 1));
+        assert!(matches!(signed_era(1_000_000, 1000), Era::Mortal(65536, _)));
+    }
+
+    #[test]
+    fn dropped_connection_is_told_apart() {
+        let dropped = || RpcError::DisconnectedWillReconnect("connection reset".to_owned());
+
+        assert!(ClientError::Rpc(dropped()).is_disconnected());
+        assert!(ClientError::Subxt(Error::Rpc(dropped())).is_disconnected());
+        assert!(!ClientError::NoBlockWithNumber(7).is_disconnected());
+    }
 }
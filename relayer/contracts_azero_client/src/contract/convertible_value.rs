@@ -55,6 +55,25 @@ impl TryFrom<ConvertibleValue> for () {
     }
 }
 
+impl<A, B> TryFrom<ConvertibleValue> for (A, B)
+where
+    ConvertibleValue: TryInto<A, Error = anyhow::Error> + TryInto<B, Error = anyhow::Error>,
+{
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvertibleValue) -> Result<Self> {
+        match &value.0 {
+            Value::Tuple(tuple) if tuple.ident().is_none() && tuple.values().count() == 2 => {
+                let mut values = tuple.values().cloned().map(ConvertibleValue);
+                let first = values.next().unwrap().try_into()?;
+                let second = values.next().unwrap().try_into()?;
+                Ok((first, second))
+            }
+            _ => bail!("Expected {:?} to be a pair", value.0),
+        }
+    }
+}
+
 impl TryFrom<ConvertibleValue> for AccountId {
     type Error = anyhow::Error;
 
@@ -205,6 +224,46 @@ impl TryFrom<ConvertibleValue> for Option<AccountId> {
     }
 }
 
+impl TryFrom<ConvertibleValue> for Option<u128> {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvertibleValue) -> Result<Option<u128>> {
+        let tuple = match &value.0 {
+            Value::Tuple(tuple) => tuple,
+            _ => bail!("Expected {:?} to be a Some(_) or None Tuple.", &value),
+        };
+
+        match tuple.ident() {
+            Some(x) if x == "Some" => {
+                if tuple.values().count() == 1 {
+                    let item =
+                        ConvertibleValue(tuple.values().next().unwrap().clone()).try_into()?;
+                    Ok(Some(item))
+                } else {
+                    bail!(
+                        "Unexpected number of elements in Some(_) variant: {:?}. Expected one.",
+                        &value
+                    );
+                }
+            }
+            Some(x) if x == "None" => {
+                if tuple.values().count() == 0 {
+                    Ok(None)
+                } else {
+                    bail!(
+                        "Unexpected number of elements in None variant: {:?}. Expected zero.",
+                        &value
+                    );
+                }
+            }
+            _ => bail!(
+                "Expected `.ident()` to be `Some` or `None`, got: {:?}",
+                &tuple
+            ),
+        }
+    }
+}
+
 impl<Elem: TryFrom<ConvertibleValue, Error = anyhow::Error>> TryFrom<ConvertibleValue>
     for Vec<Elem>
 {
@@ -239,7 +298,10 @@ impl<const N: usize, Elem: TryFrom<ConvertibleValue, Error = anyhow::Error> + De
 
 #[cfg(test)]
 mod tests {
-    use contract_transcode::Value::{Bool, Char, Int, Seq, UInt};
+    use contract_transcode::{
+        Tuple, Value,
+        Value::{Bool, Char, Int, Seq, UInt},
+    };
 
     use crate::contract::ConvertibleValue;
 
@@ -276,6 +338,21 @@ mod tests {
         assert_eq!(41, cast);
     }
 
+    #[test]
+    fn converts_optional_uint() {
+        let some = Value::Tuple(Tuple::new(Some("Some"), vec![UInt(41)]));
+        let cast: Option<u128> = ConvertibleValue(some)
+            .try_into()
+            .expect("Should cast successfully");
+        assert_eq!(Some(41), cast);
+
+        let none = Value::Tuple(Tuple::new(Some("None"), vec![]));
+        let cast: Option<u128> = ConvertibleValue(none)
+            .try_into()
+            .expect("Should cast successfully");
+        assert_eq!(None, cast);
+    }
+
     #[test]
     fn converts_bigint() {
         let long_int = -41414141414141414141414141414141414141i128;
@@ -293,6 +370,13 @@ mod tests {
         assert_eq!(-41, cast);
     }
 
+    #[test]
+    fn converts_pair() {
+        let cv = ConvertibleValue(Value::Tuple(Tuple::new(None, vec![UInt(41), Bool(true)])));
+        let cast: (u128, bool) = cv.try_into().expect("Should cast successfully");
+        assert_eq!((41, true), cast);
+    }
+
     #[test]
     fn converts_integer_array() {
         let cv = ConvertibleValue(Seq(vec![UInt(4), UInt(1)].into()));
@@ -62,6 +62,7 @@ fn build_event(address: AccountId, event_data: Value) -> Result<ContractEvent> {
                 .iter()
                 .map(|(key, value)| (key.to_string(), value.clone()))
                 .collect(),
+            block_number: None,
         }),
         _ => bail!("Contract event data is not a map"),
     }
@@ -5,7 +5,7 @@ mod types;
 
 use std::fmt::Debug;
 
-pub use client::{Client, ClientConfig, ClientError, ClientWithSigner};
+pub use client::{Client, ClientConfig, ClientError, ClientWithSigner, DEFAULT_MORTALITY_BLOCKS};
 pub use contract::*;
 pub use contract_transcode;
 pub use keypair::*;
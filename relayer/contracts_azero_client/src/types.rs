@@ -52,6 +52,8 @@ pub struct ContractEvent {
     pub name: Option<String>,
     /// Data contained in the event.
     pub data: HashMap<String, Value>,
+    /// The block the event was emitted in, if it was fetched from a specific block.
+    pub block_number: Option<u32>,
 }
 
 #[derive(Encode)]
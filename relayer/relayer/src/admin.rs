@@ -0,0 +1,562 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use log::{debug, info, warn};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::oneshot,
+};
+
+use crate::{
+    config::{Config, Secret},
+    metrics::Metrics,
+    redis::SharedRedisConnection,
+};
+
+const LOG_TARGET: &str = "AdminServer";
+const MAX_REQUEST_SIZE: usize = 8192;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Endpoints which change the state of the relayer, guarded by the admin token
+const MUTATING_ENDPOINTS: &[&str] = &["/confirm"];
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum AdminServerError {
+    #[error("IO error")]
+    Io(#[from] std::io::Error),
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum SubmissionGateError {
+    #[error("Submission of 0x{0} already awaits confirmation")]
+    AlreadyPending(String),
+
+    #[error("Submission of 0x{0} was not confirmed")]
+    NotConfirmed(String),
+}
+
+/// Holds back the first `manual_confirm_first` submissions until they are confirmed by an operator.
+pub struct SubmissionGate {
+    remaining: AtomicUsize,
+    pending: Mutex<HashMap<String, oneshot::Sender<()>>>,
+}
+
+impl SubmissionGate {
+    pub fn new(manual_confirm_first: usize) -> Self {
+        Self {
+            remaining: AtomicUsize::new(manual_confirm_first),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns immediately once the warm-up period is over, otherwise waits for the `/confirm` call.
+    ///
+    /// Fails if a submission of the same request already awaits confirmation, or if the confirmation can no longer
+    /// arrive, so that nothing is submitted without it.
+    pub async fn wait_for_confirmation(
+        &self,
+        request_hash: &str,
+        intended_call: &str,
+    ) -> Result<(), SubmissionGateError> {
+        let request_hash = normalize_request_hash(request_hash);
+        let confirmation_receiver = {
+            let mut pending = self.pending.lock().expect("mutex lock");
+            // the entry of a wait which was cancelled, e.g. by a restart, is stale
+            if pending
+                .get(&request_hash)
+                .is_some_and(|confirmation_sender| !confirmation_sender.is_closed())
+            {
+                return Err(SubmissionGateError::AlreadyPending(request_hash));
+            }
+
+            if self
+                .remaining
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |remaining| {
+                    remaining.checked_sub(1)
+                })
+                .is_err()
+            {
+                return Ok(());
+            }
+
+            let (confirmation_sender, confirmation_receiver) = oneshot::channel();
+            pending.insert(request_hash.clone(), confirmation_sender);
+            confirmation_receiver
+        };
+
+        warn!(
+            target: LOG_TARGET,
+            "Awaiting manual confirmation of {intended_call}, confirm with `POST /confirm?request_hash=0x{request_hash}`"
+        );
+        if confirmation_receiver.await.is_err() {
+            return Err(SubmissionGateError::NotConfirmed(request_hash));
+        }
+        info!(target: LOG_TARGET, "Submission of 0x{request_hash} confirmed");
+
+        Ok(())
+    }
+
+    /// Releases the submission awaiting confirmation, returns `false` if there is none.
+    pub fn confirm(&self, request_hash: &str) -> bool {
+        self.pending
+            .lock()
+            .expect("mutex lock")
+            .remove(&normalize_request_hash(request_hash))
+            .is_some_and(|confirmation_sender| confirmation_sender.send(()).is_ok())
+    }
+}
+
+fn normalize_request_hash(request_hash: &str) -> String {
+    request_hash.trim_start_matches("0x").to_lowercase()
+}
+
+/// Fixed window limit of the number of requests coming from a single IP address
+pub struct RateLimiter {
+    limit: u32,
+    windows: Mutex<HashMap<IpAddr, (Instant, u32)>>,
+}
+
+impl RateLimiter {
+    pub fn new(limit: u32) -> Self {
+        Self {
+            limit,
+            windows: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Counts the request, returns `false` once the address went over the limit in the current window
+    fn allow(&self, address: IpAddr, now: Instant) -> bool {
+        let mut windows = self.windows.lock().expect("mutex lock");
+        // expired windows are dropped, so that addresses which stopped sending requests are not kept around
+        windows.retain(|_, (start, _)| now.duration_since(*start) < RATE_LIMIT_WINDOW);
+
+        let (_, count) = windows.entry(address).or_insert((now, 0));
+        *count = count.saturating_add(1);
+        *count <= self.limit
+    }
+}
+
+/// Whether the request carries the admin token in the `Authorization: Bearer` header.
+/// Every request is authorized if no token is configured.
+fn is_authorized(request: &str, admin_token: Option<&Secret>) -> bool {
+    let Some(admin_token) = admin_token else {
+        return true;
+    };
+
+    request
+        .lines()
+        .skip(1)
+        .take_while(|header| !header.is_empty())
+        .filter_map(|header| header.split_once(':'))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("authorization"))
+        .and_then(|(_, value)| value.trim().strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.trim(), admin_token.expose()))
+}
+
+/// Compares the whole strings regardless of where they differ, not to leak the token through timing
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |difference, (a, b)| difference | (a ^ b))
+            == 0
+}
+
+/// Minimal HTTP server exposing the operator endpoints.
+pub struct AdminServer;
+
+impl AdminServer {
+    pub async fn run(
+        config: Arc<Config>,
+        gate: Arc<SubmissionGate>,
+        redis_connection: Arc<SharedRedisConnection>,
+        metrics: Arc<Metrics>,
+    ) -> Result<(), AdminServerError> {
+        let listener = TcpListener::bind(&config.admin_address).await?;
+        info!(target: LOG_TARGET, "Listening on {}", config.admin_address);
+        if config.admin_token.is_none() {
+            warn!(target: LOG_TARGET, "No admin token set, the endpoints are not protected");
+        }
+
+        let rate_limiter = Arc::new(RateLimiter::new(config.admin_rate_limit_per_min));
+
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            debug!(target: LOG_TARGET, "Connection from {peer}");
+
+            if !rate_limiter.allow(peer.ip(), Instant::now()) {
+                warn!(target: LOG_TARGET, "Rate limit exceeded by {peer}");
+                tokio::spawn(respond(
+                    stream,
+                    "429 Too Many Requests",
+                    "rate limit exceeded".to_string(),
+                ));
+                continue;
+            }
+
+            let config = Arc::clone(&config);
+            let gate = Arc::clone(&gate);
+            let redis_connection = Arc::clone(&redis_connection);
+            let metrics = Arc::clone(&metrics);
+            tokio::spawn(async move {
+                if let Err(why) =
+                    handle_connection(stream, &config, &gate, &redis_connection, &metrics).await
+                {
+                    warn!(target: LOG_TARGET, "Failed to handle a connection from {peer}: {why:?}");
+                }
+            });
+        }
+    }
+}
+
+async fn handle_connection(
+    mut stream: TcpStream,
+    config: &Config,
+    gate: &SubmissionGate,
+    redis_connection: &SharedRedisConnection,
+    metrics: &Metrics,
+) -> Result<(), AdminServerError> {
+    let mut buffer = vec![0; MAX_REQUEST_SIZE];
+    let read = stream.read(&mut buffer).await?;
+    let request = String::from_utf8_lossy(&buffer[..read]);
+    let request_line = request.lines().next().unwrap_or_default();
+
+    let (status, body) = handle_request(
+        request_line,
+        is_authorized(&request, config.admin_token.as_ref()),
+        gate,
+        metrics,
+        redis_connection.is_up(),
+    );
+    respond(stream, status, body).await
+}
+
+async fn respond(
+    mut stream: TcpStream,
+    status: &'static str,
+    body: String,
+) -> Result<(), AdminServerError> {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    Ok(())
+}
+
+fn handle_request(
+    request_line: &str,
+    authorized: bool,
+    gate: &SubmissionGate,
+    metrics: &Metrics,
+    redis_up: bool,
+) -> (&'static str, String) {
+    let mut parts = request_line.split_whitespace();
+    let (Some(method), Some(target)) = (parts.next(), parts.next()) else {
+        return ("400 Bad Request", "malformed request".to_string());
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    if MUTATING_ENDPOINTS.contains(&path) && !authorized {
+        return (
+            "401 Unauthorized",
+            "missing or invalid admin token".to_string(),
+        );
+    }
+
+    match (method, path) {
+        // a crawler or a prefetch following a link must not confirm a submission
+        ("POST", "/confirm") => {
+            let Some(request_hash) = query
+                .split('&')
+                .find_map(|param| param.strip_prefix("request_hash="))
+            else {
+                return ("400 Bad Request", "missing request_hash".to_string());
+            };
+
+            if gate.confirm(request_hash) {
+                ("200 OK", format!("confirmed {request_hash}"))
+            } else {
+                (
+                    "404 Not Found",
+                    format!("no submission of {request_hash} awaits confirmation"),
+                )
+            }
+        }
+        (_, "/confirm") => ("405 Method Not Allowed", "confirm with POST".to_string()),
+        ("GET", "/metrics") => ("200 OK", metrics.render(redis_up)),
+        _ => ("404 Not Found", "unknown endpoint".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn first_submission_waits_for_confirmation() {
+        let gate = Arc::new(SubmissionGate::new(1));
+
+        let first = tokio::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.wait_for_confirmation("0xAB", "first").await }
+        });
+
+        tokio::task::yield_now().await;
+        assert!(!first.is_finished());
+
+        assert_eq!(
+            handle_request(
+                "POST /confirm?request_hash=0xab HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "200 OK"
+        );
+        timeout(Duration::from_secs(1), first)
+            .await
+            .expect("confirmed submission proceeds")
+            .unwrap()
+            .unwrap();
+
+        timeout(
+            Duration::from_secs(1),
+            gate.wait_for_confirmation("0xcd", "second"),
+        )
+        .await
+        .expect("submissions after the first one proceed without confirmation")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn second_wait_for_a_pending_request_is_refused() {
+        let gate = Arc::new(SubmissionGate::new(2));
+
+        let first = tokio::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.wait_for_confirmation("0xab", "first").await }
+        });
+        tokio::task::yield_now().await;
+
+        assert!(matches!(
+            gate.wait_for_confirmation("0xAB", "again").await,
+            Err(SubmissionGateError::AlreadyPending(_))
+        ));
+        assert!(!first.is_finished());
+
+        assert!(gate.confirm("0xab"));
+        first.await.unwrap().unwrap();
+    }
+
+    #[tokio::test]
+    async fn lost_confirmation_is_not_a_confirmation() {
+        let gate = Arc::new(SubmissionGate::new(1));
+
+        let first = tokio::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.wait_for_confirmation("0xab", "first").await }
+        });
+        tokio::task::yield_now().await;
+        gate.pending.lock().unwrap().clear();
+
+        assert!(matches!(
+            first.await.unwrap(),
+            Err(SubmissionGateError::NotConfirmed(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn cancelled_wait_does_not_block_the_request() {
+        let gate = Arc::new(SubmissionGate::new(2));
+
+        let first = tokio::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.wait_for_confirmation("0xab", "first").await }
+        });
+        tokio::task::yield_now().await;
+        first.abort();
+        let _ = first.await;
+
+        let second = tokio::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.wait_for_confirmation("0xab", "second").await }
+        });
+        tokio::task::yield_now().await;
+
+        assert!(!second.is_finished());
+        assert!(gate.confirm("0xab"));
+        second.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn confirming_unknown_request_fails() {
+        let gate = SubmissionGate::new(1);
+
+        assert_eq!(
+            handle_request(
+                "POST /confirm?request_hash=0xab HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "404 Not Found"
+        );
+        assert_eq!(
+            handle_request(
+                "POST /confirm HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "400 Bad Request"
+        );
+        assert_eq!(
+            handle_request(
+                "GET /other HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "404 Not Found"
+        );
+    }
+
+    #[tokio::test]
+    async fn confirmation_is_only_accepted_with_post() {
+        let gate = Arc::new(SubmissionGate::new(1));
+
+        let first = tokio::spawn({
+            let gate = Arc::clone(&gate);
+            async move { gate.wait_for_confirmation("0xab", "first").await }
+        });
+        tokio::task::yield_now().await;
+
+        assert_eq!(
+            handle_request(
+                "GET /confirm?request_hash=0xab HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "405 Method Not Allowed"
+        );
+        assert!(!first.is_finished());
+
+        assert!(gate.confirm("0xab"));
+        first.await.unwrap().unwrap();
+    }
+
+    #[test]
+    fn mutating_endpoints_require_the_admin_token() {
+        let token = "s3cret".parse::<Secret>().unwrap();
+        let gate = SubmissionGate::new(0);
+        let request =
+            |headers: &str| format!("POST /confirm?request_hash=0xab HTTP/1.1\r\n{headers}\r\n");
+
+        assert!(is_authorized(
+            &request("Authorization: Bearer s3cret\r\n"),
+            Some(&token)
+        ));
+        assert!(is_authorized(
+            &request("Host: relayer\r\nauthorization:  Bearer s3cret \r\n"),
+            Some(&token)
+        ));
+        assert!(!is_authorized(
+            &request("Authorization: Bearer s3cre\r\n"),
+            Some(&token)
+        ));
+        assert!(!is_authorized(&request(""), Some(&token)));
+        assert!(is_authorized(&request(""), None));
+
+        assert_eq!(
+            handle_request(
+                "POST /confirm?request_hash=0xab HTTP/1.1",
+                false,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "401 Unauthorized"
+        );
+        assert_eq!(
+            handle_request(
+                "GET /metrics HTTP/1.1",
+                false,
+                &gate,
+                &Metrics::default(),
+                true
+            )
+            .0,
+            "200 OK"
+        );
+    }
+
+    #[test]
+    fn requests_over_the_limit_are_rejected_per_address() {
+        let limiter = RateLimiter::new(2);
+        let first: IpAddr = "10.0.0.1".parse().unwrap();
+        let second: IpAddr = "10.0.0.2".parse().unwrap();
+        let start = Instant::now();
+
+        assert!(limiter.allow(first, start));
+        assert!(limiter.allow(first, start));
+        assert!(!limiter.allow(first, start + Duration::from_secs(1)));
+        assert!(limiter.allow(second, start + Duration::from_secs(1)));
+
+        assert!(limiter.allow(first, start + RATE_LIMIT_WINDOW));
+    }
+
+    #[test]
+    fn metrics_report_redis_status() {
+        let gate = SubmissionGate::new(0);
+
+        assert_eq!(
+            handle_request(
+                "GET /metrics HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                true
+            ),
+            ("200 OK", "redis_up 1\n".to_string())
+        );
+        assert_eq!(
+            handle_request(
+                "GET /metrics HTTP/1.1",
+                true,
+                &gate,
+                &Metrics::default(),
+                false
+            ),
+            ("200 OK", "redis_up 0\n".to_string())
+        );
+    }
+}
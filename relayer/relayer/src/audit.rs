@@ -0,0 +1,259 @@
+use std::{
+    fmt::Debug,
+    fs::{File, OpenOptions},
+    future::Future,
+    io::{self, Write},
+    path::Path,
+    sync::Mutex,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use redis::RedisError;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::contracts::CrosschainTransferRequestData;
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum AuditError {
+    #[error("IO error")]
+    Io(#[from] io::Error),
+
+    #[error("JSON error")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Redis error")]
+    Redis(#[from] RedisError),
+}
+
+/// How the handling of a transfer request ended
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Submitted { tx_hash: String },
+    NotSubmitted { reason: String },
+    Failed { error: String },
+}
+
+/// A transfer request observed on AlephZero, written out once its handling is over
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub block_number: Option<u32>,
+    pub timestamp: u64,
+    pub request_hash: String,
+    pub committee_id: u128,
+    pub dest_token_address: String,
+    pub amount: u128,
+    pub dest_receiver_address: String,
+    pub request_nonce: u128,
+    pub outcome: AuditOutcome,
+}
+
+impl AuditRecord {
+    pub fn new<E: Debug>(
+        block_number: Option<u32>,
+        request_hash: [u8; 32],
+        request: &CrosschainTransferRequestData,
+        outcome: &Result<AuditOutcome, E>,
+    ) -> Self {
+        Self {
+            block_number,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since_epoch| since_epoch.as_secs())
+                .unwrap_or_default(),
+            request_hash: format!("0x{}", hex::encode(request_hash)),
+            committee_id: request.committee_id,
+            dest_token_address: format!("0x{}", hex::encode(request.dest_token_address)),
+            amount: request.amount,
+            dest_receiver_address: format!("0x{}", hex::encode(request.dest_receiver_address)),
+            request_nonce: request.request_nonce,
+            outcome: match outcome {
+                Ok(outcome) => outcome.clone(),
+                Err(why) => AuditOutcome::Failed {
+                    error: format!("{why:?}"),
+                },
+            },
+        }
+    }
+}
+
+/// Append-only record of every transfer request the relayer handled, whether it was submitted or not
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> Result<(), AuditError>;
+}
+
+/// Used when no audit sink is configured
+pub struct NoAuditSink;
+
+impl AuditSink for NoAuditSink {
+    fn record(&self, _record: &AuditRecord) -> Result<(), AuditError> {
+        Ok(())
+    }
+}
+
+/// Appends the records to a file, one JSON object per line
+pub struct FileAuditSink(Mutex<File>);
+
+impl FileAuditSink {
+    pub fn open(path: &Path) -> Result<Self, AuditError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self(Mutex::new(file)))
+    }
+}
+
+impl AuditSink for FileAuditSink {
+    fn record(&self, record: &AuditRecord) -> Result<(), AuditError> {
+        // written at once, so that a crash does not leave a partial line behind
+        let mut line = serde_json::to_vec(record)?;
+        line.push(b'\n');
+        self.0.lock().expect("mutex lock").write_all(&line)?;
+        Ok(())
+    }
+}
+
+/// Runs `handling` of the request and records how it ended, also when it failed.
+///
+/// An error of the handling takes precedence over the one of the audit sink.
+pub async fn audited<F, E>(
+    audit_sink: &dyn AuditSink,
+    block_number: Option<u32>,
+    request_hash: [u8; 32],
+    request: &CrosschainTransferRequestData,
+    handling: F,
+) -> Result<(), E>
+where
+    F: Future<Output = Result<AuditOutcome, E>>,
+    E: Debug + From<AuditError>,
+{
+    let outcome = handling.await;
+    let recorded = audit_sink.record(&AuditRecord::new(
+        block_number,
+        request_hash,
+        request,
+        &outcome,
+    ));
+
+    outcome?;
+    Ok(recorded?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct InMemoryAuditSink(Mutex<Vec<AuditRecord>>);
+
+    impl AuditSink for InMemoryAuditSink {
+        fn record(&self, record: &AuditRecord) -> Result<(), AuditError> {
+            self.0.lock().unwrap().push(record.clone());
+            Ok(())
+        }
+    }
+
+    fn request(request_nonce: u128) -> CrosschainTransferRequestData {
+        CrosschainTransferRequestData {
+            committee_id: 1,
+            dest_token_address: [2; 32],
+            amount: 1000,
+            dest_receiver_address: [3; 32],
+            request_nonce,
+        }
+    }
+
+    fn test_file(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("relayer-audit-{name}-{}.jsonl", std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn every_handled_request_is_recorded_once_with_its_outcome() {
+        let audit_sink = InMemoryAuditSink::default();
+        let outcomes = [
+            Ok(AuditOutcome::Submitted {
+                tx_hash: "0x01".to_owned(),
+            }),
+            Ok(AuditOutcome::NotSubmitted {
+                reason: "blacklisted".to_owned(),
+            }),
+            Err(AuditError::Io(io::ErrorKind::TimedOut.into())),
+        ];
+
+        let mut results = Vec::new();
+        for (nonce, outcome) in outcomes.into_iter().enumerate() {
+            let request = request(nonce as u128);
+            let handling = async move { outcome };
+            results
+                .push(audited(&audit_sink, Some(7), [nonce as u8; 32], &request, handling).await);
+        }
+
+        assert!(results[0].is_ok());
+        assert!(results[1].is_ok());
+        assert!(results[2].is_err());
+
+        let records = audit_sink.0.lock().unwrap();
+        assert_eq!(records.len(), 3);
+        assert_eq!(
+            records
+                .iter()
+                .map(|record| record.request_nonce)
+                .collect::<Vec<_>>(),
+            [0, 1, 2]
+        );
+        assert_eq!(
+            records[0].outcome,
+            AuditOutcome::Submitted {
+                tx_hash: "0x01".to_owned()
+            }
+        );
+        assert_eq!(
+            records[1].outcome,
+            AuditOutcome::NotSubmitted {
+                reason: "blacklisted".to_owned()
+            }
+        );
+        assert!(matches!(records[2].outcome, AuditOutcome::Failed { .. }));
+        assert!(records.iter().all(|record| record.block_number == Some(7)));
+    }
+
+    #[test]
+    fn file_sink_appends_a_line_per_record() {
+        let path = test_file("append");
+        let records: Vec<_> = (0..2)
+            .map(|nonce| {
+                AuditRecord::new::<AuditError>(
+                    Some(5),
+                    [1; 32],
+                    &request(nonce),
+                    &Ok(AuditOutcome::NotSubmitted {
+                        reason: "past committee".to_owned(),
+                    }),
+                )
+            })
+            .collect();
+
+        FileAuditSink::open(&path)
+            .unwrap()
+            .record(&records[0])
+            .unwrap();
+        // reopening keeps the previous records
+        FileAuditSink::open(&path)
+            .unwrap()
+            .record(&records[1])
+            .unwrap();
+
+        let written: Vec<AuditRecord> = fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(written, records);
+    }
+}
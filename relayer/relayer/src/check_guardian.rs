@@ -0,0 +1,426 @@
+use std::{fmt, sync::Arc};
+
+use contracts_azero_client::ClientWithSigner;
+use ethers::{abi::Address, contract::ContractError};
+use thiserror::Error;
+
+use crate::{
+    config::Config,
+    connections::{azero::AzeroSigner, eth::SignedEthConnection},
+    contracts::{AzeroContractError, Most, MostInstance},
+};
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum CheckGuardianError {
+    #[error("Azero contract error")]
+    AzeroContract(#[from] AzeroContractError),
+
+    #[error("Eth contract error")]
+    EthContract(#[from] ContractError<SignedEthConnection>),
+
+    #[error("Error when parsing ethereum address")]
+    FromHex(#[from] rustc_hex::FromHexError),
+
+    #[error("Unbridgeable committee configuration: {0}")]
+    InvalidThreshold(String),
+
+    #[error("Relayer account outside of the committee: {0}")]
+    NotInCommittee(String),
+}
+
+/// Committee membership of the relayer's account on one of the chains
+#[derive(Debug)]
+pub struct GuardianCheck {
+    pub chain: &'static str,
+    pub account: String,
+    pub committee_id: u128,
+    pub signature_threshold: Option<u128>,
+    /// `None` when the contract does not know the members, e.g. of a committee set before an upgrade
+    pub committee_size: Option<u128>,
+    pub is_member: bool,
+}
+
+impl GuardianCheck {
+    /// Describes why no request can reach the signature threshold of the committee, if none can
+    pub fn threshold_violation(&self) -> Option<String> {
+        match self.signature_threshold {
+            Some(0) => Some(format!(
+                "zero signature threshold of committee {} on {}",
+                self.committee_id, self.chain
+            )),
+            Some(threshold) => match self.committee_size {
+                Some(size) if threshold > size => Some(format!(
+                    "signature threshold {threshold} of committee {} on {} above its size {size}",
+                    self.committee_id, self.chain
+                )),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    pub fn ensure_bridgeable(&self) -> Result<(), CheckGuardianError> {
+        match self.threshold_violation() {
+            Some(violation) => Err(CheckGuardianError::InvalidThreshold(violation)),
+            None => Ok(()),
+        }
+    }
+
+    /// Fails when every request signed by the account would revert with `NotInCommittee`, e.g. with a wrong key
+    pub fn ensure_member(&self) -> Result<(), CheckGuardianError> {
+        if self.is_member {
+            return Ok(());
+        }
+
+        Err(CheckGuardianError::NotInCommittee(format!(
+            "{} is not a member of committee {} on {}",
+            self.account, self.committee_id, self.chain
+        )))
+    }
+}
+
+impl fmt::Display for GuardianCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let result = if self.is_member { "PASS" } else { "FAIL" };
+        write!(
+            f,
+            "{}: {result} [committee_id: {}, signature_threshold: ",
+            self.chain, self.committee_id
+        )?;
+        match self.signature_threshold {
+            Some(threshold) => write!(f, "{threshold}]"),
+            None => write!(f, "unknown]"),
+        }
+    }
+}
+
+/// Reads of the Most contract on one of the chains which the committee membership is checked with
+#[async_trait::async_trait]
+pub trait CommitteeReads: Send + Sync {
+    /// Account of the relayer on the chain
+    fn account(&self) -> String;
+
+    async fn current_committee_id(&self) -> Result<u128, CheckGuardianError>;
+
+    async fn is_in_committee(&self, committee_id: u128) -> Result<bool, CheckGuardianError>;
+
+    async fn signature_threshold(
+        &self,
+        committee_id: u128,
+    ) -> Result<Option<u128>, CheckGuardianError>;
+
+    async fn committee_size(&self, committee_id: u128) -> Result<Option<u128>, CheckGuardianError>;
+}
+
+/// Checks the relayer's account against the current committee on `chain`
+pub async fn check(
+    chain: &'static str,
+    reads: &dyn CommitteeReads,
+) -> Result<GuardianCheck, CheckGuardianError> {
+    let committee_id = reads.current_committee_id().await?;
+
+    Ok(GuardianCheck {
+        chain,
+        account: reads.account(),
+        committee_id,
+        signature_threshold: reads.signature_threshold(committee_id).await?,
+        committee_size: reads.committee_size(committee_id).await?,
+        is_member: reads.is_in_committee(committee_id).await?,
+    })
+}
+
+struct AzeroCommitteeReads<'a> {
+    most: MostInstance,
+    connection: &'a ClientWithSigner<AzeroSigner>,
+}
+
+#[async_trait::async_trait]
+impl CommitteeReads for AzeroCommitteeReads<'_> {
+    fn account(&self) -> String {
+        self.connection.account_id().to_string()
+    }
+
+    async fn current_committee_id(&self) -> Result<u128, CheckGuardianError> {
+        Ok(self
+            .most
+            .current_committee_id(self.connection.client())
+            .await?)
+    }
+
+    async fn is_in_committee(&self, committee_id: u128) -> Result<bool, CheckGuardianError> {
+        Ok(self
+            .most
+            .is_in_committee(
+                self.connection.client(),
+                committee_id,
+                self.connection.account_id().clone(),
+            )
+            .await?)
+    }
+
+    async fn signature_threshold(
+        &self,
+        committee_id: u128,
+    ) -> Result<Option<u128>, CheckGuardianError> {
+        Ok(self
+            .most
+            .committee_threshold(self.connection.client(), committee_id)
+            .await?)
+    }
+
+    async fn committee_size(&self, committee_id: u128) -> Result<Option<u128>, CheckGuardianError> {
+        let committee = self
+            .most
+            .committee(self.connection.client(), committee_id)
+            .await?;
+        // members of a committee set before the contract recorded them are unknown until backfilled
+        Ok((!committee.is_empty()).then_some(committee.len() as u128))
+    }
+}
+
+pub async fn check_azero(
+    config: &Config,
+    azero_signed_connection: &ClientWithSigner<AzeroSigner>,
+) -> Result<GuardianCheck, CheckGuardianError> {
+    let most = MostInstance::new(
+        &config.azero_contract_address,
+        &config.azero_contract_metadata,
+        config.azero_ref_time_limit,
+        config.azero_proof_size_limit,
+    )?;
+
+    check(
+        "AlephZero",
+        &AzeroCommitteeReads {
+            most,
+            connection: azero_signed_connection,
+        },
+    )
+    .await
+}
+
+struct EthCommitteeReads {
+    most: Most<SignedEthConnection>,
+    account: Address,
+}
+
+#[async_trait::async_trait]
+impl CommitteeReads for EthCommitteeReads {
+    fn account(&self) -> String {
+        format!("{:#x}", self.account)
+    }
+
+    async fn current_committee_id(&self) -> Result<u128, CheckGuardianError> {
+        Ok(self.most.committee_id().await?.as_u128())
+    }
+
+    async fn is_in_committee(&self, committee_id: u128) -> Result<bool, CheckGuardianError> {
+        Ok(self
+            .most
+            .is_in_committee(committee_id.into(), self.account)
+            .await?)
+    }
+
+    async fn signature_threshold(
+        &self,
+        committee_id: u128,
+    ) -> Result<Option<u128>, CheckGuardianError> {
+        Ok(Some(
+            self.most
+                .signature_threshold(committee_id.into())
+                .await?
+                .as_u128(),
+        ))
+    }
+
+    async fn committee_size(&self, committee_id: u128) -> Result<Option<u128>, CheckGuardianError> {
+        Ok(Some(
+            self.most
+                .committee_size(committee_id.into())
+                .await?
+                .as_u128(),
+        ))
+    }
+}
+
+pub async fn check_eth(
+    config: &Config,
+    eth_signed_connection: Arc<SignedEthConnection>,
+) -> Result<GuardianCheck, CheckGuardianError> {
+    let address = config.eth_contract_address.parse::<Address>()?;
+    let account = eth_signed_connection.address();
+
+    check(
+        "Ethereum",
+        &EthCommitteeReads {
+            most: Most::new(address, eth_signed_connection),
+            account,
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ACCOUNT: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    /// Contract whose current committee is made of `members`, read on behalf of [`ACCOUNT`]
+    struct Committee {
+        id: u128,
+        members: Vec<&'static str>,
+        threshold: u128,
+    }
+
+    #[async_trait::async_trait]
+    impl CommitteeReads for Committee {
+        fn account(&self) -> String {
+            ACCOUNT.to_owned()
+        }
+
+        async fn current_committee_id(&self) -> Result<u128, CheckGuardianError> {
+            Ok(self.id)
+        }
+
+        async fn is_in_committee(&self, committee_id: u128) -> Result<bool, CheckGuardianError> {
+            Ok(committee_id == self.id && self.members.contains(&ACCOUNT))
+        }
+
+        async fn signature_threshold(
+            &self,
+            _committee_id: u128,
+        ) -> Result<Option<u128>, CheckGuardianError> {
+            Ok(Some(self.threshold))
+        }
+
+        async fn committee_size(
+            &self,
+            _committee_id: u128,
+        ) -> Result<Option<u128>, CheckGuardianError> {
+            Ok(Some(self.members.len() as u128))
+        }
+    }
+
+    #[tokio::test]
+    async fn committee_including_the_account_passes() {
+        let committee = Committee {
+            id: 2,
+            members: vec!["0x01", ACCOUNT, "0x02"],
+            threshold: 2,
+        };
+
+        let check = check("Ethereum", &committee).await.unwrap();
+
+        assert!(check.is_member);
+        assert_eq!(check.committee_size, Some(3));
+        assert_eq!(
+            check.to_string(),
+            "Ethereum: PASS [committee_id: 2, signature_threshold: 2]"
+        );
+    }
+
+    #[tokio::test]
+    async fn committee_excluding_the_account_fails() {
+        let committee = Committee {
+            id: 2,
+            members: vec!["0x01", "0x02"],
+            threshold: 2,
+        };
+
+        let check = check("AlephZero", &committee).await.unwrap();
+
+        assert!(!check.is_member);
+        assert_eq!(
+            check.to_string(),
+            "AlephZero: FAIL [committee_id: 2, signature_threshold: 2]"
+        );
+    }
+
+    #[test]
+    fn member_of_the_committee_passes() {
+        let check = GuardianCheck {
+            chain: "Ethereum",
+            account: "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266".to_owned(),
+            committee_id: 2,
+            signature_threshold: Some(3),
+            committee_size: Some(5),
+            is_member: true,
+        };
+
+        assert_eq!(
+            check.to_string(),
+            "Ethereum: PASS [committee_id: 2, signature_threshold: 3]"
+        );
+    }
+
+    #[test]
+    fn account_outside_of_the_committee_fails() {
+        let check = GuardianCheck {
+            chain: "AlephZero",
+            account: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_owned(),
+            committee_id: 0,
+            signature_threshold: None,
+            committee_size: None,
+            is_member: false,
+        };
+
+        assert_eq!(
+            check.to_string(),
+            "AlephZero: FAIL [committee_id: 0, signature_threshold: unknown]"
+        );
+    }
+
+    #[test]
+    fn threshold_has_to_be_reachable() {
+        let check = |signature_threshold, committee_size| GuardianCheck {
+            chain: "AlephZero",
+            account: "5GrwvaEF5zXb26Fz9rcQpDWS57CtERHpNehXCPcNoHGKutQY".to_owned(),
+            committee_id: 1,
+            signature_threshold,
+            committee_size,
+            is_member: true,
+        };
+
+        assert!(check(Some(3), Some(5)).ensure_bridgeable().is_ok());
+        assert!(check(Some(5), Some(5)).ensure_bridgeable().is_ok());
+        assert_eq!(
+            check(Some(0), Some(5)).threshold_violation(),
+            Some("zero signature threshold of committee 1 on AlephZero".to_owned())
+        );
+        assert_eq!(
+            check(Some(6), Some(5)).threshold_violation(),
+            Some("signature threshold 6 of committee 1 on AlephZero above its size 5".to_owned())
+        );
+        assert!(check(Some(6), None).ensure_bridgeable().is_ok());
+        assert!(matches!(
+            check(Some(6), Some(5)).ensure_bridgeable(),
+            Err(CheckGuardianError::InvalidThreshold(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn account_outside_of_the_committee_is_reported() {
+        let committee = |members| Committee {
+            id: 4,
+            members,
+            threshold: 2,
+        };
+
+        let included = check("Ethereum", &committee(vec![ACCOUNT, "0x01", "0x02"]))
+            .await
+            .unwrap();
+        let excluded = check("Ethereum", &committee(vec!["0x01", "0x02", "0x03"]))
+            .await
+            .unwrap();
+
+        assert!(included.ensure_member().is_ok());
+        assert!(matches!(
+            excluded.ensure_member(),
+            Err(CheckGuardianError::NotInCommittee(why))
+                if why == "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266 is not a member of committee 4 on Ethereum"
+        ));
+    }
+}
@@ -0,0 +1,325 @@
+//! Commands of the relayer which run once and exit, instead of relaying the requests continuously
+
+use std::{sync::Arc, time::Duration};
+
+use ethers::types::H256;
+use log::{error, info};
+use tokio::sync::{broadcast, mpsc, oneshot, watch};
+
+use crate::{
+    admin::SubmissionGate,
+    check_guardian,
+    config::{Chain, Config},
+    connections::eth::{self, with_gas_escalator},
+    contracts::MostInstance,
+    dedup::RequestDeduplicator,
+    handlers::{
+        AlephZeroEventHandler, AlephZeroEventsHandler, AzeroMost, EthereumEventsHandler,
+        SignedMostInstance,
+    },
+    listeners::{
+        fetch_eth_events_in_block_range, fetch_events_in_block_range, AzeroMostEvents,
+        CachedBaseFee, EthMostEvents, EthereumFinalizedHeadListener,
+    },
+    metadata,
+    metrics::Metrics,
+    redis::{
+        DeadLetterStore, RedisDeadLetterStore, RedisRelayedRequestStore, RedisSubmittedTxStore,
+    },
+    supervisor::{
+        audit_sink, create_azero_connections, create_eth_connections, oracle_fees, RelayerError,
+    },
+    CircuitBreakerEvent,
+};
+
+/// Prints the committee membership of the relayer's accounts on both chains, without starting any of the components
+pub async fn check_guardian(config: &Config) -> Result<bool, RelayerError> {
+    let (_, azero_signed_connection) = create_azero_connections(config).await?;
+    let (_, eth_signed_connection) =
+        create_eth_connections(config, with_gas_escalator(eth::connect(config).await).await)
+            .await?;
+
+    let checks = [
+        check_guardian::check_azero(config, &azero_signed_connection).await?,
+        check_guardian::check_eth(config, eth_signed_connection).await?,
+    ];
+    for check in &checks {
+        println!("{check}");
+        if let Some(violation) = check.threshold_violation() {
+            println!("{}: FAIL [{violation}]", check.chain);
+        }
+    }
+
+    Ok(checks
+        .iter()
+        .all(|check| check.is_member && check.threshold_violation().is_none()))
+}
+
+/// Prints whether the request is unknown, pending or processed in the AlephZero contract
+pub async fn check_request(config: &Config, request_hash: H256) -> Result<(), RelayerError> {
+    let (azero_connection, _) = create_azero_connections(config).await?;
+    let most_azero = MostInstance::new(
+        &config.azero_contract_address,
+        &config.azero_contract_metadata,
+        config.azero_ref_time_limit,
+        config.azero_proof_size_limit,
+    )?;
+
+    let status = most_azero
+        .request_status(&azero_connection, request_hash.into())
+        .await?;
+    println!("AlephZero: {request_hash:?} {status}");
+
+    Ok(())
+}
+
+/// Splits the blocks from `from` to `to`, inclusive, into batches of at most `sync_step` blocks, as the listeners do
+fn replay_batches(from: u32, to: u32, sync_step: u32) -> impl Iterator<Item = (u32, u32)> {
+    let sync_step = sync_step.max(1);
+
+    (from..=to)
+        .step_by(sync_step as usize)
+        .map(move |from_block| (from_block, to.min(from_block.saturating_add(sync_step - 1))))
+}
+
+/// Relays the requests from the blocks `from` to `to` of `chain` through its events handler, and exits once they are handled.
+///
+/// Unlike the live loop, it neither reads nor advances the cached block numbers. A request which was already relayed
+/// is skipped by the handler, as it is after a restart. Returns whether all the requests were handled.
+pub async fn replay(
+    config: Arc<Config>,
+    chain: Chain,
+    from: u32,
+    to: u32,
+) -> Result<bool, RelayerError> {
+    let (azero_connection, azero_signed_connection) = create_azero_connections(&config).await?;
+    let (eth_connection, eth_signed_connection) = create_eth_connections(
+        &config,
+        with_gas_escalator(eth::connect(&config).await).await,
+    )
+    .await?;
+    let redis_connection = Arc::new(crate::redis::connect(&config)?);
+    let metrics = Arc::new(Metrics::default());
+    // the operator asked for these very requests, so they are not confirmed once more
+    let submission_gate = Arc::new(SubmissionGate::new(0));
+    let dead_letters: Arc<dyn DeadLetterStore> = Arc::new(RedisDeadLetterStore::new(
+        &config,
+        Arc::clone(&redis_connection),
+    ));
+    let dedup = Arc::new(RequestDeduplicator::new(
+        Duration::from_secs(config.request_dedup_window_sec),
+        Arc::clone(&metrics),
+    ));
+    let azero_most: Arc<dyn AzeroMost> = Arc::new(SignedMostInstance::new(
+        &config,
+        Arc::clone(&azero_signed_connection),
+    )?);
+
+    let (circuit_breaker_sender, mut circuit_breaker_receiver) =
+        broadcast::channel::<CircuitBreakerEvent>(1);
+    let handler_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+
+    let outcome = match chain {
+        Chain::Eth => {
+            let finalized_head =
+                eth::get_finalized_block_number(&*eth_connection, config.eth_finality)
+                    .await
+                    .unwrap_or_default();
+            if to > finalized_head {
+                return Err(RelayerError::NotFinalized(to, finalized_head));
+            }
+
+            let (eth_events_sender, eth_events_receiver) = mpsc::channel::<EthMostEvents>(1);
+            let handler = tokio::spawn(EthereumEventsHandler::run(
+                Arc::clone(&config),
+                eth_events_receiver,
+                azero_most,
+                dead_letters,
+                Arc::new(RedisRelayedRequestStore::new(
+                    &config,
+                    Arc::clone(&redis_connection),
+                    "eth_to_azero",
+                )),
+                dedup,
+                submission_gate,
+                metrics,
+                circuit_breaker_sender,
+                handler_circuit_breaker_receiver,
+            ));
+
+            for (from_block, to_block) in replay_batches(from, to, config.sync_step) {
+                let events = fetch_eth_events_in_block_range(
+                    &config,
+                    Arc::clone(&eth_connection),
+                    from_block,
+                    to_block,
+                )
+                .await?;
+                info!(
+                    "Replaying {} events from blocks {from_block} - {to_block}",
+                    events.len()
+                );
+
+                let (events_ack_sender, events_ack_receiver) = oneshot::channel();
+                let batch = EthMostEvents {
+                    events,
+                    from_block,
+                    to_block,
+                    events_ack_sender,
+                };
+                // the handler exits without an ack once it fails
+                if eth_events_sender.send(batch).await.is_err()
+                    || events_ack_receiver.await.is_err()
+                {
+                    break;
+                }
+            }
+
+            drop(eth_events_sender);
+            handler.await??
+        }
+
+        Chain::Azero => {
+            let finalized_hash = azero_connection.get_finalized_block_hash().await?;
+            let finalized_head = azero_connection
+                .get_block_number(finalized_hash)
+                .await?
+                .unwrap_or_default();
+            if to > finalized_head {
+                return Err(RelayerError::NotFinalized(to, finalized_head));
+            }
+
+            // the handler waits for its Ethereum transactions to be finalized
+            let (eth_finalized_head_sender, eth_finalized_head_receiver) = watch::channel::<u32>(0);
+            let eth_finalized_head_listener = tokio::spawn(EthereumFinalizedHeadListener::run(
+                Arc::clone(&config),
+                Arc::clone(&eth_connection),
+                eth_finalized_head_sender,
+                circuit_breaker_sender.subscribe(),
+            ));
+
+            let event_handler = Arc::new(AlephZeroEventHandler::new(
+                Arc::clone(&config),
+                azero_most,
+                eth_signed_connection,
+                eth_finalized_head_receiver,
+                Arc::new(RedisSubmittedTxStore::new(
+                    &config,
+                    Arc::clone(&redis_connection),
+                )),
+                dead_letters,
+                audit_sink(&config, &redis_connection)?,
+                dedup,
+                submission_gate,
+                oracle_fees(&config, &azero_connection)?,
+                Arc::new(CachedBaseFee::new(Duration::from_secs(
+                    config.azero_base_fee_cache_ttl_sec,
+                ))),
+                metrics,
+            ));
+            let (azero_events_sender, azero_events_receiver) = mpsc::channel::<AzeroMostEvents>(1);
+            let handler = tokio::spawn(AlephZeroEventsHandler::run(
+                Arc::clone(&config),
+                event_handler,
+                azero_events_receiver,
+                circuit_breaker_sender,
+                handler_circuit_breaker_receiver,
+            ));
+
+            let most_azero = MostInstance::new(
+                &config.azero_contract_address,
+                &config.azero_contract_metadata,
+                config.azero_ref_time_limit,
+                config.azero_proof_size_limit,
+            )?;
+            for (from_block, to_block) in replay_batches(from, to, config.sync_step) {
+                let events = fetch_events_in_block_range(
+                    &azero_connection,
+                    from_block,
+                    to_block,
+                    &[&most_azero.contract],
+                    config.sequential_processing,
+                )
+                .await?;
+                info!(
+                    "Replaying {} events from blocks {from_block} - {to_block}",
+                    events.len()
+                );
+
+                let (ack, ack_receiver) = oneshot::channel();
+                let batch = AzeroMostEvents {
+                    events,
+                    from_block,
+                    to_block,
+                    ack,
+                };
+                // a failed request opens the circuit breaker, but its batch is acked nevertheless
+                if azero_events_sender.send(batch).await.is_err()
+                    || ack_receiver.await.is_err()
+                    || circuit_breaker_receiver.try_recv().is_ok()
+                {
+                    break;
+                }
+            }
+
+            drop(azero_events_sender);
+            let outcome = handler.await??;
+            eth_finalized_head_listener.abort();
+            outcome
+        }
+    };
+
+    if let CircuitBreakerEvent::Shutdown = outcome {
+        info!("Replayed blocks {from} - {to}");
+        return Ok(true);
+    }
+
+    error!("Replay of blocks {from} - {to} stopped by {outcome:?}");
+    Ok(false)
+}
+
+/// Prints the requests which were moved to the dead letter queue for a manual review
+pub fn print_dead_letters(config: &Config) -> Result<(), RelayerError> {
+    let redis_connection = Arc::new(crate::redis::connect(config)?);
+    let dead_letters = RedisDeadLetterStore::new(config, redis_connection).entries()?;
+
+    for (request_hash, reason) in &dead_letters {
+        println!("0x{request_hash}: {reason}");
+    }
+    println!("{} requests in the dead letter queue", dead_letters.len());
+
+    Ok(())
+}
+
+/// Prints the mismatches of the AlephZero contract metadata, without connecting to any node
+pub fn validate_metadata(config: &Config) -> Result<bool, RelayerError> {
+    let path = &config.azero_contract_metadata;
+    let mismatches = metadata::most_metadata_mismatches(path.as_ref())?;
+
+    if mismatches.is_empty() {
+        println!("{path}: PASS");
+    }
+    for mismatch in &mismatches {
+        println!("{path}: FAIL [{mismatch}]");
+    }
+
+    Ok(mismatches.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn replayed_range_is_split_into_sync_steps() {
+        assert_eq!(
+            replay_batches(10, 34, 10).collect::<Vec<_>>(),
+            [(10, 19), (20, 29), (30, 34)]
+        );
+        assert_eq!(replay_batches(7, 7, 100).collect::<Vec<_>>(), [(7, 7)]);
+        assert_eq!(
+            replay_batches(u32::MAX - 1, u32::MAX, 100).collect::<Vec<_>>(),
+            [(u32::MAX - 1, u32::MAX)]
+        );
+    }
+}
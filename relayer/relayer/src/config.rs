@@ -1,6 +1,10 @@
-use std::{cmp::max, ops::Deref, str::FromStr};
+use std::{cmp::max, ops::Deref, path::PathBuf, str::FromStr, time::Duration};
 
-use ethers::core::types::H256;
+use ethers::core::types::{Address, H256, U256};
+use signer_client::Timeouts;
+use thiserror::Error;
+
+use crate::{fees::OracleFeeStrategy, helpers::PollBackoff};
 
 #[derive(Debug, Clone)]
 pub struct SyncFromBlock(u32);
@@ -22,8 +26,274 @@ impl Deref for SyncFromBlock {
     }
 }
 
+/// Value which is kept out of the logs, e.g. when the whole `Config` is printed at startup
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(String);
+
+impl Secret {
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl FromStr for Secret {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl std::fmt::Debug for Secret {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Secret(***)")
+    }
+}
+
+/// Vsock ports the signer may listen on, probed in order, e.g. `1234`, `1234,1300` or `1234-1237`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignerPorts(Vec<u32>);
+
+/// Guards against a typo in a range turning into millions of connection attempts
+const MAX_SIGNER_PORTS: usize = 1024;
+
+impl FromStr for SignerPorts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ports = Vec::new();
+        for part in s.split(',').map(str::trim) {
+            match part.split_once('-') {
+                Some((first, last)) => {
+                    let first: u32 = first.trim().parse().map_err(|e| format!("{e}"))?;
+                    let last: u32 = last.trim().parse().map_err(|e| format!("{e}"))?;
+                    if first > last {
+                        return Err(format!("port range `{part}` is empty"));
+                    }
+                    if (last - first) as usize >= MAX_SIGNER_PORTS {
+                        return Err(format!(
+                            "port range `{part}` is longer than {MAX_SIGNER_PORTS} ports"
+                        ));
+                    }
+                    ports.extend(first..=last);
+                }
+                None => ports.push(part.parse().map_err(|e| format!("{e}"))?),
+            }
+        }
+
+        if ports.len() > MAX_SIGNER_PORTS {
+            return Err(format!("more than {MAX_SIGNER_PORTS} ports given"));
+        }
+
+        Ok(Self(ports))
+    }
+}
+
+impl Deref for SignerPorts {
+    type Target = [u32];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Log level override for a single module, in the `module=level` format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFilter {
+    pub module: String,
+    pub level: log::LevelFilter,
+}
+
+impl FromStr for LogFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (module, level) = s
+            .split_once('=')
+            .ok_or(format!("expected `module=level`, got `{s}`"))?;
+        let level = level.parse().map_err(|e| format!("{e}"))?;
+
+        Ok(Self {
+            module: module.to_string(),
+            level,
+        })
+    }
+}
+
+/// Bounds on the amount of a single request to be forwarded automatically, in the `0x<dest token>=min:max` format
+///
+/// The relayer policy is applied on top of the minimum enforced by the contracts when the request is sent,
+/// so a request has to satisfy both. Setting `min` below the contract minimum has no effect.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenLimit {
+    pub token: [u8; 32],
+    pub min: u128,
+    pub max: u128,
+}
+
+impl TokenLimit {
+    /// Describes why the `amount` can not be forwarded automatically, if it can't
+    pub fn violation(&self, amount: u128) -> Option<String> {
+        if amount < self.min {
+            Some(format!("amount {amount} below the minimum of {}", self.min))
+        } else if amount > self.max {
+            Some(format!("amount {amount} above the maximum of {}", self.max))
+        } else {
+            None
+        }
+    }
+}
+
+impl FromStr for TokenLimit {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (token, limits) = s
+            .split_once('=')
+            .ok_or(format!("expected `0x<token>=min:max`, got `{s}`"))?;
+        let (min, max) = limits
+            .split_once(':')
+            .ok_or(format!("expected `min:max`, got `{limits}`"))?;
+
+        let token: [u8; 32] = hex::decode(token.trim_start_matches("0x"))
+            .map_err(|e| format!("{e}"))?
+            .try_into()
+            .map_err(|_| format!("token address `{token}` is not 32 bytes long"))?;
+        let min: u128 = min.parse().map_err(|e| format!("{e}"))?;
+        let max: u128 = max.parse().map_err(|e| format!("{e}"))?;
+
+        if min > max {
+            return Err(format!("minimum {min} is greater than the maximum {max}"));
+        }
+
+        Ok(Self { token, min, max })
+    }
+}
+
+/// Which Ethereum blocks are treated as final, in the `finalized` or `confirmations:<n>` format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinalityStrategy {
+    /// Blocks at least `n` blocks below the latest one
+    Confirmations(u64),
+    /// Blocks up to the one the node reports under the `finalized` tag
+    FinalizedTag,
+}
+
+/// On an L2 the latest block is treated as final, as there is no finality of its own to wait for
+#[cfg(feature = "l2")]
+const DEFAULT_ETH_FINALITY: &str = "confirmations:0";
+
+#[cfg(not(feature = "l2"))]
+const DEFAULT_ETH_FINALITY: &str = "finalized";
+
+impl FromStr for FinalityStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once(':') {
+            None if s == "finalized" => Ok(Self::FinalizedTag),
+            Some(("confirmations", n)) => {
+                Ok(Self::Confirmations(n.parse().map_err(|e| format!("{e}"))?))
+            }
+            _ => Err(format!(
+                "expected `finalized` or `confirmations:<n>`, got `{s}`"
+            )),
+        }
+    }
+}
+
+/// Where the audit records of the handled transfer requests are appended to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum AuditSinkKind {
+    /// JSON lines appended to `audit_log_path`
+    File,
+    /// Redis stream under `redis_audit_stream_key`
+    Redis,
+}
+
+/// How the fees of the transactions submitted to Ethereum are chosen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum EthFeeStrategyKind {
+    /// Estimated by the Ethereum node
+    Node,
+    /// Derived from the gas price oracle the AlephZero Most contract charges the transfer fees by
+    Oracle,
+}
+
+/// Chain whose blocks are replayed by the `replay` subcommand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum Chain {
+    /// Transfer requests from AlephZero, forwarded to Ethereum
+    Azero,
+    /// Transfer requests from Ethereum, forwarded to AlephZero
+    Eth,
+}
+
+/// Combination of options the relayer can not run with, caught before connecting to anything
+#[derive(Debug, Error, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigError {
+    #[error("no signing mode: pass --signer-cid to sign with the signer, or --dev to sign with a development key")]
+    NoSigningMode,
+
+    #[error("--signer-cid and --dev are mutually exclusive, pass only one of them")]
+    ConflictingSigningModes,
+
+    #[error("--eth-private-key-env and --eth-mnemonic-env can not be used with --signer-cid, which signs with the signer's Ethereum key")]
+    EthKeyWithSigner,
+
+    #[error("--{0} is empty")]
+    EmptyUrl(&'static str),
+
+    #[error("--advisory-contract-addresses is required to run the relayer")]
+    NoAdvisoryAddresses,
+
+    #[error(
+        "--advisory-contract-metadata is empty, while --advisory-contract-addresses are given"
+    )]
+    NoAdvisoryMetadata,
+
+    #[error("replay range {from}..={to} is empty")]
+    EmptyReplayRange { from: u32, to: u32 },
+
+    #[error("replay can not be run with --shadow-of, as it submits the replayed requests")]
+    ReplayInShadowMode,
+
+    #[error("--offline-signing-dir requires --offline-signer-address, which the transactions are sent from")]
+    NoOfflineSigner,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, clap::Subcommand)]
+pub enum Command {
+    /// Checks whether the relayer's accounts are members of the current committees and exits
+    CheckGuardian,
+    /// Prints the status of the request with the given hash in the AlephZero contract and exits
+    CheckRequest { request_hash: H256 },
+    /// Checks that the AlephZero contract metadata has the messages and events the relayer depends on and exits
+    ValidateMetadata,
+    /// Prints the requests in the dead letter queue along with the reasons they were not forwarded and exits
+    DeadLetters,
+    /// Relays the requests from the given finalized blocks of one chain once and exits, without entering the live loop.
+    ///
+    /// The cached block numbers are neither read nor advanced. Requests which were already relayed are skipped
+    /// by the handlers, as they are in the live loop.
+    Replay {
+        #[arg(long, value_enum)]
+        chain: Chain,
+        /// First block of the range
+        #[arg(long)]
+        from: u32,
+        /// Last block of the range, inclusive
+        #[arg(long)]
+        to: u32,
+    },
+}
+
 #[derive(Debug, clap::Parser)]
 pub struct Config {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     #[arg(long)]
     pub name: String,
 
@@ -33,12 +303,26 @@ pub struct Config {
     #[arg(long, default_value = "0")]
     pub dev_account_index: u32,
 
+    /// In dev mode, name of the environment variable holding the hex encoded private key to use for Ethereum
+    /// instead of the development mnemonic
+    #[arg(long, conflicts_with = "eth_mnemonic_env")]
+    pub eth_private_key_env: Option<String>,
+
+    /// In dev mode, name of the environment variable holding the mnemonic to use for Ethereum instead of the
+    /// development one, derived at `dev_account_index`
+    #[arg(long)]
+    pub eth_mnemonic_env: Option<String>,
+
     #[arg(long)]
     pub override_azero_cache: bool,
 
     #[arg(long)]
     pub override_eth_cache: bool,
 
+    /// Handle blocks and events one at a time, which makes the logs linear and the runs reproducible. Meant for debugging
+    #[arg(long)]
+    pub sequential_processing: bool,
+
     /// Optional list of hex encoded request hashes to skip from processing
     #[arg(long, use_value_delimiter = true, value_delimiter = ',')]
     pub blacklisted_requests: Option<Vec<H256>>,
@@ -49,11 +333,37 @@ pub struct Config {
     #[arg(long, default_value = "../azero/artifacts/advisory.json")]
     pub advisory_contract_metadata: String,
 
+    /// How often the advisory contracts are checked for an emergency
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub advisory_poll_interval_sec: u64,
+
+    /// How long a read of a single advisory contract may take before the contract is skipped for the round
+    #[arg(long, default_value = "10", value_parser = clap::value_parser!(u64).range(1..))]
+    pub advisory_query_timeout_sec: u64,
+
+    /// Upper bound on the extra delay between the advisory checks after a round with a contract which could not be read,
+    /// which starts at the poll interval and doubles with every such round in a row
+    #[arg(long, default_value = "60")]
+    pub advisory_max_backoff_sec: u64,
+
+    /// Number of rounds in a row with a contract which could not be read before opening the circuit breaker
+    #[arg(long, default_value = "5")]
+    pub advisory_max_retries: u32,
+
     #[arg(long)]
     pub signer_cid: Option<u32>,
 
-    #[arg(long, default_value = "1234")]
-    pub signer_port: u32,
+    /// Port, list of ports or range of ports of the signer, the first one which answers is used
+    #[arg(long = "signer-port", default_value = "1234")]
+    pub signer_ports: SignerPorts,
+
+    /// How many seconds to wait for the signer to accept a connection, 0 waits forever
+    #[arg(long, default_value = "5")]
+    pub signer_connect_timeout_sec: u64,
+
+    /// How many seconds to wait for the signer to take a request and to answer it, 0 waits forever
+    #[arg(long, default_value = "30")]
+    pub signer_request_timeout_sec: u64,
 
     #[arg(long)]
     pub azero_contract_address: String,
@@ -73,6 +383,11 @@ pub struct Config {
     #[arg(long, default_value = "10000000")]
     pub azero_proof_size_limit: u64,
 
+    /// Number of blocks, counted from the last finalized one, after which a submitted extrinsic expires if not included.
+    /// Rounded up to a power of two, at least 4
+    #[arg(long, default_value = "64")]
+    pub azero_extrinsic_mortality_blocks: u64,
+
     #[arg(long, default_value = "1")]
     pub default_sync_from_block_azero: SyncFromBlock,
 
@@ -85,30 +400,916 @@ pub struct Config {
     #[arg(long, default_value = "10")]
     pub eth_tx_submission_retries: usize,
 
+    /// Retries of forwarding an AlephZero request to Ethereum after a transient failure, e.g. of the node connection.
+    /// A request still failing afterwards is moved to the dead letter queue for a manual review
+    #[arg(long, default_value = "2")]
+    pub eth_forwarding_retries: u32,
+
+    /// Upper bound of the delay between the retries of forwarding a request, which start at one Ethereum block
+    #[arg(long, default_value = "120")]
+    pub eth_forwarding_max_backoff_sec: u64,
+
     #[arg(long, default_value = "32")]
     pub eth_tx_min_confirmations: usize,
 
+    /// Besides the status of a confirmed `receive_request` tx, check that its receipt carries the contract's log of the guardian's signature
+    #[arg(long)]
+    pub eth_verify_receipt_logs: bool,
+
+    /// Send `receive_request` transactions to Ethereum without simulating them first
+    #[arg(long)]
+    pub eth_skip_dry_run: bool,
+
+    /// Do not send another `receive_request` transaction for a request while the one recorded for it in redis,
+    /// e.g. before a restart, is still pending or mined. A reverted or dropped one is forgotten and sent again
+    #[arg(long)]
+    pub eth_single_submission: bool,
+
+    /// Wait for the finality of the submitted signatures in up to this many background tasks, so that a batch of
+    /// AlephZero blocks is marked as handled once its txs are mined. The handlers wait for the finality if not set
+    #[arg(long)]
+    pub eth_finality_trackers: Option<usize>,
+
     #[arg(long, default_value = "1")]
     pub default_sync_from_block_eth: SyncFromBlock,
 
     #[arg(long, default_value = "200000")]
     pub eth_gas_limit: u32,
 
+    #[arg(long, value_enum, default_value = "node")]
+    pub eth_fee_strategy: EthFeeStrategyKind,
+
+    /// Wei per picoAZERO, the unit of the oracle price. Required by the `oracle` fee strategy
+    #[arg(long, required_if_eq("eth_fee_strategy", "oracle"))]
+    pub eth_fee_wei_per_pico_azero: Option<f64>,
+
+    /// Max fee per gas as a percentage of the oracle price converted to wei
+    #[arg(long, default_value = "150")]
+    pub eth_fee_oracle_percent: u64,
+
+    #[arg(long, default_value = "1")]
+    pub eth_min_max_fee_per_gas_gwei: u64,
+
+    #[arg(long, default_value = "300")]
+    pub eth_max_max_fee_per_gas_gwei: u64,
+
+    #[arg(long, default_value = "2")]
+    pub eth_max_priority_fee_per_gas_gwei: u64,
+
+    /// Gas price above which the submissions to Ethereum are deferred until it drops. No ceiling if not set
+    #[arg(long)]
+    pub eth_max_gas_price_gwei: Option<u64>,
+
+    /// Upper bound of the delay between the gas price checks of a deferred submission
+    #[arg(long, default_value = "300")]
+    pub eth_gas_price_max_backoff_sec: u64,
+
+    #[arg(long, default_value = "../azero/artifacts/oracle.json")]
+    pub gas_price_oracle_metadata: String,
+
+    /// Requests to Ethereum below this percentage of the minimum transfer amount, which Ethereum enforces
+    /// on the requests it sends, are reported as suspicious. They are still processed
+    #[arg(long, default_value = "10")]
+    pub suspicious_amount_percent: u64,
+
+    /// When set, `receive_request` transactions on Ethereum are not sent, but written to the `unsigned`
+    /// subdirectory for an offline signer. Signed transactions put in the `signed` subdirectory are broadcast,
+    /// the ones which fail to be are moved to the `failed` subdirectory.
+    ///
+    /// The gas price of a transaction is the one at the time it is written out, a transaction which the offline
+    /// signer signs after the price went up may have to be rewritten by removing it from the `unsigned` subdirectory.
+    #[arg(long)]
+    pub offline_signing_dir: Option<PathBuf>,
+
+    /// Ethereum account of the offline signer, whose nonce the written out transactions take
+    /// and whose signatures of the requests are looked up. Required with `--offline-signing-dir`
+    #[arg(long, requires = "offline_signing_dir")]
+    pub offline_signer_address: Option<Address>,
+
+    #[arg(long, default_value = "10")]
+    pub offline_signing_poll_interval_sec: u64,
+
     #[arg(long, default_value = "100")]
     pub sync_step: u32,
 
+    /// Expected time between AlephZero blocks, which paces the AlephZero head and halt polls
+    #[arg(long, default_value = "1", value_parser = clap::value_parser!(u64).range(1..))]
+    pub azero_block_prod_time_sec: u64,
+
+    /// Expected time between Ethereum blocks, which paces the Ethereum head and pause polls
+    #[arg(long, default_value = "12", value_parser = clap::value_parser!(u64).range(1..))]
+    pub eth_block_prod_time_sec: u64,
+
+    /// Which Ethereum blocks are final, so that the transactions in them are no longer waited for
+    #[arg(long, default_value = DEFAULT_ETH_FINALITY)]
+    pub eth_finality: FinalityStrategy,
+
+    /// Maximum number of AlephZero requests handled at once. A busier batch is handled in chunks of this size
+    #[arg(long, default_value = "50", value_parser = clap::value_parser!(u64).range(1..))]
+    pub azero_max_requests_per_block: u64,
+
+    /// Maximum number of AlephZero requests being submitted to Ethereum at once, across all the batches in flight
+    #[arg(long, default_value = "50", value_parser = clap::value_parser!(u64).range(1..))]
+    pub eth_max_concurrent_submissions: u64,
+
+    /// Maximum number of AlephZero blocks fetched but not handled yet. Keeps catching up after a downtime
+    /// to a few batches at a time instead of the whole gap
+    #[arg(long, default_value = "1000", value_parser = clap::value_parser!(u32).range(1..))]
+    pub azero_catch_up_window: u32,
+
+    /// Number of blocks on top of an AlephZero block which the node has to report as finalized before its events are processed,
+    /// in addition to the block itself
+    #[arg(long, default_value = "0")]
+    pub azero_min_confirmations: u32,
+
+    /// How long the last value read of `is_halted`, the current committee id and the committee membership in the AlephZero
+    /// contract is served in place of a failed read. Zero fails on the first failed read
+    #[arg(long, default_value = "30")]
+    pub azero_guard_read_ttl_sec: u64,
+
+    /// How often the AlephZero contract is checked again while a transfer request to Ethereum is deferred because it is halted
+    #[arg(long, default_value = "30", value_parser = clap::value_parser!(u64).range(1..))]
+    pub azero_halt_recheck_interval_sec: u64,
+
+    /// How long the base fee of the AlephZero contract, logged along the transfer requests, is reused before it is read again
+    #[arg(long, default_value = "60")]
+    pub azero_base_fee_cache_ttl_sec: u64,
+
     #[arg(long)]
     pub payout_address: Option<String>,
 
+    /// AlephZero account of a production guardian. If set, the relayer runs in the shadow mode: it never submits anything,
+    /// but checks that the guardian signed the same requests it would sign and logs every divergence
+    #[arg(long)]
+    pub shadow_of: Option<String>,
+
+    /// How long to wait for the production guardian's signature of a request before reporting a divergence
+    #[arg(long, default_value = "600")]
+    pub shadow_grace_period_sec: u64,
+
     #[arg(long, default_value = "redis://127.0.0.1:6379")]
     pub redis_node: String,
 
+    /// Connect to redis over TLS, regardless of the scheme of `redis_node`
+    #[arg(long)]
+    pub redis_tls: bool,
+
+    /// PEM encoded CA certificate used to verify the redis server, instead of the system roots
+    #[arg(long)]
+    pub redis_ca_path: Option<PathBuf>,
+
+    #[arg(long)]
+    pub redis_username: Option<String>,
+
+    /// Takes precedence over the password embedded in `redis_node`. Best passed in the environment, which unlike
+    /// the command line is not visible to the other processes
+    #[arg(long, env = "REDIS_PASSWORD", hide_env_values = true)]
+    pub redis_password: Option<Secret>,
+
+    /// How often the redis connection is pinged and re-established if it was lost
+    #[arg(long, default_value = "10")]
+    pub redis_health_check_interval_sec: u64,
+
     #[arg(long, default_value = "alephzero_next_block_number")]
     pub redis_azero_block_key: String,
 
+    /// Hashes of the AlephZero blocks the cached block number was advanced past
+    #[arg(long, default_value = "alephzero_block_checkpoints")]
+    pub redis_azero_checkpoints_key: String,
+
+    /// How many of the latest AlephZero block hashes are kept to roll the cached block number back to after a reorg
+    #[arg(long, default_value = "32", value_parser = clap::value_parser!(u64).range(1..))]
+    pub azero_checkpoints_kept: u64,
+
     #[arg(long, default_value = "ethereum_next_block_number")]
     pub redis_eth_block_key: String,
 
-    #[arg(long, default_value = "info")]
-    pub rust_log: log::Level,
+    #[arg(long, default_value = "ethereum_submitted_txs")]
+    pub redis_submitted_txs_key: String,
+
+    #[arg(long, default_value = "dead_letter_requests")]
+    pub redis_dead_letter_key: String,
+
+    #[arg(long, default_value = "relayed_requests")]
+    pub redis_relayed_requests_key: String,
+
+    /// Record every transfer request observed on AlephZero along with the outcome of its handling,
+    /// regardless of whether it was submitted. Not recorded if not set
+    #[arg(long, value_enum)]
+    pub audit_sink: Option<AuditSinkKind>,
+
+    #[arg(long, default_value = "audit.jsonl")]
+    pub audit_log_path: PathBuf,
+
+    #[arg(long, default_value = "audit_requests")]
+    pub redis_audit_stream_key: String,
+
+    /// Comma separated list of per-token amount bounds, keyed by the destination token address.
+    /// Requests outside of them are not forwarded, but put aside for a manual review
+    #[arg(long, value_delimiter = ',')]
+    pub token_limits: Vec<TokenLimit>,
+
+    /// Optional list of `<azero token address>=<eth token address>` pairs, for which the amount bridged
+    /// to AlephZero is periodically compared against the amount locked in the Ethereum contract
+    #[arg(long, use_value_delimiter = true, value_delimiter = ',')]
+    pub reconciled_tokens: Option<Vec<String>>,
+
+    #[arg(long, default_value = "600")]
+    pub reconciliation_interval_sec: u64,
+
+    /// Restart a listener which did not advance to the next block range for this long, resuming from the block cached in redis.
+    /// Has to exceed the time it takes Ethereum to finalize a new epoch, the wait for a busy handler does not count. Disabled if not set
+    #[arg(long)]
+    pub listener_stall_timeout_sec: Option<u64>,
+
+    /// Upper bound on the delay before rebooting the relayer, which doubles with every restart following a short run
+    #[arg(long, default_value = "600", value_parser = clap::value_parser!(u64).range(2..))]
+    pub max_restart_backoff_sec: u64,
+
+    /// Exit instead of rebooting the relayer once it was restarted this many times in a row, each after a short run.
+    /// Restarts forever if not set
+    #[arg(long)]
+    pub max_consecutive_restarts: Option<u32>,
+
+    /// On SIGTERM or SIGINT, how long to wait for the in-flight requests to be handled and their block numbers cached,
+    /// before exiting anyway
+    #[arg(long, default_value = "120")]
+    pub shutdown_grace_period_sec: u64,
+
+    /// Upper bound on the delay between the finalized head queries after a failure, which starts at one block time
+    /// and doubles with every failure in a row
+    #[arg(long, default_value = "60")]
+    pub finalized_head_max_backoff_sec: u64,
+
+    /// Number of failed finalized head queries in a row which are retried before opening the circuit breaker
+    #[arg(long, default_value = "3")]
+    pub finalized_head_max_retries: u32,
+
+    /// A request delivered again within this many seconds of the first delivery is dropped, whichever chain it comes from.
+    /// Set to 0 to hand every delivery to the handlers
+    #[arg(long, default_value = "3600")]
+    pub request_dedup_window_sec: u64,
+
+    /// How often to check that the signer's AlephZero account is still a member of the current committee
+    #[arg(long, default_value = "60")]
+    pub committee_membership_check_interval_sec: u64,
+
+    /// Number of submissions after startup which have to be confirmed manually through the admin endpoint
+    #[arg(long, default_value = "0")]
+    pub manual_confirm_first: usize,
+
+    #[arg(long, default_value = "127.0.0.1:9615")]
+    pub admin_address: String,
+
+    /// OpenTelemetry collector the metrics are pushed to over OTLP/HTTP, e.g. `http://localhost:4318`. Not pushed if not set
+    #[cfg(feature = "otel")]
+    #[arg(long)]
+    pub otlp_endpoint: Option<String>,
+
+    /// How often the metrics are pushed to the collector, a push which takes longer than that being given up on
+    #[cfg(feature = "otel")]
+    #[arg(long, default_value = "60", value_parser = clap::value_parser!(u64).range(1..))]
+    pub otlp_export_interval_sec: u64,
+
+    /// Bearer token required by the admin endpoints which change the relayer's state, e.g. `/confirm`.
+    /// Read-only endpoints like `/metrics` stay open. Without it all the endpoints are open. Best passed in the environment,
+    /// which unlike the command line is not visible to the other processes
+    #[arg(long, env = "ADMIN_TOKEN", hide_env_values = true)]
+    pub admin_token: Option<Secret>,
+
+    /// Requests accepted per minute from a single IP address by the admin server
+    #[arg(long, default_value = "60")]
+    pub admin_rate_limit_per_min: u32,
+
+    /// Default log level, taking precedence over the `RUST_LOG` environment variable. `info` if neither is set
+    #[arg(long)]
+    pub rust_log: Option<log::Level>,
+
+    /// Comma separated list of per-module log levels, e.g. `relayer::listeners=debug,ethers=warn`
+    #[arg(long, value_delimiter = ',')]
+    pub log_filters: Vec<LogFilter>,
+}
+
+impl Config {
+    /// Describes why a request of `amount` of `dest_token` can not be forwarded automatically, if it can't
+    pub fn token_limit_violation(&self, dest_token: &[u8; 32], amount: u128) -> Option<String> {
+        self.token_limits
+            .iter()
+            .find(|limit| &limit.token == dest_token)
+            .and_then(|limit| limit.violation(amount))
+    }
+
+    /// Describes why a request can not be forwarded automatically, if it can't
+    ///
+    /// A request to the zero receiver is never forwarded, as the tokens would be lost for good.
+    pub fn forwarding_violation(
+        &self,
+        dest_token: &[u8; 32],
+        amount: u128,
+        dest_receiver: &[u8; 32],
+    ) -> Option<String> {
+        if dest_receiver == &[0; 32] {
+            return Some("zero receiver address".to_owned());
+        }
+        self.token_limit_violation(dest_token, amount)
+    }
+
+    /// Composes the log filter, in the `RUST_LOG` syntax, out of the default level and the per-module overrides.
+    ///
+    /// Without `rust_log` the directives of the `RUST_LOG` environment variable, passed as `rust_log_env`, are the default.
+    pub fn log_filter(&self, rust_log_env: Option<String>) -> String {
+        let default = match (self.rust_log, rust_log_env) {
+            (Some(level), _) => level.to_string().to_lowercase(),
+            (None, Some(directives)) if !directives.is_empty() => directives,
+            (None, _) => "info".to_owned(),
+        };
+        let mut directives = vec![default];
+        directives.extend(self.log_filters.iter().map(|LogFilter { module, level }| {
+            format!("{module}={}", level.to_string().to_lowercase())
+        }));
+
+        directives.join(",")
+    }
+
+    /// Checks the invariants clap can not express, so that a misconfigured relayer exits instead of failing later on
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        // checking the metadata or a request does not sign anything
+        let signs = matches!(
+            self.command,
+            None | Some(Command::CheckGuardian) | Some(Command::Replay { .. })
+        );
+        match (self.signer_cid, self.dev) {
+            (None, false) if signs => return Err(ConfigError::NoSigningMode),
+            (Some(_), true) => return Err(ConfigError::ConflictingSigningModes),
+            _ => {}
+        }
+        if self.signer_cid.is_some()
+            && (self.eth_private_key_env.is_some() || self.eth_mnemonic_env.is_some())
+        {
+            return Err(ConfigError::EthKeyWithSigner);
+        }
+
+        for (flag, url) in [
+            ("azero-node-wss-url", &self.azero_node_wss_url),
+            ("eth-node-http-url", &self.eth_node_http_url),
+            ("redis-node", &self.redis_node),
+        ] {
+            if url.trim().is_empty() {
+                return Err(ConfigError::EmptyUrl(flag));
+            }
+        }
+
+        if let Some(Command::Replay { from, to, .. }) = self.command {
+            if from > to {
+                return Err(ConfigError::EmptyReplayRange { from, to });
+            }
+            if self.shadow_of.is_some() {
+                return Err(ConfigError::ReplayInShadowMode);
+            }
+        }
+
+        if self.offline_signing_dir.is_some() && self.offline_signer_address.is_none() {
+            return Err(ConfigError::NoOfflineSigner);
+        }
+
+        match &self.advisory_contract_addresses {
+            None if self.command.is_none() => Err(ConfigError::NoAdvisoryAddresses),
+            Some(_) if self.advisory_contract_metadata.trim().is_empty() => {
+                Err(ConfigError::NoAdvisoryMetadata)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn signer_timeouts(&self) -> Timeouts {
+        let limit = |seconds| (seconds > 0).then(|| Duration::from_secs(seconds));
+        let request = limit(self.signer_request_timeout_sec);
+
+        Timeouts {
+            connect: limit(self.signer_connect_timeout_sec),
+            read: request,
+            write: request,
+        }
+    }
+
+    /// Fee strategy derived from the gas price oracle, `None` if the fees are left to the Ethereum node
+    pub fn oracle_fee_strategy(&self) -> Option<OracleFeeStrategy> {
+        let gwei = |amount: u64| U256::from(amount) * U256::exp10(9);
+
+        match (self.eth_fee_strategy, self.eth_fee_wei_per_pico_azero) {
+            (EthFeeStrategyKind::Oracle, Some(wei_per_pico_azero)) => Some(OracleFeeStrategy {
+                wei_per_pico_azero,
+                max_fee_percent: self.eth_fee_oracle_percent,
+                min_max_fee_per_gas: gwei(self.eth_min_max_fee_per_gas_gwei),
+                max_max_fee_per_gas: gwei(self.eth_max_max_fee_per_gas_gwei),
+                max_priority_fee_per_gas: gwei(self.eth_max_priority_fee_per_gas_gwei),
+            }),
+            _ => None,
+        }
+    }
+
+    pub fn azero_block_time(&self) -> Duration {
+        Duration::from_secs(self.azero_block_prod_time_sec)
+    }
+
+    pub fn eth_block_time(&self) -> Duration {
+        Duration::from_secs(self.eth_block_prod_time_sec)
+    }
+
+    pub fn advisory_backoff(&self) -> PollBackoff {
+        let poll_interval = Duration::from_secs(self.advisory_poll_interval_sec);
+
+        PollBackoff::new(
+            poll_interval,
+            Duration::from_secs(self.advisory_max_backoff_sec).max(poll_interval),
+            self.advisory_max_retries,
+        )
+    }
+
+    /// Gas price ceiling of the submissions to Ethereum, in wei
+    pub fn eth_gas_price_ceiling(&self) -> Option<U256> {
+        self.eth_max_gas_price_gwei
+            .map(|gwei| U256::from(gwei) * U256::exp10(9))
+    }
+
+    /// Backoff of the gas price checks of a deferred submission, starting at one Ethereum block.
+    ///
+    /// A submission is deferred for as long as the gas price is too high, so no retries are counted.
+    pub fn gas_price_backoff(&self) -> PollBackoff {
+        PollBackoff::new(
+            self.eth_block_time(),
+            Duration::from_secs(self.eth_gas_price_max_backoff_sec).max(self.eth_block_time()),
+            0,
+        )
+    }
+
+    pub fn forwarding_backoff(&self) -> PollBackoff {
+        PollBackoff::new(
+            self.eth_block_time(),
+            Duration::from_secs(self.eth_forwarding_max_backoff_sec).max(self.eth_block_time()),
+            self.eth_forwarding_retries,
+        )
+    }
+
+    /// Backoff of the finalized head queries of a chain producing a block every `block_time`
+    pub fn finalized_head_backoff(&self, block_time: Duration) -> PollBackoff {
+        PollBackoff::new(
+            block_time,
+            Duration::from_secs(self.finalized_head_max_backoff_sec).max(block_time),
+            self.finalized_head_max_retries,
+        )
+    }
+}
+
+/// Config of the relayer with only the required arguments set on top of `args`, for the tests
+#[cfg(test)]
+pub(crate) fn test_config(args: &[&str]) -> Config {
+    use clap::Parser;
+
+    let required = [
+        "relayer",
+        "--name",
+        "test",
+        "--azero-contract-address",
+        "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM",
+        "--eth-contract-address",
+        "0x0000000000000000000000000000000000000000",
+    ];
+    Config::parse_from(required.iter().chain(args))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    #[test]
+    fn check_guardian_subcommand_is_parsed() {
+        assert_eq!(test_config(&[]).command, None);
+        assert_eq!(
+            test_config(&["check-guardian"]).command,
+            Some(Command::CheckGuardian)
+        );
+    }
+
+    #[test]
+    fn check_request_subcommand_takes_the_request_hash() {
+        assert_eq!(
+            test_config(&["check-request", &format!("0x{}", "ab".repeat(32))]).command,
+            Some(Command::CheckRequest {
+                request_hash: H256::repeat_byte(0xab)
+            })
+        );
+    }
+
+    #[test]
+    fn validate_metadata_subcommand_is_parsed() {
+        assert_eq!(
+            test_config(&["validate-metadata"]).command,
+            Some(Command::ValidateMetadata)
+        );
+    }
+
+    #[test]
+    fn dead_letters_subcommand_needs_no_signing_mode() {
+        let config = test_config(&["dead-letters"]);
+
+        assert_eq!(config.command, Some(Command::DeadLetters));
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn replay_subcommand_takes_the_chain_and_the_range() {
+        assert_eq!(
+            test_config(&["replay", "--chain", "azero", "--from", "10", "--to", "20"]).command,
+            Some(Command::Replay {
+                chain: Chain::Azero,
+                from: 10,
+                to: 20
+            })
+        );
+    }
+
+    #[test]
+    fn gas_price_ceiling_is_in_wei() {
+        assert_eq!(test_config(&[]).eth_gas_price_ceiling(), None);
+        assert_eq!(
+            test_config(&["--eth-max-gas-price-gwei", "150"]).eth_gas_price_ceiling(),
+            Some(U256::from(150_000_000_000u64))
+        );
+    }
+
+    #[test]
+    fn redis_password_is_redacted() {
+        let config = test_config(&["--redis-password", "hunter2"]);
+
+        assert_eq!(config.redis_password.as_ref().unwrap().expose(), "hunter2");
+        assert!(!format!("{config:?}").contains("hunter2"));
+    }
+
+    #[test]
+    fn redis_password_is_read_from_the_environment() {
+        std::env::set_var("REDIS_PASSWORD", "hunter3");
+        let config = test_config(&[]);
+        std::env::remove_var("REDIS_PASSWORD");
+
+        assert_eq!(config.redis_password.as_ref().unwrap().expose(), "hunter3");
+    }
+
+    #[test]
+    fn log_filter_defaults_to_rust_log() {
+        assert_eq!(test_config(&[]).log_filter(None), "info");
+        assert_eq!(
+            test_config(&["--rust-log", "debug"]).log_filter(None),
+            "debug"
+        );
+    }
+
+    #[test]
+    fn log_filter_falls_back_to_the_environment() {
+        let rust_log_env = || Some("debug,aleph-client=warn".to_owned());
+
+        assert_eq!(
+            test_config(&[]).log_filter(rust_log_env()),
+            "debug,aleph-client=warn"
+        );
+        assert_eq!(test_config(&[]).log_filter(Some(String::new())), "info");
+        assert_eq!(
+            test_config(&["--rust-log", "error"]).log_filter(rust_log_env()),
+            "error"
+        );
+    }
+
+    #[test]
+    fn log_filter_includes_module_levels() {
+        let config = test_config(&[
+            "--rust-log",
+            "warn",
+            "--log-filters",
+            "relayer::listeners::azero=debug,ethers=ERROR",
+        ]);
+
+        assert_eq!(
+            config.log_filter(None),
+            "warn,relayer::listeners::azero=debug,ethers=error"
+        );
+    }
+
+    #[test]
+    fn malformed_log_filter_is_rejected() {
+        assert!("relayer".parse::<LogFilter>().is_err());
+        assert!("relayer=loud".parse::<LogFilter>().is_err());
+    }
+
+    const TOKEN_A: &str = "0x0101010101010101010101010101010101010101010101010101010101010101";
+    const TOKEN_B: &str = "0x0202020202020202020202020202020202020202020202020202020202020202";
+
+    #[test]
+    fn token_limits_are_enforced_per_token() {
+        let config = test_config(&[
+            "--token-limits",
+            &format!("{TOKEN_A}=10:100,{TOKEN_B}=1000:2000"),
+        ]);
+
+        assert_eq!(config.token_limit_violation(&[1; 32], 50), None);
+        assert!(config.token_limit_violation(&[1; 32], 9).is_some());
+        assert!(config.token_limit_violation(&[1; 32], 101).is_some());
+
+        assert!(config.token_limit_violation(&[2; 32], 50).is_some());
+        assert_eq!(config.token_limit_violation(&[2; 32], 2000), None);
+        assert!(config.token_limit_violation(&[2; 32], 2001).is_some());
+    }
+
+    #[test]
+    fn tokens_without_limits_are_not_restricted() {
+        let config = test_config(&["--token-limits", &format!("{TOKEN_A}=10:100")]);
+
+        assert_eq!(config.token_limit_violation(&[2; 32], 0), None);
+        assert_eq!(config.token_limit_violation(&[2; 32], u128::MAX), None);
+    }
+
+    #[test]
+    fn requests_to_the_zero_receiver_are_not_forwarded() {
+        let config = test_config(&["--token-limits", &format!("{TOKEN_A}=10:100")]);
+
+        assert!(config
+            .forwarding_violation(&[2; 32], 50, &[0; 32])
+            .is_some());
+        assert!(config
+            .forwarding_violation(&[1; 32], 50, &[0; 32])
+            .is_some());
+        assert_eq!(config.forwarding_violation(&[1; 32], 50, &[3; 32]), None);
+        assert!(config
+            .forwarding_violation(&[1; 32], 101, &[3; 32])
+            .is_some());
+    }
+
+    #[test]
+    fn malformed_token_limit_is_rejected() {
+        assert!(format!("{TOKEN_A}=10").parse::<TokenLimit>().is_err());
+        assert!(format!("{TOKEN_A}=100:10").parse::<TokenLimit>().is_err());
+        assert!("0x0101=10:100".parse::<TokenLimit>().is_err());
+    }
+
+    #[test]
+    fn signer_ports_accept_lists_and_ranges() {
+        assert_eq!(*test_config(&[]).signer_ports, [1234]);
+        assert_eq!(
+            *test_config(&["--signer-port", "1234,1300-1302"]).signer_ports,
+            [1234, 1300, 1301, 1302]
+        );
+    }
+
+    #[test]
+    fn malformed_signer_ports_are_rejected() {
+        assert!("".parse::<SignerPorts>().is_err());
+        assert!("1236-1234".parse::<SignerPorts>().is_err());
+        assert!("1234-".parse::<SignerPorts>().is_err());
+        assert!("0-4294967295".parse::<SignerPorts>().is_err());
+    }
+
+    #[test]
+    fn zero_signer_timeout_waits_forever() {
+        let timeouts = test_config(&["--signer-connect-timeout-sec", "0"]).signer_timeouts();
+
+        assert_eq!(timeouts.connect, None);
+        assert_eq!(timeouts.read, Some(Duration::from_secs(30)));
+        assert_eq!(timeouts.write, Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn block_times_default_to_the_mainnet_ones() {
+        let config = test_config(&[]);
+
+        assert_eq!(config.azero_block_time(), Duration::from_secs(1));
+        assert_eq!(config.eth_block_time(), Duration::from_secs(12));
+        assert_eq!(config.azero_max_requests_per_block, 50);
+    }
+
+    #[test]
+    fn eth_finality_is_parsed() {
+        let expected = if cfg!(feature = "l2") {
+            FinalityStrategy::Confirmations(0)
+        } else {
+            FinalityStrategy::FinalizedTag
+        };
+
+        assert_eq!(test_config(&[]).eth_finality, expected);
+        assert_eq!(
+            test_config(&["--eth-finality", "confirmations:64"]).eth_finality,
+            FinalityStrategy::Confirmations(64)
+        );
+        assert_eq!(
+            test_config(&["--eth-finality", "finalized"]).eth_finality,
+            FinalityStrategy::FinalizedTag
+        );
+    }
+
+    #[test]
+    fn malformed_eth_finality_is_rejected() {
+        assert!("latest".parse::<FinalityStrategy>().is_err());
+        assert!("confirmations".parse::<FinalityStrategy>().is_err());
+        assert!("confirmations:-1".parse::<FinalityStrategy>().is_err());
+        assert!("finalized:1".parse::<FinalityStrategy>().is_err());
+    }
+
+    #[test]
+    fn zero_block_time_is_rejected() {
+        let required = [
+            "relayer",
+            "--name",
+            "test",
+            "--azero-contract-address",
+            "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM",
+            "--eth-contract-address",
+            "0x0000000000000000000000000000000000000000",
+            "--eth-block-prod-time-sec",
+            "0",
+        ];
+
+        assert!(Config::try_parse_from(required).is_err());
+    }
+
+    const ADVISORY: &str = "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM";
+
+    #[test]
+    fn relayer_with_a_signing_mode_and_advisories_is_valid() {
+        assert_eq!(
+            test_config(&["--dev", "--advisory-contract-addresses", ADVISORY]).validate(),
+            Ok(())
+        );
+        assert_eq!(
+            test_config(&[
+                "--signer-cid",
+                "3",
+                "--advisory-contract-addresses",
+                ADVISORY
+            ])
+            .validate(),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn exactly_one_signing_mode_is_required() {
+        assert_eq!(
+            test_config(&["--advisory-contract-addresses", ADVISORY]).validate(),
+            Err(ConfigError::NoSigningMode)
+        );
+        assert_eq!(
+            test_config(&[
+                "--dev",
+                "--signer-cid",
+                "3",
+                "--advisory-contract-addresses",
+                ADVISORY
+            ])
+            .validate(),
+            Err(ConfigError::ConflictingSigningModes)
+        );
+    }
+
+    #[test]
+    fn subcommands_which_do_not_sign_need_no_signing_mode() {
+        assert_eq!(test_config(&["validate-metadata"]).validate(), Ok(()));
+        assert_eq!(
+            test_config(&["check-guardian"]).validate(),
+            Err(ConfigError::NoSigningMode)
+        );
+    }
+
+    #[test]
+    fn replay_needs_a_signing_mode_and_a_range() {
+        let replay = |signing: &[&str], from: &str, to: &str| {
+            let range = ["replay", "--chain", "eth", "--from", from, "--to", to];
+            test_config(&[signing, &range[..]].concat()).validate()
+        };
+
+        assert_eq!(replay(&[], "1", "1"), Err(ConfigError::NoSigningMode));
+        assert_eq!(replay(&["--dev"], "1", "1"), Ok(()));
+        assert_eq!(
+            replay(&["--dev"], "2", "1"),
+            Err(ConfigError::EmptyReplayRange { from: 2, to: 1 })
+        );
+    }
+
+    #[test]
+    fn replay_is_not_run_in_the_shadow_mode() {
+        let config = test_config(&[
+            "--dev",
+            "--shadow-of",
+            "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM",
+            "replay",
+            "--chain",
+            "azero",
+            "--from",
+            "1",
+            "--to",
+            "1",
+        ]);
+
+        assert_eq!(config.validate(), Err(ConfigError::ReplayInShadowMode));
+    }
+
+    #[test]
+    fn offline_signing_needs_the_signer_address() {
+        let config = test_config(&[
+            "--dev",
+            "--advisory-contract-addresses",
+            ADVISORY,
+            "--offline-signing-dir",
+            "/tmp/offline",
+        ]);
+        assert_eq!(config.validate(), Err(ConfigError::NoOfflineSigner));
+
+        let config = test_config(&[
+            "--dev",
+            "--advisory-contract-addresses",
+            ADVISORY,
+            "--offline-signing-dir",
+            "/tmp/offline",
+            "--offline-signer-address",
+            "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266",
+        ]);
+        assert_eq!(config.validate(), Ok(()));
+        assert_eq!(
+            config.offline_signer_address,
+            Some(
+                "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266"
+                    .parse()
+                    .unwrap()
+            )
+        );
+    }
+
+    #[test]
+    fn empty_url_is_rejected() {
+        for flag in ["azero-node-wss-url", "eth-node-http-url", "redis-node"] {
+            let option = format!("--{flag}");
+            let config = test_config(&[
+                "--dev",
+                "--advisory-contract-addresses",
+                ADVISORY,
+                &option,
+                " ",
+            ]);
+
+            assert_eq!(config.validate(), Err(ConfigError::EmptyUrl(flag)));
+        }
+    }
+
+    #[test]
+    fn advisories_are_required_to_run_the_relayer() {
+        assert_eq!(
+            test_config(&["--dev"]).validate(),
+            Err(ConfigError::NoAdvisoryAddresses)
+        );
+        assert_eq!(
+            test_config(&[
+                "--dev",
+                "--advisory-contract-addresses",
+                ADVISORY,
+                "--advisory-contract-metadata",
+                ""
+            ])
+            .validate(),
+            Err(ConfigError::NoAdvisoryMetadata)
+        );
+    }
+
+    #[test]
+    fn eth_key_from_env_is_not_used_with_the_signer() {
+        for option in ["--eth-private-key-env", "--eth-mnemonic-env"] {
+            let config = test_config(&[
+                "--signer-cid",
+                "3",
+                "--advisory-contract-addresses",
+                ADVISORY,
+                option,
+                "ETH_KEY",
+            ]);
+
+            assert_eq!(config.validate(), Err(ConfigError::EthKeyWithSigner));
+        }
+    }
+
+    #[test]
+    fn eth_key_is_read_from_one_env_var_at_most() {
+        let required = [
+            "relayer",
+            "--name",
+            "test",
+            "--azero-contract-address",
+            "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM",
+            "--eth-contract-address",
+            "0x0000000000000000000000000000000000000000",
+            "--eth-private-key-env",
+            "ETH_PRIVATE_KEY",
+            "--eth-mnemonic-env",
+            "ETH_MNEMONIC",
+        ];
+
+        assert!(Config::try_parse_from(required).is_err());
+    }
 }
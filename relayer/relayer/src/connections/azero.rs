@@ -1,7 +1,10 @@
 use std::time::Duration;
 
-use contracts_azero_client::{AccountId, ClientConfig, KeyPair, MultiSignature, Signer};
-use signer_client::Client;
+use contracts_azero_client::{
+    AccountId, ClientConfig, ClientError, KeyPair, MultiSignature, Signer,
+};
+use log::warn;
+use signer_client::{Client, Timeouts};
 use subxt::ext::sp_core::Pair;
 use tokio::sync::Mutex;
 
@@ -18,9 +21,25 @@ pub async fn init(url: &str) -> AzeroWsConnection {
     .unwrap()
 }
 
+/// Hashes of the AlephZero blocks by number, to notice the chain changing under a cached block number
+#[async_trait::async_trait]
+pub trait AzeroBlockHashes: Send + Sync {
+    async fn block_hash(&self, block_number: u32) -> Result<Option<[u8; 32]>, ClientError>;
+}
+
+#[async_trait::async_trait]
+impl AzeroBlockHashes for AzeroWsConnection {
+    async fn block_hash(&self, block_number: u32) -> Result<Option<[u8; 32]>, ClientError> {
+        Ok(self.get_block_hash(block_number).await?.map(|hash| hash.0))
+    }
+}
+
 pub struct AzeroSignerClient {
     client: Mutex<Client>,
     account_id: AccountId,
+    cid: u32,
+    port: u32,
+    timeouts: Timeouts,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -33,19 +52,69 @@ pub enum Error {
 
     #[error("Rpc error: {0}")]
     Rpc(#[from] subxt::error::Error),
+
+    #[error("Signer signs with {got} after reconnecting, expected {expected}")]
+    AccountChanged { expected: AccountId, got: AccountId },
 }
 
 impl AzeroSignerClient {
-    pub async fn new(cid: u32, port: u32) -> Result<Self, Error> {
-        let mut client = Client::new(cid, port).await?;
+    /// Connects to the signer on the first of `ports` which answers
+    pub async fn new(cid: u32, ports: &[u32], timeouts: Timeouts) -> Result<Self, Error> {
+        let (port, mut client) = super::connect_signer(cid, ports, timeouts).await?;
         let account_id = client.azero_account_id().await?;
         let client = Mutex::new(client);
 
         Ok(Self {
             client,
             account_id: account_id.into(),
+            cid,
+            port,
+            timeouts,
         })
     }
+
+    /// Port the signer was found on
+    pub fn port(&self) -> u32 {
+        self.port
+    }
+
+    /// Reconnects and retries once if the connection to the signer was lost, e.g. by a restart of the enclave
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, Error> {
+        let mut client = self.client.lock().await;
+
+        match client.sign_azero(payload).await {
+            Err(signer_client::Error::IO(why)) => {
+                warn!(
+                    "Connection to the signer on port {} lost: {why}, reconnecting",
+                    self.port
+                );
+            }
+            Err(signer_client::Error::Closed) => {
+                warn!(
+                    "Signer on port {} closed the connection, reconnecting",
+                    self.port
+                );
+            }
+            result => return Ok(result?.into()),
+        }
+
+        *client = self.reconnect().await?;
+        Ok(client.sign_azero(payload).await?.into())
+    }
+
+    /// The signer must still sign with the same account, a different one means the key was swapped under the relayer
+    async fn reconnect(&self) -> Result<Client, Error> {
+        let mut client = Client::with_timeouts(self.cid, self.port, self.timeouts).await?;
+        let account_id: AccountId = client.azero_account_id().await?.into();
+
+        if account_id != self.account_id {
+            return Err(Error::AccountChanged {
+                expected: self.account_id.clone(),
+                got: account_id,
+            });
+        }
+        Ok(client)
+    }
 }
 
 pub enum AzeroSigner {
@@ -61,18 +130,23 @@ impl AzeroSigner {
         }
     }
 
-    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, anyhow::Error> {
+    /// Asks the signer for its current account id, which may differ from the one it was created with if the key was rotated
+    pub async fn fetch_account_id(&self) -> Result<AccountId, Error> {
         match self {
-            AzeroSigner::Dev(keypair) => Ok(keypair.signer().sign(payload).into()),
+            AzeroSigner::Dev(keypair) => Ok(keypair.account_id().clone()),
             AzeroSigner::Signer(signer) => {
                 let mut client = signer.client.lock().await;
-                let payload = payload.to_vec();
-                let signature = client.sign_azero(&payload).await?;
-
-                Ok(signature.into())
+                Ok(client.azero_account_id().await?.into())
             }
         }
     }
+
+    async fn sign(&self, payload: &[u8]) -> Result<MultiSignature, anyhow::Error> {
+        match self {
+            AzeroSigner::Dev(keypair) => Ok(keypair.signer().sign(payload).into()),
+            AzeroSigner::Signer(signer) => Ok(signer.sign(payload).await?),
+        }
+    }
 }
 
 #[async_trait::async_trait]
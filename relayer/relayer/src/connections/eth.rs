@@ -1,4 +1,4 @@
-use std::{fmt::Debug, sync::Arc, time::Duration};
+use std::{env, fmt::Debug};
 
 use ethers::{
     abi::Address,
@@ -12,17 +12,22 @@ use ethers::{
         SignerMiddleware,
     },
     providers::{Http, Provider, ProviderExt},
-    signers::{LocalWallet, Signer},
+    signers::{coins_bip39::English, LocalWallet, MnemonicBuilder, Signer},
     types::{
         transaction::{eip2718::TypedTransaction, eip712::Eip712},
         Signature,
     },
 };
-use log::{debug, warn};
+use log::warn;
+use signer_client::Timeouts;
 use thiserror::Error;
-use tokio::{sync::Mutex, time::sleep};
+use tokio::sync::Mutex;
+use zeroize::Zeroizing;
 
-use crate::{config::Config, listeners::ETH_BLOCK_PROD_TIME_SEC};
+use crate::config::{Config, FinalityStrategy};
+
+pub const DEV_MNEMONIC: &str =
+    "harsh master island dirt equip search awesome double turn crush wool grant";
 
 pub type EthConnection = Provider<Http>;
 pub type GasEscalatingEthConnection = GasEscalatorMiddleware<EthConnection>;
@@ -50,6 +55,9 @@ pub enum EthConnectionError {
 
     #[error("Local wallet error {0}")]
     LocalWallet(#[from] ethers::signers::WalletError),
+
+    #[error("Cannot read the Ethereum key from the environment variable {0}")]
+    KeyEnv(String, #[source] env::VarError),
 }
 
 #[derive(Debug, Error)]
@@ -211,6 +219,54 @@ pub async fn connect(config: &Config) -> EthConnection {
     Provider::<Http>::connect(&config.eth_node_http_url).await
 }
 
+/// Where the key of the local Ethereum wallet is read from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LocalKeySource {
+    /// Hex encoded private key in the named environment variable
+    PrivateKeyEnv(String),
+    /// Mnemonic in the named environment variable
+    MnemonicEnv(String),
+    /// The well known development mnemonic
+    DevMnemonic,
+}
+
+impl LocalKeySource {
+    pub fn from_config(config: &Config) -> Self {
+        match (&config.eth_private_key_env, &config.eth_mnemonic_env) {
+            (Some(var), _) => Self::PrivateKeyEnv(var.clone()),
+            (None, Some(var)) => Self::MnemonicEnv(var.clone()),
+            (None, None) => Self::DevMnemonic,
+        }
+    }
+
+    /// Builds the wallet, with mnemonics derived at `index`.
+    ///
+    /// `read_var` looks up environment variables, the secret read from one is zeroized once the wallet is built.
+    pub fn wallet(
+        &self,
+        index: u32,
+        read_var: impl Fn(&str) -> Result<String, env::VarError>,
+    ) -> Result<LocalWallet, EthConnectionError> {
+        let read = |var: &str| {
+            read_var(var)
+                .map(Zeroizing::new)
+                .map_err(|why| EthConnectionError::KeyEnv(var.to_owned(), why))
+        };
+        let from_mnemonic = |phrase: &str| {
+            MnemonicBuilder::<English>::default()
+                .phrase(phrase)
+                .index(index)?
+                .build()
+        };
+
+        Ok(match self {
+            Self::PrivateKeyEnv(var) => read(var)?.trim().parse::<LocalWallet>()?,
+            Self::MnemonicEnv(var) => from_mnemonic(read(var)?.trim())?,
+            Self::DevMnemonic => from_mnemonic(DEV_MNEMONIC)?,
+        })
+    }
+}
+
 pub async fn with_local_wallet(
     connection: GasEscalatingEthConnection,
     wallet: LocalWallet,
@@ -224,9 +280,10 @@ pub async fn with_local_wallet(
 pub async fn with_signer(
     connection: GasEscalatingEthConnection,
     cid: u32,
-    port: u32,
+    ports: &[u32],
+    timeouts: Timeouts,
 ) -> Result<SignedEthConnection, EthConnectionError> {
-    let mut client = signer_client::Client::new(cid, port).await?;
+    let (_, mut client) = super::connect_signer(cid, ports, timeouts).await?;
     let address = client.eth_address().await?;
     let client = Mutex::new(client);
     let nonce_manager = with_nonce_manager(connection, address).await?;
@@ -256,46 +313,171 @@ pub async fn with_gas_escalator(connection: EthConnection) -> GasEscalatingEthCo
     GasEscalatorMiddleware::new(connection, escalator, Frequency::Duration(15000))
 }
 
-#[cfg(feature = "l2")]
-pub async fn get_next_finalized_block_number(
-    eth_connection: Arc<EthConnection>,
-    not_older_than: u32,
-) -> u32 {
-    // In L2 context we treat latest block as finalized.
-    get_block_not_older_than(eth_connection, not_older_than, BlockNumber::Latest).await
-}
+/// Queries the node once for the number of the latest block which is final according to `finality`.
+pub async fn get_finalized_block_number<M: Middleware>(
+    eth_connection: &M,
+    finality: FinalityStrategy,
+) -> Option<u32> {
+    let block_number = match finality {
+        FinalityStrategy::Confirmations(confirmations) => eth_connection
+            .get_block_number()
+            .await
+            .map(|latest| Some(latest.as_u64().saturating_sub(confirmations))),
+        FinalityStrategy::FinalizedTag => eth_connection
+            .get_block(BlockNumber::Finalized)
+            .await
+            .map(|block| block.map(|block| block.number.expect("Block has a number.").as_u64())),
+    };
 
-#[cfg(not(feature = "l2"))]
-pub async fn get_next_finalized_block_number(
-    eth_connection: Arc<EthConnection>,
-    not_older_than: u32,
-) -> u32 {
-    // In ethereum l1 context we treat finalized block as, well, finalized :).
-    get_block_not_older_than(eth_connection, not_older_than, BlockNumber::Finalized).await
+    match block_number {
+        Ok(Some(block_number)) => Some(block_number as u32),
+        Ok(None) => {
+            warn!("No block found.");
+            None
+        }
+        Err(e) => {
+            warn!("Client error when getting block: {e}");
+            None
+        }
+    }
 }
 
-pub async fn get_block_not_older_than(
-    eth_connection: Arc<EthConnection>,
-    not_older_than: u32,
-    block: BlockNumber,
-) -> u32 {
-    loop {
-        match eth_connection.get_block(block).await {
-            Ok(Some(block)) => {
-                let block_number = block.number.expect("Block has a number.").as_u32();
-                if block_number >= not_older_than {
-                    return block_number;
-                }
-            }
-            Ok(None) => {
-                warn!("No block found.");
-            }
-            Err(e) => {
-                warn!("Client error when getting block: {e}");
-            }
-        };
+#[cfg(test)]
+mod tests {
+    use ethers::{
+        providers::MockProvider,
+        types::{Block, U64},
+    };
+
+    use super::*;
+    use crate::config::test_config;
+
+    const PRIVATE_KEY: &str = "0xac0974bec39a17e36ba4a6b4d238ff944bacb478cbed5efcae784d7bf4f2ff80";
+    const MNEMONIC: &str = "test test test test test test test test test test test junk";
+    // account of both the private key and the mnemonic at index 0
+    const ADDRESS: &str = "0xf39fd6e51aad88f6f4ce6ab8827279cfffb92266";
+
+    fn env(vars: &[(&str, &str)]) -> impl Fn(&str) -> Result<String, env::VarError> {
+        let vars: Vec<_> = vars
+            .iter()
+            .map(|&(name, value)| (name.to_owned(), value.to_owned()))
+            .collect();
+        move |var| {
+            vars.iter()
+                .find(|(name, _)| name == var)
+                .map(|(_, value)| value.clone())
+                .ok_or(env::VarError::NotPresent)
+        }
+    }
+
+    #[test]
+    fn private_key_is_read_from_the_env() {
+        let source =
+            LocalKeySource::from_config(&test_config(&["--eth-private-key-env", "ETH_KEY"]));
+
+        let wallet = source
+            .wallet(0, env(&[("ETH_KEY", &format!("{PRIVATE_KEY}\n"))]))
+            .unwrap();
+
+        assert_eq!(source, LocalKeySource::PrivateKeyEnv("ETH_KEY".to_owned()));
+        assert_eq!(wallet.address(), ADDRESS.parse().unwrap());
+    }
+
+    #[test]
+    fn mnemonic_is_read_from_the_env() {
+        let source =
+            LocalKeySource::from_config(&test_config(&["--eth-mnemonic-env", "ETH_MNEMONIC"]));
+        let env = env(&[("ETH_MNEMONIC", MNEMONIC)]);
+
+        assert_eq!(
+            source,
+            LocalKeySource::MnemonicEnv("ETH_MNEMONIC".to_owned())
+        );
+        assert_eq!(
+            source.wallet(0, &env).unwrap().address(),
+            ADDRESS.parse().unwrap()
+        );
+        assert_ne!(
+            source.wallet(1, &env).unwrap().address(),
+            ADDRESS.parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn dev_mnemonic_is_used_without_an_env_var() {
+        let source = LocalKeySource::from_config(&test_config(&["--dev"]));
+
+        let wallet = source.wallet(0, env(&[])).unwrap();
+
+        assert_eq!(source, LocalKeySource::DevMnemonic);
+        assert_eq!(
+            wallet.address(),
+            MnemonicBuilder::<English>::default()
+                .phrase(DEV_MNEMONIC)
+                .build()
+                .unwrap()
+                .address()
+        );
+    }
+
+    #[test]
+    fn missing_env_var_is_named() {
+        let source = LocalKeySource::PrivateKeyEnv("ETH_KEY".to_owned());
+
+        let why = source.wallet(0, env(&[])).unwrap_err();
+
+        assert!(matches!(
+            why,
+            EthConnectionError::KeyEnv(var, env::VarError::NotPresent) if var == "ETH_KEY"
+        ));
+    }
+
+    #[tokio::test]
+    async fn finalized_tag_is_the_block_the_node_reports() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(Block::<H256> {
+            number: Some(U64::from(90)),
+            ..Default::default()
+        })
+        .unwrap();
+
+        let finalized = get_finalized_block_number(&provider, FinalityStrategy::FinalizedTag).await;
+
+        assert_eq!(finalized, Some(90));
+        mock.assert_request("eth_getBlockByNumber", ("finalized", false))
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn confirmations_are_counted_from_the_latest_block() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::from(100)).unwrap();
+
+        let finalized =
+            get_finalized_block_number(&provider, FinalityStrategy::Confirmations(12)).await;
+
+        assert_eq!(finalized, Some(88));
+        mock.assert_request("eth_blockNumber", ()).unwrap();
+    }
+
+    #[tokio::test]
+    async fn chain_shorter_than_the_confirmations_is_final_at_genesis() {
+        let (provider, mock) = Provider::mocked();
+        mock.push(U64::from(5)).unwrap();
+
+        let finalized =
+            get_finalized_block_number(&provider, FinalityStrategy::Confirmations(12)).await;
+
+        assert_eq!(finalized, Some(0));
+    }
+
+    #[tokio::test]
+    async fn node_error_is_no_finalized_block() {
+        let (provider, _mock) = Provider::<MockProvider>::mocked();
 
-        debug!("Waiting for a next block");
-        sleep(Duration::from_secs(ETH_BLOCK_PROD_TIME_SEC)).await;
+        assert_eq!(
+            get_finalized_block_number(&provider, FinalityStrategy::FinalizedTag).await,
+            None
+        );
     }
 }
@@ -1,2 +1,89 @@
+use std::{future::Future, io};
+
+use log::{info, warn};
+use signer_client::Timeouts;
+
 pub mod azero;
 pub mod eth;
+
+/// Connects to the signer on the first of `ports` which passes the health check, returning the port along with the client
+pub async fn connect_signer(
+    cid: u32,
+    ports: &[u32],
+    timeouts: Timeouts,
+) -> Result<(u32, signer_client::Client), signer_client::Error> {
+    probe_ports(ports, |port| async move {
+        let mut client = signer_client::Client::with_timeouts(cid, port, timeouts).await?;
+        let account_id = client.health_check().await?;
+        info!("Signer on port {port} signs with AlephZero account {account_id}");
+        Ok(client)
+    })
+    .await
+}
+
+/// Tries `connect` on the ports in order until it succeeds, failing with the error of the last port otherwise
+async fn probe_ports<T, F, Fut>(
+    ports: &[u32],
+    mut connect: F,
+) -> Result<(u32, T), signer_client::Error>
+where
+    F: FnMut(u32) -> Fut,
+    Fut: Future<Output = Result<T, signer_client::Error>>,
+{
+    let mut last_error = None;
+    for &port in ports {
+        match connect(port).await {
+            Ok(connected) => {
+                info!("Connected to the signer on port {port}");
+                return Ok((port, connected));
+            }
+            Err(why) => {
+                warn!("Signer does not answer on port {port}: {why}");
+                last_error = Some(why);
+            }
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "no signer ports to probe").into()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn probing_skips_dead_ports() {
+        let mut probed = Vec::new();
+
+        let connected = probe_ports(&[1234, 1235, 1236], |port| {
+            probed.push(port);
+            async move {
+                match port {
+                    1234 => Err(io::Error::from(io::ErrorKind::ConnectionRefused).into()),
+                    _ => Ok(format!("client on {port}")),
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(connected, (1235, "client on 1235".to_string()));
+        assert_eq!(probed, [1234, 1235]);
+    }
+
+    #[tokio::test]
+    async fn probing_fails_when_no_port_answers() {
+        let result = probe_ports(&[1234, 1235], |_| async {
+            Err::<(), _>(io::Error::from(io::ErrorKind::ConnectionRefused).into())
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(signer_client::Error::IO(why)) if why.kind() == io::ErrorKind::ConnectionRefused
+        ));
+        assert!(probe_ports(&[], |_| async { Ok(()) }).await.is_err());
+    }
+}
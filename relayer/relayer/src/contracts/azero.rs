@@ -1,13 +1,14 @@
 use std::{
     collections::HashMap,
-    str,
+    fmt, str,
     str::{FromStr, Utf8Error},
 };
 
+use anyhow::bail;
 use contracts_azero_client::{
     contract_transcode::{Value, Value::Seq},
-    AccountId, Client, ClientWithSigner, ContractInstance, ExecCallParams, ReadonlyCallParams,
-    Weight,
+    AccountId, Client, ClientWithSigner, ContractInstance, ConvertibleValue, ExecCallParams,
+    ReadonlyCallParams, Weight,
 };
 use log::{debug, error};
 use thiserror::Error;
@@ -32,6 +33,85 @@ pub enum AzeroContractError {
 
     #[error("Missing or invalid field")]
     MissingOrInvalidField(String),
+
+    #[error("Contract reverted with {0:?}")]
+    ContractReverted(MostError),
+
+    #[error("Ownable2Step call failed with {0:?}")]
+    Ownable(OwnableError),
+}
+
+impl AzeroContractError {
+    /// Types the error of a reverted call if the Most contract reverted it with an error the relayer knows
+    fn from_call(why: anyhow::Error) -> Self {
+        match MostError::from_revert(&why.to_string()) {
+            Some(reason) => Self::ContractReverted(reason),
+            None => Self::ContractCall(why),
+        }
+    }
+}
+
+/// Errors of the AlephZero Most contract the relayer tells apart when a call reverts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MostError {
+    NotInCommittee,
+    NoSuchCommittee,
+    HashDoesNotMatchData,
+    UnsupportedPair,
+    RequestAlreadySigned,
+    IsHalted,
+    SignaturesFrozen,
+    ZeroTransferAmount,
+    InvalidReceiver,
+    AmountAboveMaximum,
+    InvalidThreshold,
+    DuplicateCommitteeMember,
+}
+
+const MOST_ERRORS: &[(&str, MostError)] = &[
+    ("NotInCommittee", MostError::NotInCommittee),
+    ("NoSuchCommittee", MostError::NoSuchCommittee),
+    ("HashDoesNotMatchData", MostError::HashDoesNotMatchData),
+    ("UnsupportedPair", MostError::UnsupportedPair),
+    ("RequestAlreadySigned", MostError::RequestAlreadySigned),
+    ("IsHalted", MostError::IsHalted),
+    ("SignaturesFrozen", MostError::SignaturesFrozen),
+    ("ZeroTransferAmount", MostError::ZeroTransferAmount),
+    ("InvalidReceiver", MostError::InvalidReceiver),
+    ("AmountAboveMaximum", MostError::AmountAboveMaximum),
+    ("InvalidThreshold", MostError::InvalidThreshold),
+    (
+        "DuplicateCommitteeMember",
+        MostError::DuplicateCommitteeMember,
+    ),
+];
+
+impl MostError {
+    /// Finds the error in the message of a reverted dry run, which carries the decoded return value of the call
+    fn from_revert(message: &str) -> Option<Self> {
+        let decoded = message.strip_prefix("Dry-run call reverted")?;
+
+        decoded
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find_map(|word| {
+                MOST_ERRORS
+                    .iter()
+                    .find(|(name, _)| *name == word)
+                    .map(|&(_, error)| error)
+            })
+    }
+
+    /// Whether the same call can never succeed, as opposed to the state of the contract which may still change
+    pub fn is_permanent(&self) -> bool {
+        matches!(
+            self,
+            Self::HashDoesNotMatchData
+                | Self::UnsupportedPair
+                | Self::ZeroTransferAmount
+                | Self::InvalidReceiver
+                | Self::AmountAboveMaximum
+        )
+    }
 }
 
 pub struct AdvisoryInstance {
@@ -64,6 +144,30 @@ impl AdvisoryInstance {
     }
 }
 
+pub struct GasPriceOracleInstance {
+    pub contract: ContractInstance,
+}
+
+impl GasPriceOracleInstance {
+    pub fn new(address: AccountId, metadata_path: &str) -> Result<Self, AzeroContractError> {
+        Ok(Self {
+            contract: ContractInstance::new(address, metadata_path)?,
+        })
+    }
+
+    /// Price of one unit of Ethereum gas in picoAZERO and the timestamp of its last update in milliseconds
+    pub async fn price(&self, connection: &Client) -> Result<(u128, u64), AzeroContractError> {
+        Ok(self
+            .contract
+            .read0(
+                connection,
+                "EthGasPriceOracle::get_price",
+                Default::default(),
+            )
+            .await?)
+    }
+}
+
 pub struct MostInstance {
     pub contract: ContractInstance,
     pub ref_time_limit: u64,
@@ -116,7 +220,7 @@ impl MostInstance {
             .contract
             .exec(signed_connection, "receive_request", &args, params)
             .await
-            .map_err(AzeroContractError::ContractCall);
+            .map_err(AzeroContractError::from_call);
         debug!("receive_request: {:?}", call_result);
         call_result
     }
@@ -144,6 +248,116 @@ impl MostInstance {
         call_result
     }
 
+    /// Rotates the committee to `members` signing with `threshold`, which requires the owner's signature
+    /// and the contract to be halted
+    pub async fn set_committee(
+        &self,
+        signed_connection: &ClientWithSigner<AzeroSigner>,
+        members: &[AccountId],
+        threshold: u128,
+    ) -> Result<(), AzeroContractError> {
+        let gas_limit = Weight {
+            ref_time: self.ref_time_limit,
+            proof_size: self.proof_size_limit,
+        };
+        let args = [committee_arg(members), threshold.to_string()];
+        let params = ExecCallParams::new().gas_limit(gas_limit);
+
+        // Exec does dry run first, so a duplicate member is reported before anything is submitted
+        let call_result = self
+            .contract
+            .exec(signed_connection, "set_committee", &args, params)
+            .await
+            .map_err(AzeroContractError::from_call);
+        debug!("set_committee: {:?}", call_result);
+        call_result
+    }
+
+    /// Pays out the outstanding rewards of `member_id` in the committee with `committee_id`
+    pub async fn payout_rewards(
+        &self,
+        signed_connection: &ClientWithSigner<AzeroSigner>,
+        committee_id: u128,
+        member_id: AccountId,
+    ) -> Result<(), AzeroContractError> {
+        let gas_limit = Weight {
+            ref_time: self.ref_time_limit,
+            proof_size: self.proof_size_limit,
+        };
+        let args = [committee_id.to_string(), member_id.to_string()];
+        let params = ExecCallParams::new().gas_limit(gas_limit);
+
+        // Exec does dry run first, so there's no need to repeat it here
+        let call_result = self
+            .contract
+            .exec(signed_connection, "payout_rewards", &args, params)
+            .await
+            .map_err(AzeroContractError::ContractCall);
+        debug!("payout_rewards: {:?}", call_result);
+        call_result
+    }
+
+    /// Dry run of `payout_rewards`, which fails the way the payout would without submitting anything
+    pub async fn dry_run_payout_rewards(
+        &self,
+        connection: &Client,
+        committee_id: u128,
+        member_id: AccountId,
+    ) -> Result<(), AzeroContractError> {
+        Ok(self
+            .contract
+            .read::<_, Result<(), _>>(
+                connection,
+                "payout_rewards",
+                &[committee_id.to_string(), member_id.to_string()],
+                Default::default(),
+            )
+            .await??)
+    }
+
+    /// Rewards collected by the committee with `committee_id`, in AZERO
+    pub async fn get_collected_committee_rewards(
+        &self,
+        connection: &Client,
+        committee_id: u128,
+    ) -> Result<u128, AzeroContractError> {
+        Ok(self
+            .contract
+            .read(
+                connection,
+                "get_collected_committee_rewards",
+                &[committee_id.to_string()],
+                Default::default(),
+            )
+            .await?)
+    }
+
+    /// Rewards of `member_id` in the committee with `committee_id` which are not paid out yet, in AZERO
+    pub async fn get_outstanding_member_rewards(
+        &self,
+        connection: &Client,
+        committee_id: u128,
+        member_id: AccountId,
+    ) -> Result<u128, AzeroContractError> {
+        Ok(self
+            .contract
+            .read::<_, Result<u128, _>>(
+                connection,
+                "get_outstanding_member_rewards",
+                &[committee_id.to_string(), member_id.to_string()],
+                Default::default(),
+            )
+            .await??)
+    }
+
+    /// Fee charged on top of the transferred amount of a request to Ethereum, in AZERO
+    pub async fn get_base_fee(&self, connection: &Client) -> Result<u128, AzeroContractError> {
+        Ok(self
+            .contract
+            .read0::<Result<u128, _>>(connection, "get_base_fee", Default::default())
+            .await??)
+    }
+
     pub async fn is_halted(&self, connection: &Client) -> Result<bool, AzeroContractError> {
         Ok(self
             .contract
@@ -191,6 +405,22 @@ impl MostInstance {
             .await??)
     }
 
+    pub async fn get_net_bridged(
+        &self,
+        connection: &Client,
+        token: AccountId,
+    ) -> Result<i128, AzeroContractError> {
+        Ok(self
+            .contract
+            .read(
+                connection,
+                "get_net_bridged",
+                &[token.to_string()],
+                Default::default(),
+            )
+            .await?)
+    }
+
     pub async fn is_in_committee(
         &self,
         connection: &Client,
@@ -207,6 +437,165 @@ impl MostInstance {
             )
             .await?)
     }
+
+    pub async fn request_status(
+        &self,
+        connection: &Client,
+        request_hash: [u8; 32],
+    ) -> Result<RequestStatus, AzeroContractError> {
+        Ok(self
+            .contract
+            .read(
+                connection,
+                "request_status",
+                &[bytes32_to_str(&request_hash)],
+                Default::default(),
+            )
+            .await?)
+    }
+
+    /// Largest amount of the token a single request can transfer to AlephZero, `None` if there is no limit
+    pub async fn max_receive_amount(
+        &self,
+        connection: &Client,
+        dest_token_address: [u8; 32],
+    ) -> Result<Option<u128>, AzeroContractError> {
+        Ok(self
+            .contract
+            .read(
+                connection,
+                "get_max_receive_amount",
+                &[bytes32_to_str(&dest_token_address)],
+                Default::default(),
+            )
+            .await?)
+    }
+
+    /// Address of the oracle the contract charges the transfer fees by, `None` if not set
+    pub async fn gas_price_oracle(
+        &self,
+        connection: &Client,
+    ) -> Result<Option<AccountId>, AzeroContractError> {
+        Ok(self
+            .contract
+            .read0::<Result<Option<AccountId>, _>>(
+                connection,
+                "get_gas_price_oracle",
+                Default::default(),
+            )
+            .await??)
+    }
+
+    pub async fn owner(&self, connection: &Client) -> Result<AccountId, AzeroContractError> {
+        Ok(self
+            .contract
+            .read0::<Result<AccountId, _>>(
+                connection,
+                "Ownable2Step::get_owner",
+                Default::default(),
+            )
+            .await??)
+    }
+
+    /// Account the ownership is being transferred to, `None` if no transfer is pending
+    pub async fn pending_owner(
+        &self,
+        connection: &Client,
+    ) -> Result<Option<AccountId>, AzeroContractError> {
+        let PendingOwner(pending_owner) = self
+            .contract
+            .read0(
+                connection,
+                "Ownable2Step::get_pending_owner",
+                Default::default(),
+            )
+            .await?;
+
+        match pending_owner {
+            Ok(pending_owner) => Ok(Some(pending_owner)),
+            Err(OwnableError::NoPendingOwner) => Ok(None),
+            Err(why) => Err(AzeroContractError::Ownable(why)),
+        }
+    }
+
+    /// Members of the committee with `committee_id` in the order they were set in, empty if no such committee was ever set
+    pub async fn committee(
+        &self,
+        connection: &Client,
+        committee_id: u128,
+    ) -> Result<Vec<AccountId>, AzeroContractError> {
+        Ok(self
+            .contract
+            .read(
+                connection,
+                "get_committee",
+                &[committee_id.to_string()],
+                Default::default(),
+            )
+            .await?)
+    }
+
+    /// Signature threshold of the committee with `committee_id`, `None` if no such committee was ever set
+    pub async fn committee_threshold(
+        &self,
+        connection: &Client,
+        committee_id: u128,
+    ) -> Result<Option<u128>, AzeroContractError> {
+        Ok(self
+            .contract
+            .read(
+                connection,
+                "get_committee_threshold",
+                &[committee_id.to_string()],
+                Default::default(),
+            )
+            .await?)
+    }
+}
+
+/// Status of a request in the AlephZero contract
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RequestStatus {
+    Unknown,
+    Pending { collected_signatures: u32 },
+    Processed,
+}
+
+impl TryFrom<ConvertibleValue> for RequestStatus {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvertibleValue) -> anyhow::Result<Self> {
+        match &value.0 {
+            Value::Tuple(tuple) if tuple.ident().as_deref() == Some("Processed") => {
+                Ok(RequestStatus::Processed)
+            }
+            Value::Tuple(tuple) if tuple.ident().as_deref() == Some("RequestHashNotKnown") => {
+                Ok(RequestStatus::Unknown)
+            }
+            Value::Map(map) if map.ident().as_deref() == Some("Pending") => {
+                match map.get_by_str("collected_signatures") {
+                    Some(collected_signatures) => Ok(RequestStatus::Pending {
+                        collected_signatures: ConvertibleValue(collected_signatures.clone())
+                            .try_into()?,
+                    }),
+                    None => bail!("Missing collected_signatures in {:?}", &value),
+                }
+            }
+            _ => bail!("Expected {:?} to be a RequestStatus", &value),
+        }
+    }
+}
+
+impl fmt::Display for RequestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestStatus::Unknown => write!(f, "unknown"),
+            RequestStatus::Pending {
+                collected_signatures,
+            } => write!(f, "pending ({collected_signatures} signatures collected)"),
+            RequestStatus::Processed => write!(f, "processed"),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -236,6 +625,120 @@ pub fn get_request_event_data(
     })
 }
 
+/// Value of a parameter of the Most contract, as reported by its `ConfigChanged` event
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigValue {
+    Amount(u128),
+    Account(Option<AccountId>),
+    Flag(bool),
+}
+
+impl TryFrom<ConvertibleValue> for ConfigValue {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvertibleValue) -> anyhow::Result<Self> {
+        match &value.0 {
+            Value::Tuple(tuple) if tuple.values().count() == 1 => {
+                let inner = ConvertibleValue(tuple.values().next().unwrap().clone());
+                match tuple.ident().as_deref() {
+                    Some("Amount") => Ok(ConfigValue::Amount(inner.try_into()?)),
+                    Some("Account") => Ok(ConfigValue::Account(inner.try_into()?)),
+                    Some("Flag") => Ok(ConfigValue::Flag(inner.try_into()?)),
+                    _ => bail!("Expected {:?} to be a ConfigValue", &value),
+                }
+            }
+            _ => bail!("Expected {:?} to be a ConfigValue", &value),
+        }
+    }
+}
+
+impl fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigValue::Amount(amount) => write!(f, "{amount}"),
+            ConfigValue::Account(Some(account)) => write!(f, "{account}"),
+            ConfigValue::Account(None) => write!(f, "none"),
+            ConfigValue::Flag(flag) => write!(f, "{flag}"),
+        }
+    }
+}
+
+/// Error of the `Ownable2Step` messages of the Most contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OwnableError {
+    CallerNotOwner(AccountId),
+    CallerNotPendingOwner(AccountId),
+    NoPendingOwner,
+    Custom(String),
+}
+
+impl TryFrom<ConvertibleValue> for OwnableError {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvertibleValue) -> anyhow::Result<Self> {
+        match &value.0 {
+            Value::Tuple(tuple) if tuple.ident().as_deref() == Some("NoPendingOwner") => {
+                Ok(OwnableError::NoPendingOwner)
+            }
+            Value::Tuple(tuple) if tuple.values().count() == 1 => {
+                let inner = tuple.values().next().unwrap().clone();
+                match tuple.ident().as_deref() {
+                    Some("CallerNotOwner") => Ok(OwnableError::CallerNotOwner(
+                        ConvertibleValue(inner).try_into()?,
+                    )),
+                    Some("CallerNotPendingOwner") => Ok(OwnableError::CallerNotPendingOwner(
+                        ConvertibleValue(inner).try_into()?,
+                    )),
+                    Some("Custom") => Ok(OwnableError::Custom(match inner {
+                        Value::String(message) => message,
+                        inner => inner.to_string(),
+                    })),
+                    _ => bail!("Expected {:?} to be an Ownable2StepError", &value),
+                }
+            }
+            _ => bail!("Expected {:?} to be an Ownable2StepError", &value),
+        }
+    }
+}
+
+/// Result of `Ownable2Step::get_pending_owner`, with its error decoded rather than only described
+struct PendingOwner(Result<AccountId, OwnableError>);
+
+impl TryFrom<ConvertibleValue> for PendingOwner {
+    type Error = anyhow::Error;
+
+    fn try_from(value: ConvertibleValue) -> anyhow::Result<Self> {
+        match &value.0 {
+            Value::Tuple(tuple) if tuple.values().count() == 1 => {
+                let inner = ConvertibleValue(tuple.values().next().unwrap().clone());
+                match tuple.ident().as_deref() {
+                    Some("Ok") => Ok(PendingOwner(Ok(inner.try_into()?))),
+                    Some("Err") => Ok(PendingOwner(Err(inner.try_into()?))),
+                    _ => bail!("Expected {:?} to be an Ok(_) or Err(_) tuple", &value),
+                }
+            }
+            _ => bail!("Expected {:?} to be an Ok(_) or Err(_) tuple", &value),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ConfigChangedData {
+    pub field: String,
+    pub old: ConfigValue,
+    pub new: ConfigValue,
+}
+
+pub fn get_config_changed_event_data(
+    data: &HashMap<String, Value>,
+) -> Result<ConfigChangedData, AzeroContractError> {
+    let field: String = decode_string_field(data, "field")?;
+    let old = decode_config_value_field(data, "old")?;
+    let new = decode_config_value_field(data, "new")?;
+
+    Ok(ConfigChangedData { field, old, new })
+}
+
 fn decode_seq_field(
     data: &HashMap<String, Value>,
     field: &str,
@@ -284,6 +787,193 @@ fn decode_uint_field(
     }
 }
 
+fn decode_config_value_field(
+    data: &HashMap<String, Value>,
+    field: &str,
+) -> Result<ConfigValue, AzeroContractError> {
+    data.get(field)
+        .and_then(|value| ConvertibleValue(value.clone()).try_into().ok())
+        .ok_or_else(|| {
+            AzeroContractError::MissingOrInvalidField(format!(
+                "Data field {:?} couldn't be found or has incorrect format",
+                field
+            ))
+        })
+}
+
+fn decode_string_field(
+    data: &HashMap<String, Value>,
+    field: &str,
+) -> Result<String, AzeroContractError> {
+    if let Some(Value::String(x)) = data.get(field) {
+        Ok(x.clone())
+    } else {
+        Err(AzeroContractError::MissingOrInvalidField(format!(
+            "Data field {:?} couldn't be found or has incorrect format",
+            field
+        )))
+    }
+}
+
 fn bytes32_to_str(data: &[u8; 32]) -> String {
     "0x".to_owned() + &hex::encode(data)
 }
+
+/// Committee in the transcoder syntax of a `Vec<AccountId>` argument
+fn committee_arg(members: &[AccountId]) -> String {
+    let members: Vec<_> = members.iter().map(ToString::to_string).collect();
+    format!("[{}]", members.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use contracts_azero_client::contract_transcode::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn known_revert_is_typed() {
+        let why = anyhow::anyhow!(
+            "Dry-run call reverted, decoded result: Ok(Ok(Err(HashDoesNotMatchData)))"
+        );
+
+        assert!(matches!(
+            AzeroContractError::from_call(why),
+            AzeroContractError::ContractReverted(MostError::HashDoesNotMatchData)
+        ));
+    }
+
+    #[test]
+    fn unknown_revert_keeps_the_raw_error() {
+        let why = anyhow::anyhow!("Dry-run call reverted, decoded result: Ok(Ok(Err(Arithmetic)))");
+
+        match AzeroContractError::from_call(why) {
+            AzeroContractError::ContractCall(why) => {
+                assert!(why.to_string().contains("Arithmetic"))
+            }
+            other => panic!("unexpected {other:?}"),
+        }
+    }
+
+    #[test]
+    fn failure_other_than_a_revert_is_not_typed() {
+        let why = anyhow::anyhow!("RPC request error - NotInCommittee");
+
+        assert!(matches!(
+            AzeroContractError::from_call(why),
+            AzeroContractError::ContractCall(_)
+        ));
+    }
+
+    #[test]
+    fn duplicate_committee_member_is_typed() {
+        let why = anyhow::anyhow!(
+            "Dry-run call reverted, decoded result: Ok(Ok(Err(DuplicateCommitteeMember)))"
+        );
+
+        assert!(matches!(
+            AzeroContractError::from_call(why),
+            AzeroContractError::ContractReverted(MostError::DuplicateCommitteeMember)
+        ));
+    }
+
+    #[test]
+    fn committee_is_passed_as_a_sequence_of_accounts() {
+        let member = AccountId::from([1; 32]);
+
+        assert_eq!(
+            committee_arg(&[member.clone(), member.clone()]),
+            format!("[{member}, {member}]")
+        );
+        assert_eq!(committee_arg(&[]), "[]");
+    }
+
+    #[test]
+    fn config_changed_event_is_decoded() {
+        let oracle = AccountId::from([1; 32]);
+        let variant = |name, value| Value::Tuple(Tuple::new(Some(name), vec![value]));
+        let data = HashMap::from([
+            (
+                "field".to_owned(),
+                Value::String("gas_price_oracle".to_owned()),
+            ),
+            (
+                "old".to_owned(),
+                variant("Account", Value::Tuple(Tuple::new(Some("None"), vec![]))),
+            ),
+            (
+                "new".to_owned(),
+                variant(
+                    "Account",
+                    variant("Some", Value::Literal(oracle.to_string())),
+                ),
+            ),
+        ]);
+
+        let ConfigChangedData { field, old, new } = get_config_changed_event_data(&data).unwrap();
+        assert_eq!(field, "gas_price_oracle");
+        assert_eq!(old, ConfigValue::Account(None));
+        assert_eq!(new, ConfigValue::Account(Some(oracle)));
+    }
+
+    #[test]
+    fn pending_owner_is_decoded() {
+        let owner = AccountId::from([1; 32]);
+        let result = |variant, value| {
+            PendingOwner::try_from(ConvertibleValue(Value::Tuple(Tuple::new(
+                Some(variant),
+                vec![value],
+            ))))
+            .unwrap()
+            .0
+        };
+        let error = |variant, values| Value::Tuple(Tuple::new(Some(variant), values));
+
+        assert_eq!(
+            result("Ok", Value::Literal(owner.to_string())),
+            Ok(owner.clone())
+        );
+        assert_eq!(
+            result("Err", error("NoPendingOwner", vec![])),
+            Err(OwnableError::NoPendingOwner)
+        );
+        assert_eq!(
+            result(
+                "Err",
+                error("CallerNotOwner", vec![Value::Literal(owner.to_string())])
+            ),
+            Err(OwnableError::CallerNotOwner(owner))
+        );
+        assert_eq!(
+            result(
+                "Err",
+                error("Custom", vec![Value::String("CorruptedStorage".to_owned())])
+            ),
+            Err(OwnableError::Custom("CorruptedStorage".to_owned()))
+        );
+    }
+
+    #[test]
+    fn config_changed_amount_is_decoded() {
+        let amount = |value| Value::Tuple(Tuple::new(Some("Amount"), vec![Value::UInt(value)]));
+        let data = HashMap::from([
+            (
+                "field".to_owned(),
+                Value::String("max_pocket_money".to_owned()),
+            ),
+            ("old".to_owned(), amount(1)),
+            ("new".to_owned(), amount(2)),
+        ]);
+
+        let ConfigChangedData { old, new, .. } = get_config_changed_event_data(&data).unwrap();
+        assert_eq!(old, ConfigValue::Amount(1));
+        assert_eq!(new.to_string(), "2");
+    }
+
+    #[test]
+    fn only_reverts_independent_of_the_contract_state_are_permanent() {
+        assert!(MostError::UnsupportedPair.is_permanent());
+        assert!(!MostError::IsHalted.is_permanent());
+        assert!(!MostError::NotInCommittee.is_permanent());
+    }
+}
@@ -1,3 +1,11 @@
+use std::sync::Arc;
+
+use ethers::{
+    contract::{abigen, ContractError},
+    providers::Middleware,
+    types::{Address, U256},
+};
+
 mod azero;
 #[cfg(not(feature = "l2"))]
 mod eth;
@@ -10,6 +18,40 @@ pub use eth::*;
 #[cfg(feature = "l2")]
 pub use l2_eth::*;
 
+abigen!(
+    Erc20,
+    r#"[function balanceOf(address account) external view returns (uint256)]"#
+);
+
+abigen!(
+    MostTransferLimit,
+    r#"[function transferLimit() external view returns (address)]"#
+);
+
+abigen!(
+    TransferLimit,
+    r#"[function minimumTransferAmount(address token) external view returns (uint256)]"#
+);
+
+/// Reads the minimum amount of `token` that can be sent from Ethereum, if the Most contract has a transfer limit set
+pub async fn minimum_transfer_amount<M: Middleware + 'static>(
+    client: Arc<M>,
+    most_address: Address,
+    token: Address,
+) -> Result<Option<U256>, ContractError<M>> {
+    let transfer_limit_address = MostTransferLimit::new(most_address, Arc::clone(&client))
+        .transfer_limit()
+        .await?;
+    if transfer_limit_address.is_zero() {
+        return Ok(None);
+    }
+
+    let minimum = TransferLimit::new(transfer_limit_address, client)
+        .minimum_transfer_amount(token)
+        .await?;
+    Ok(Some(minimum))
+}
+
 pub enum SignatureState {
     Signed { finalized: bool },
     NeedSignature,
@@ -0,0 +1,125 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use tokio::time::{Duration, Instant};
+
+use crate::metrics::Metrics;
+
+/// Upper bound on the number of remembered requests, the oldest ones are forgotten first
+const MAX_TRACKED_REQUESTS: usize = 100_000;
+
+#[derive(Default)]
+struct Seen {
+    at: HashMap<[u8; 32], Instant>,
+    order: VecDeque<([u8; 32], Instant)>,
+}
+
+/// Requests handed to the event handlers of both chains within the last `window`, keyed by the request hash.
+///
+/// Drops the same request delivered again, e.g. by a listener which resumed from an older block after a restart.
+/// It is created anew with every start of the relayer, so a request whose handling failed is handled again after the reboot.
+pub struct RequestDeduplicator {
+    window: Duration,
+    seen: Mutex<Seen>,
+    metrics: Arc<Metrics>,
+}
+
+impl RequestDeduplicator {
+    /// A zero `window` disables the deduplication
+    pub fn new(window: Duration, metrics: Arc<Metrics>) -> Self {
+        Self {
+            window,
+            seen: Mutex::new(Seen::default()),
+            metrics,
+        }
+    }
+
+    /// Whether `request_hash` is delivered for the first time within the window, remembering it if so.
+    ///
+    /// Duplicates are counted in the metrics under `chain`.
+    pub fn first_delivery(&self, chain: &'static str, request_hash: [u8; 32]) -> bool {
+        if self.window.is_zero() {
+            return true;
+        }
+
+        let now = Instant::now();
+        let mut seen = self.seen.lock().expect("mutex lock");
+        seen.forget_older_than(now, self.window);
+
+        if seen.at.contains_key(&request_hash) {
+            drop(seen);
+            self.metrics.record_duplicate_request(chain);
+            return false;
+        }
+
+        if seen.order.len() >= MAX_TRACKED_REQUESTS {
+            if let Some((oldest, _)) = seen.order.pop_front() {
+                seen.at.remove(&oldest);
+            }
+        }
+        seen.at.insert(request_hash, now);
+        seen.order.push_back((request_hash, now));
+        true
+    }
+}
+
+impl Seen {
+    fn forget_older_than(&mut self, now: Instant, window: Duration) {
+        while let Some(&(request_hash, at)) = self.order.front() {
+            if now.duration_since(at) < window {
+                break;
+            }
+            self.order.pop_front();
+            self.at.remove(&request_hash);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::time::advance;
+
+    use super::*;
+
+    const WINDOW: Duration = Duration::from_secs(60);
+
+    #[tokio::test(start_paused = true)]
+    async fn duplicates_within_the_window_are_dropped() {
+        let metrics = Arc::new(Metrics::default());
+        let dedup = RequestDeduplicator::new(WINDOW, Arc::clone(&metrics));
+
+        assert!(dedup.first_delivery("AlephZero", [1; 32]));
+        advance(Duration::from_secs(30)).await;
+        assert!(!dedup.first_delivery("AlephZero", [1; 32]));
+        // the other chain shares the set
+        assert!(!dedup.first_delivery("Ethereum", [1; 32]));
+        assert!(dedup.first_delivery("AlephZero", [2; 32]));
+
+        assert_eq!(
+            metrics.render(true),
+            "redis_up 1\nduplicate_requests_total{chain=\"AlephZero\"} 1\nduplicate_requests_total{chain=\"Ethereum\"} 1\n"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn duplicates_outside_the_window_are_handled_again() {
+        let metrics = Arc::new(Metrics::default());
+        let dedup = RequestDeduplicator::new(WINDOW, Arc::clone(&metrics));
+
+        assert!(dedup.first_delivery("AlephZero", [1; 32]));
+        advance(WINDOW).await;
+        assert!(dedup.first_delivery("AlephZero", [1; 32]));
+
+        assert_eq!(metrics.render(true), "redis_up 1\n");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn zero_window_disables_deduplication() {
+        let dedup = RequestDeduplicator::new(Duration::ZERO, Arc::new(Metrics::default()));
+
+        assert!(dedup.first_delivery("AlephZero", [1; 32]));
+        assert!(dedup.first_delivery("AlephZero", [1; 32]));
+    }
+}
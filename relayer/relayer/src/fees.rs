@@ -0,0 +1,187 @@
+use std::sync::Arc;
+
+use ethers::types::{transaction::eip2718::TypedTransaction, U256};
+use log::{debug, warn};
+
+use crate::{
+    connections::azero::AzeroWsConnection,
+    contracts::{AzeroContractError, GasPriceOracleInstance, MostInstance},
+};
+
+/// Source of the price users are charged for the Ethereum gas of their transfers
+#[async_trait::async_trait]
+pub trait GasPriceOracle: Send + Sync {
+    /// Price of one unit of Ethereum gas in picoAZERO, `None` if the Most contract has no oracle set
+    async fn price(&self) -> Result<Option<u128>, AzeroContractError>;
+}
+
+/// The oracle set in the AlephZero Most contract, looked up on every read as it can be replaced by the owner
+pub struct AzeroGasPriceOracle {
+    connection: Arc<AzeroWsConnection>,
+    most: MostInstance,
+    metadata_path: String,
+}
+
+impl AzeroGasPriceOracle {
+    pub fn new(
+        connection: Arc<AzeroWsConnection>,
+        most: MostInstance,
+        metadata_path: &str,
+    ) -> Self {
+        Self {
+            connection,
+            most,
+            metadata_path: metadata_path.to_owned(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl GasPriceOracle for AzeroGasPriceOracle {
+    async fn price(&self) -> Result<Option<u128>, AzeroContractError> {
+        let Some(address) = self.most.gas_price_oracle(&self.connection).await? else {
+            return Ok(None);
+        };
+
+        let oracle = GasPriceOracleInstance::new(address, &self.metadata_path)?;
+        let (price, updated_at) = oracle.price(&self.connection).await?;
+        debug!("Gas price oracle price: {price} picoAZERO, updated at {updated_at}");
+
+        Ok(Some(price))
+    }
+}
+
+/// Fees of an EIP-1559 transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EthFees {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// Chooses the fees relative to the gas price users paid for according to the oracle
+#[derive(Debug, Clone, PartialEq)]
+pub struct OracleFeeStrategy {
+    pub wei_per_pico_azero: f64,
+    pub max_fee_percent: u64,
+    pub min_max_fee_per_gas: U256,
+    pub max_max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+impl OracleFeeStrategy {
+    /// The oracle price converted to wei per gas
+    pub fn expected_gas_price(&self, oracle_price: u128) -> U256 {
+        U256::from((oracle_price as f64 * self.wei_per_pico_azero) as u128)
+    }
+
+    /// Max fee per gas within the bounds, with the priority fee never above it
+    pub fn fees(&self, oracle_price: u128) -> EthFees {
+        let max_fee_per_gas = (self.expected_gas_price(oracle_price) * self.max_fee_percent / 100)
+            .max(self.min_max_fee_per_gas)
+            .min(self.max_max_fee_per_gas);
+
+        EthFees {
+            max_fee_per_gas,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas.min(max_fee_per_gas),
+        }
+    }
+}
+
+/// [`OracleFeeStrategy`] fed by a [`GasPriceOracle`]
+pub struct OracleFees {
+    strategy: OracleFeeStrategy,
+    oracle: Arc<dyn GasPriceOracle>,
+}
+
+impl OracleFees {
+    pub fn new(strategy: OracleFeeStrategy, oracle: Arc<dyn GasPriceOracle>) -> Self {
+        Self { strategy, oracle }
+    }
+
+    /// Sets the fees of `tx`, returning the expected gas price in wei.
+    ///
+    /// Without an oracle set in the contract the fees are left to the Ethereum node and nothing is expected.
+    pub async fn apply(
+        &self,
+        tx: &mut TypedTransaction,
+    ) -> Result<Option<U256>, AzeroContractError> {
+        let Some(oracle_price) = self.oracle.price().await? else {
+            warn!("No gas price oracle set in the AlephZero Most contract, leaving the fees to the Ethereum node");
+            return Ok(None);
+        };
+
+        let fees = self.strategy.fees(oracle_price);
+        debug!("Fees for the oracle price of {oracle_price} picoAZERO: {fees:?}");
+        match tx {
+            TypedTransaction::Eip1559(tx) => {
+                tx.max_fee_per_gas = Some(fees.max_fee_per_gas);
+                tx.max_priority_fee_per_gas = Some(fees.max_priority_fee_per_gas);
+            }
+            tx => {
+                tx.set_gas_price(fees.max_fee_per_gas);
+            }
+        }
+
+        Ok(Some(self.strategy.expected_gas_price(oracle_price)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GWEI: u64 = 1_000_000_000;
+
+    fn strategy() -> OracleFeeStrategy {
+        OracleFeeStrategy {
+            wei_per_pico_azero: 100.0,
+            max_fee_percent: 150,
+            min_max_fee_per_gas: U256::from(GWEI),
+            max_max_fee_per_gas: U256::from(300 * GWEI),
+            max_priority_fee_per_gas: U256::from(2 * GWEI),
+        }
+    }
+
+    #[test]
+    fn oracle_price_is_mapped_to_fees() {
+        // 20 gwei per gas
+        let oracle_price = 200_000_000;
+
+        assert_eq!(
+            strategy().expected_gas_price(oracle_price),
+            U256::from(20 * GWEI)
+        );
+        assert_eq!(
+            strategy().fees(oracle_price),
+            EthFees {
+                max_fee_per_gas: U256::from(30 * GWEI),
+                max_priority_fee_per_gas: U256::from(2 * GWEI),
+            }
+        );
+    }
+
+    #[test]
+    fn max_fee_is_kept_within_bounds() {
+        assert_eq!(strategy().fees(0).max_fee_per_gas, U256::from(GWEI));
+        assert_eq!(
+            strategy().fees(1_000_000_000_000).max_fee_per_gas,
+            U256::from(300 * GWEI)
+        );
+    }
+
+    #[test]
+    fn priority_fee_is_not_above_max_fee() {
+        let strategy = OracleFeeStrategy {
+            max_max_fee_per_gas: U256::from(GWEI),
+            ..strategy()
+        };
+
+        assert_eq!(
+            strategy.fees(200_000_000),
+            EthFees {
+                max_fee_per_gas: U256::from(GWEI),
+                max_priority_fee_per_gas: U256::from(GWEI),
+            }
+        );
+    }
+}
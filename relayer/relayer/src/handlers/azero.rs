@@ -1,35 +1,54 @@
-use std::{str::FromStr, sync::Arc};
+use std::{
+    future::Future,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
 
-use contracts_azero_client::ContractEvent;
+use contracts_azero_client::{AccountId, ContractEvent};
 use ethers::{
     abi::{self, Token},
+    contract::parse_log,
     core::types::{Address, H256},
     prelude::{ContractCall, ContractError},
-    providers::{Middleware, ProviderError},
-    types::{U256, U64},
+    providers::{JsonRpcClient, Middleware, Provider, ProviderError},
+    signers::Signer,
+    types::{BlockNumber, TransactionReceipt, U256, U64},
     utils::keccak256,
 };
+use futures::TryFutureExt;
 use log::{debug, error, info, trace, warn};
 use thiserror::Error;
 use tokio::{
     select,
-    sync::{broadcast, mpsc},
+    sync::{broadcast, mpsc, watch, AcquireError, Semaphore},
     task::{JoinError, JoinSet},
-    time::{sleep, Duration},
+    time::{sleep, sleep_until, Duration, Instant},
 };
+use tracing::Instrument;
 
 use crate::{
+    admin::{SubmissionGate, SubmissionGateError},
+    audit::{audited, AuditError, AuditOutcome, AuditSink},
     config::Config,
     connections::eth::SignedEthConnection,
     contracts::{
-        contract_signature_state, get_request_event_data, AzeroContractError,
-        CrosschainTransferRequestData, Most, SignatureState,
+        contract_signature_state, get_config_changed_event_data, get_request_event_data,
+        minimum_transfer_amount, AzeroContractError, ConfigChangedData,
+        CrosschainTransferRequestData, Most, MostErrors, MostEvents, ProcessedRequestSignedFilter,
+        RequestAlreadySignedFilter, RequestSignedFilter, SignatureState,
     },
-    listeners::AzeroMostEvents,
+    dedup::RequestDeduplicator,
+    fees::OracleFees,
+    handlers::{record_request, request_span, AzeroMost},
+    helpers::{next_circuit_breaker_event, open_circuit_breaker, PollBackoff},
+    listeners::{AzeroMostEvents, CachedBaseFee},
+    metrics::Metrics,
+    offline_signing::{unsigned_tx_exists, write_unsigned_tx, OfflineSigningError, UnsignedTx},
+    redis::{DeadLetterStore, SubmittedTxStore},
     CircuitBreakerEvent,
 };
 
-// Frequency of checking for finality of the transaction
+// Minimum interval between the checks for finality of the transaction
 const ETH_WAIT_FOR_FINALITY_CHECK_SEC: u64 = 60;
 
 #[derive(Debug, Error)]
@@ -54,38 +73,185 @@ pub enum AlephZeroEventHandlerError {
     #[error("Contract reverted")]
     EthContractReverted,
 
+    #[error("Receipt of tx {0:?} has no log of the guardian's signature")]
+    MissingSignatureLog(H256),
+
     #[error("Bridge misconfiguration: committee id mismatch")]
     CommitteeIdMismatch,
+
+    #[error("Finalized head channel closed")]
+    FinalizedHead(#[from] watch::error::RecvError),
+
+    #[error("Redis error")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Offline signing error")]
+    OfflineSigning(#[from] OfflineSigningError),
+
+    #[error("Audit sink error")]
+    Audit(#[from] AuditError),
+
+    #[error("Finality tracker or submission permits closed")]
+    Acquire(#[from] AcquireError),
+
+    #[error("Manual confirmation of the submission failed")]
+    SubmissionGate(#[from] SubmissionGateError),
+}
+
+/// Handling of an AlephZero contract event, chosen by its name
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EventRoute {
+    CrosschainTransferRequest,
+    ConfigChanged,
+}
+
+/// Events the relayer acts upon, anything else emitted by the contract is skipped
+const HANDLED_EVENTS: &[(&str, EventRoute)] = &[
+    (
+        "CrosschainTransferRequest",
+        EventRoute::CrosschainTransferRequest,
+    ),
+    ("ConfigChanged", EventRoute::ConfigChanged),
+];
+
+fn route(event_name: Option<&str>) -> Option<EventRoute> {
+    let event_name = event_name?;
+    HANDLED_EVENTS
+        .iter()
+        .find(|(name, _)| *name == event_name)
+        .map(|(_, route)| *route)
+}
+
+/// Warns about an event of the Most contract whose name could not be resolved, e.g. due to outdated metadata.
+///
+/// Returns whether the event was reported, events of other contracts are left to be skipped silently.
+fn report_unnamed_event(config: &Config, metrics: &Metrics, event: &ContractEvent) -> bool {
+    let from_most = AccountId::from_str(&config.azero_contract_address)
+        .map(|most| most == event.contract)
+        .unwrap_or(false);
+    if event.name.is_some() || !from_most {
+        return false;
+    }
+
+    warn!(
+        "Skipping an event of the Most contract without a name, check the contract metadata. Block: {:?}, data: {:?}",
+        event.block_number, event.data
+    );
+    metrics.record_unnamed_event("AlephZero");
+    true
+}
+
+/// Handles a single event of a batch received by [`AlephZeroEventsHandler`].
+///
+/// Injected into the events handler, so that its loop can be run without submitting anything to Ethereum.
+#[async_trait::async_trait]
+pub trait AzeroEventHandler: Send + Sync {
+    async fn handle(&self, event: ContractEvent) -> Result<(), AlephZeroEventHandlerError>;
 }
 
-pub struct AlephZeroEventHandler;
+/// Forwards the transfer requests to Ethereum
+pub struct AlephZeroEventHandler {
+    config: Arc<Config>,
+    azero_most: Arc<dyn AzeroMost>,
+    eth_signed_connection: Arc<SignedEthConnection>,
+    eth_finalized_head_receiver: watch::Receiver<u32>,
+    submitted_txs: Arc<dyn SubmittedTxStore>,
+    dead_letters: Arc<dyn DeadLetterStore>,
+    audit_sink: Arc<dyn AuditSink>,
+    dedup: Arc<RequestDeduplicator>,
+    submission_gate: Arc<SubmissionGate>,
+    oracle_fees: Option<Arc<OracleFees>>,
+    base_fee: Arc<CachedBaseFee>,
+    finality_tracker: Option<FinalityTracker>,
+    // shared by all the batches, so that the busy ones do not add up to a burst of Ethereum transactions
+    submission_permits: Semaphore,
+    metrics: Arc<Metrics>,
+}
+
+#[async_trait::async_trait]
+impl AzeroEventHandler for AlephZeroEventHandler {
+    async fn handle(&self, event: ContractEvent) -> Result<(), AlephZeroEventHandlerError> {
+        self.handle_event(event).await
+    }
+}
 
 impl AlephZeroEventHandler {
-    pub async fn handle_event(
-        event: ContractEvent,
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
         config: Arc<Config>,
+        azero_most: Arc<dyn AzeroMost>,
         eth_signed_connection: Arc<SignedEthConnection>,
-    ) -> Result<(), AlephZeroEventHandlerError> {
-        let Config {
-            eth_contract_address,
-            eth_tx_min_confirmations,
-            eth_tx_submission_retries,
-            blacklisted_requests,
-            ..
-        } = &*config;
+        eth_finalized_head_receiver: watch::Receiver<u32>,
+        submitted_txs: Arc<dyn SubmittedTxStore>,
+        dead_letters: Arc<dyn DeadLetterStore>,
+        audit_sink: Arc<dyn AuditSink>,
+        dedup: Arc<RequestDeduplicator>,
+        submission_gate: Arc<SubmissionGate>,
+        oracle_fees: Option<Arc<OracleFees>>,
+        base_fee: Arc<CachedBaseFee>,
+        metrics: Arc<Metrics>,
+    ) -> Self {
+        Self {
+            finality_tracker: config.eth_finality_trackers.map(FinalityTracker::new),
+            submission_permits: Semaphore::new(config.eth_max_concurrent_submissions as usize),
+            config,
+            azero_most,
+            eth_signed_connection,
+            eth_finalized_head_receiver,
+            submitted_txs,
+            dead_letters,
+            audit_sink,
+            dedup,
+            submission_gate,
+            oracle_fees,
+            base_fee,
+            metrics,
+        }
+    }
 
-        if !event
-            .name
-            .is_some_and(|name| name.eq("CrosschainTransferRequest"))
-        {
-            debug!("Skipping non azero contract event");
-            return Ok(());
+    pub async fn handle_event(
+        &self,
+        event: ContractEvent,
+    ) -> Result<(), AlephZeroEventHandlerError> {
+        match route(event.name.as_deref()) {
+            Some(EventRoute::CrosschainTransferRequest) => {
+                self.metrics.record_event_seen("AlephZero");
+                let result = self
+                    .handle_crosschain_transfer_request(event)
+                    .instrument(request_span("AlephZero"))
+                    .await;
+                if result.is_err() {
+                    self.metrics.record_relay_failure("AlephZero");
+                }
+                result
+            }
+            Some(EventRoute::ConfigChanged) => self.handle_config_changed(event),
+            None if report_unnamed_event(&self.config, &self.metrics, &event) => Ok(()),
+            None => {
+                debug!("Skipping azero contract event {:?}", event.name);
+                Ok(())
+            }
         }
+    }
 
-        let data = event.data;
+    /// Drops the cached base fee, which most of the parameters go into
+    fn handle_config_changed(
+        &self,
+        event: ContractEvent,
+    ) -> Result<(), AlephZeroEventHandlerError> {
+        let ConfigChangedData { field, old, new } = get_config_changed_event_data(&event.data)?;
+        info!("Most contract parameter {field} changed from {old} to {new}");
+        self.base_fee.invalidate();
+        Ok(())
+    }
 
+    /// Every decoded request ends up in the audit sink, whatever the outcome of forwarding it
+    async fn handle_crosschain_transfer_request(
+        &self,
+        event: ContractEvent,
+    ) -> Result<(), AlephZeroEventHandlerError> {
         // decode event data
-        let crosschain_transfer_event = get_request_event_data(&data)?;
+        let crosschain_transfer_event = get_request_event_data(&event.data)?;
 
         debug!("Handling azero contract event: {crosschain_transfer_event:?}");
 
@@ -97,22 +263,96 @@ impl AlephZeroEventHandler {
             request_nonce,
         } = crosschain_transfer_event;
 
-        // NOTE: for some reason, ethers-rs's `encode_packed` does not properly encode the data
-        // (it does not pad uint to 32 bytes, but uses the actual number of bytes required to store the value)
-        // so we use `abi::encode` instead (it only differs for signed and dynamic size types, which we don't use here)
-        let bytes = abi::encode(&[
-            Token::Uint(committee_id.into()),
-            Token::FixedBytes(dest_token_address.to_vec()),
-            Token::Uint(amount.into()),
-            Token::FixedBytes(dest_receiver_address.to_vec()),
-            Token::Uint(request_nonce.into()),
-        ]);
+        let request_hash = request_hash(
+            committee_id,
+            &dest_token_address,
+            amount,
+            &dest_receiver_address,
+            request_nonce,
+        );
+        debug!("Hashed event data: {request_hash:?}");
+        record_request(request_nonce, &request_hash);
 
-        trace!("ABI compliant concatenated event bytes {bytes:?}");
+        // checked before the deduplication, so that a request deferred until a restart is not taken for a duplicate
+        await_unhalted(
+            self.azero_most.as_ref(),
+            request_hash,
+            Duration::from_secs(self.config.azero_halt_recheck_interval_sec),
+        )
+        .await?;
 
-        let request_hash = keccak256(bytes);
-        debug!("Hashed event data: {request_hash:?}");
+        if !self.dedup.first_delivery("AlephZero", request_hash) {
+            info!(
+                "Skipping request 0x{} - delivered again within the deduplication window",
+                hex::encode(request_hash)
+            );
+            return Ok(());
+        }
+
+        audited(
+            self.audit_sink.as_ref(),
+            event.block_number,
+            request_hash,
+            &crosschain_transfer_event,
+            self.forward_request_with_retries(&crosschain_transfer_event, request_hash),
+        )
+        .await
+    }
 
+    /// A request which keeps failing for a transient reason is moved to the dead letter queue once the retries are
+    /// exhausted, so that its batch is still acknowledged
+    async fn forward_request_with_retries(
+        &self,
+        request: &CrosschainTransferRequestData,
+        request_hash: [u8; 32],
+    ) -> Result<AuditOutcome, AlephZeroEventHandlerError> {
+        let result = retry_transient(
+            self.config.forwarding_backoff(),
+            request_hash,
+            &self.submission_permits,
+            || self.forward_request(request, request_hash),
+        )
+        .await;
+
+        dead_letter_exhausted(result, request_hash, self.dead_letters.as_ref())
+    }
+
+    async fn forward_request(
+        &self,
+        request: &CrosschainTransferRequestData,
+        request_hash: [u8; 32],
+    ) -> Result<AuditOutcome, AlephZeroEventHandlerError> {
+        let Self {
+            config,
+            eth_signed_connection,
+            eth_finalized_head_receiver,
+            submitted_txs,
+            dead_letters,
+            submission_gate,
+            oracle_fees,
+            finality_tracker,
+            metrics,
+            ..
+        } = self;
+        let Config {
+            eth_contract_address,
+            eth_tx_min_confirmations,
+            eth_tx_submission_retries,
+            blacklisted_requests,
+            ..
+        } = &**config;
+        let &CrosschainTransferRequestData {
+            committee_id,
+            dest_token_address,
+            amount,
+            dest_receiver_address,
+            request_nonce,
+        } = request;
+
+        let started = Instant::now();
+        let mut finality_wait = Duration::ZERO;
+        let mut eth_finalized_head_receiver = eth_finalized_head_receiver.clone();
+        let mut submitted_tx = None;
         let request_hash_hex = hex::encode(request_hash);
 
         info!(
@@ -124,42 +364,156 @@ impl AlephZeroEventHandler {
         if let Some(blacklist) = blacklisted_requests {
             if blacklist.contains(&H256::from_str(&request_hash_hex)?) {
                 warn!("Skipping blacklisted request: 0x{request_hash_hex}");
-                return Ok(());
+                return Ok(AuditOutcome::NotSubmitted {
+                    reason: "blacklisted".to_owned(),
+                });
             }
         }
 
+        if let Some(violation) =
+            config.forwarding_violation(&dest_token_address, amount, &dest_receiver_address)
+        {
+            error!("Request 0x{request_hash_hex} not forwarded: {violation}. Moving it to the dead letter queue for a manual review");
+            dead_letters.push(request_hash, &violation)?;
+            return Ok(AuditOutcome::NotSubmitted {
+                reason: format!("moved to the dead letter queue: {violation}"),
+            });
+        }
+
         let address = eth_contract_address.parse::<Address>()?;
         let contract = Most::new(address, eth_signed_connection.clone());
+        // requests are signed by the account which sends the txs, which is the offline signer's when there is one
+        let signer = match (&config.offline_signing_dir, config.offline_signer_address) {
+            (Some(_), Some(offline_signer)) => offline_signer,
+            _ => eth_signed_connection.address(),
+        };
 
-        if not_in_committee(
-            &contract,
-            committee_id.into(),
-            eth_signed_connection.address(),
+        // tokens on Ethereum are addresses stored in the last 20 bytes
+        let dest_token = Address::from_slice(&dest_token_address[12..]);
+        match minimum_transfer_amount(eth_signed_connection.clone(), address, dest_token).await {
+            Ok(Some(minimum))
+                if is_suspiciously_small(amount, minimum, config.suspicious_amount_percent) =>
+            {
+                warn!("Request 0x{request_hash_hex} transfers {amount}, which is far below the minimum of {minimum} for sending {dest_token:?} from Ethereum. It may be a forged or a test request");
+            }
+            Ok(_) => {}
+            Err(why) => {
+                debug!("Could not read the minimum transfer amount for {dest_token:?}: {why:?}")
+            }
+        }
+
+        if not_in_committee(&contract, committee_id.into(), signer).await? {
+            info!("Guardian signature for 0x{request_hash_hex} not needed - request from a past committee");
+            return Ok(AuditOutcome::NotSubmitted {
+                reason: format!("request from a past committee {committee_id}"),
+            });
+        }
+
+        let provider = eth_signed_connection.provider();
+        if let Some(tx_hash) = await_recorded_tx(
+            submitted_txs.as_ref(),
+            request_hash,
+            |tx_hash| eth_tx_status(provider, tx_hash),
+            &mut eth_finalized_head_receiver,
         )
         .await?
         {
-            info!("Guardian signature for 0x{request_hash_hex} not needed - request from a past committee");
-            return Ok(());
+            info!("Previously submitted tx {tx_hash:?} for request 0x{request_hash_hex} has been mined, not sending another one");
+            submitted_tx = Some(tx_hash);
         }
 
         loop {
-            match contract_signature_state(
-                &contract,
-                request_hash,
-                eth_signed_connection.address(),
-                committee_id,
-            )
-            .await?
-            {
+            match contract_signature_state(&contract, request_hash, signer, committee_id).await? {
                 SignatureState::Signed { finalized: true } => {
                     info!("Guardian signature for 0x{request_hash_hex} no longer needed");
-                    return Ok(());
+                    if config.eth_single_submission {
+                        submitted_txs.clear(request_hash)?;
+                    }
+                    return Ok(match submitted_tx {
+                        Some(tx_hash) => {
+                            metrics.record_request_relayed(
+                                "AlephZero",
+                                started.elapsed(),
+                                finality_wait,
+                            );
+                            AuditOutcome::Submitted {
+                                tx_hash: format!("{tx_hash:?}"),
+                            }
+                        }
+                        None => AuditOutcome::NotSubmitted {
+                            reason: "already signed".to_owned(),
+                        },
+                    });
                 }
                 SignatureState::Signed { finalized: false } => {
+                    if let (Some(finality_tracker), Some(tx_hash)) =
+                        (finality_tracker, submitted_tx)
+                    {
+                        // kept until the finality, so that the request is not submitted again meanwhile
+                        submitted_txs.record(request_hash, tx_hash)?;
+                        info!("Tx {tx_hash:?} for request 0x{request_hash_hex} has been mined, tracking its finality in the background");
+                        let pending = PendingSignature {
+                            contract: contract.clone(),
+                            signer,
+                            request_hash,
+                            committee_id,
+                            started,
+                        };
+                        finality_tracker
+                            .track(
+                                track_finality(
+                                    pending,
+                                    eth_finalized_head_receiver.clone(),
+                                    Arc::clone(submitted_txs),
+                                    Arc::clone(dead_letters),
+                                    Arc::clone(metrics),
+                                )
+                                .unwrap_or_else(move |why| {
+                                    error!(
+                                        "Tracking the finality of tx {tx_hash:?} failed: {why:?}"
+                                    )
+                                }),
+                            )
+                            .await?;
+                        return Ok(AuditOutcome::Submitted {
+                            tx_hash: format!("{tx_hash:?}"),
+                        });
+                    }
+
                     info!("Request 0x{request_hash_hex} not yet finalized.");
-                    sleep(Duration::from_secs(ETH_WAIT_FOR_FINALITY_CHECK_SEC)).await;
+                    let waiting_since = Instant::now();
+                    next_finality_check(&mut eth_finalized_head_receiver, waiting_since).await?;
+                    finality_wait += waiting_since.elapsed();
                 }
                 SignatureState::NeedSignature => {
+                    if let Some(offline_signing_dir) = &config.offline_signing_dir {
+                        if unsigned_tx_exists(offline_signing_dir, &request_hash_hex) {
+                            debug!("Tx for request 0x{request_hash_hex} awaits offline signing");
+                            eth_finalized_head_receiver.changed().await?;
+                            continue;
+                        }
+                    }
+
+                    if config.eth_single_submission {
+                        if let Some(tx_hash) =
+                            pending_submission(submitted_txs.as_ref(), request_hash, |tx_hash| {
+                                eth_tx_status(provider, tx_hash)
+                            })
+                            .await?
+                        {
+                            info!("Tx {tx_hash:?} for request 0x{request_hash_hex} is still pending, not sending another one");
+                            submitted_tx = Some(tx_hash);
+                            eth_finalized_head_receiver.changed().await?;
+                            continue;
+                        }
+                    }
+
+                    // still signed, the contract accepts the signatures of a past committee under its own threshold
+                    let current_committee_id = contract.committee_id().await?;
+                    if U256::from(committee_id) < current_committee_id {
+                        warn!("Request 0x{request_hash_hex} is of the past committee {committee_id}, the current one on Ethereum is {current_committee_id}");
+                    }
+
                     // forward transfer & vote
                     let call: ContractCall<SignedEthConnection, ()> = contract.receive_request(
                         request_hash,
@@ -170,39 +524,598 @@ impl AlephZeroEventHandler {
                         request_nonce.into(),
                     );
 
-                    debug!("Dry-running tx for request 0x{request_hash_hex}");
+                    if config.eth_skip_dry_run {
+                        debug!("Not dry-running tx for request 0x{request_hash_hex}");
+                    } else {
+                        debug!("Dry-running tx for request 0x{request_hash_hex}");
+
+                        // Dry-run the tx to check for potential reverts.
+                        if let Err(why) = call.clone().gas(config.eth_gas_limit).call().await {
+                            let reason = revert_reason(&why);
+                            if is_permanent_revert(&why) {
+                                error!("Request 0x{request_hash_hex} not forwarded: receive_request reverts with {reason}. Moving it to the dead letter queue for a manual review");
+                                let violation = format!(
+                                    "receive_request of nonce {request_nonce} reverts with {reason}"
+                                );
+                                dead_letters.push(request_hash, &violation)?;
+                                return Ok(AuditOutcome::NotSubmitted {
+                                    reason: format!("moved to the dead letter queue: {violation}"),
+                                });
+                            }
+                            warn!(
+                                "Dry run of tx for request 0x{request_hash_hex} reverted: {reason}"
+                            );
+                            return Err(why.into());
+                        }
+                    }
+
+                    submission_gate
+                        .wait_for_confirmation(
+                            &request_hash_hex,
+                            &format!(
+                                "receive_request(0x{request_hash_hex}, {committee_id}, 0x{}, {amount}, 0x{}, {request_nonce}) on Ethereum",
+                                hex::encode(dest_token_address),
+                                hex::encode(dest_receiver_address)
+                            ),
+                        )
+                        .await?;
+
+                    if let Some(ceiling) = config.eth_gas_price_ceiling() {
+                        await_gas_price_within(
+                            ceiling,
+                            request_hash,
+                            config.gas_price_backoff(),
+                            || provider.get_gas_price(),
+                        )
+                        .await?;
+                    }
+
+                    if let Some(offline_signing_dir) = &config.offline_signing_dir {
+                        // the hot key's nonce manager is left alone, the tx is sent from the offline signer's account
+                        let nonce = provider
+                            .get_transaction_count(signer, Some(BlockNumber::Pending.into()))
+                            .await?;
+                        let tx = UnsignedTx {
+                            to: address,
+                            data: call.calldata().unwrap_or_default(),
+                            value: U256::zero(),
+                            chain_id: eth_signed_connection.signer().chain_id().into(),
+                            nonce,
+                            gas: config.eth_gas_limit.into(),
+                            gas_price: eth_signed_connection.provider().get_gas_price().await?,
+                        };
 
-                    // Dry-run the tx to check for potential reverts.
-                    call.clone().gas(config.eth_gas_limit).call().await?;
+                        write_unsigned_tx(offline_signing_dir, &request_hash_hex, &tx)?;
+                        info!("Tx for request 0x{request_hash_hex} written out for offline signing: {tx:?}");
+
+                        eth_finalized_head_receiver.changed().await?;
+                        continue;
+                    }
 
-                    info!("Sending tx for request 0x{request_hash_hex} to the Ethereum network and waiting for {eth_tx_min_confirmations} confirmations.");
+                    info!("{}: sending tx for request 0x{request_hash_hex} to the Ethereum network and waiting for {eth_tx_min_confirmations} confirmations.", metrics.guardian());
 
-                    let receipt = call
+                    let mut call = call
                         .gas(config.eth_gas_limit)
-                        .nonce(eth_signed_connection.inner().next())
-                        .send()
-                        .await?
-                        .confirmations(*eth_tx_min_confirmations)
-                        .retries(*eth_tx_submission_retries)
-                        .await?
-                        .ok_or(AlephZeroEventHandlerError::TxNotPresentInBlockOrMempool)?;
+                        .nonce(eth_signed_connection.inner().next());
+                    let expected_gas_price = match oracle_fees {
+                        Some(oracle_fees) => oracle_fees.apply(&mut call.tx).await?,
+                        None => None,
+                    };
+                    let pending_tx = call.send().await?;
+
+                    let receipt = await_confirmations(
+                        submitted_txs.as_ref(),
+                        request_hash,
+                        pending_tx.tx_hash(),
+                        async {
+                            pending_tx
+                                .confirmations(*eth_tx_min_confirmations)
+                                .retries(*eth_tx_submission_retries)
+                                .await?
+                                .ok_or(AlephZeroEventHandlerError::TxNotPresentInBlockOrMempool)
+                        },
+                    )
+                    .await?;
 
                     let tx_hash = receipt.transaction_hash;
-                    let tx_status = receipt.status;
 
-                    // Check if the tx reverted.
-                    if tx_status == Some(U64::from(0)) {
-                        warn!("Tx for request 0x{request_hash_hex} has been sent to the Ethereum network: {tx_hash:?} but it reverted.");
-                        return Err(AlephZeroEventHandlerError::EthContractReverted);
+                    if let (Some(expected), Some(paid)) =
+                        (expected_gas_price, receipt.effective_gas_price)
+                    {
+                        if paid > expected {
+                            warn!("Tx {tx_hash:?} for request 0x{request_hash_hex} paid {paid} wei per gas, above the {expected} users were charged for according to the gas price oracle");
+                        }
                     }
 
-                    info!("Tx for request 0x{request_hash_hex} has been sent to the Ethereum network: {tx_hash:?} and received {eth_tx_min_confirmations} confirmations.");
+                    if let Err(why) = check_receipt(
+                        &receipt,
+                        address,
+                        request_hash,
+                        eth_signed_connection.address(),
+                        config.eth_verify_receipt_logs,
+                    ) {
+                        warn!("Tx for request 0x{request_hash_hex} has been sent to the Ethereum network: {tx_hash:?} but it did not succeed: {why}");
+                        return Err(why);
+                    }
+
+                    info!("{}: tx for request 0x{request_hash_hex} has been sent to the Ethereum network: {tx_hash:?} and received {eth_tx_min_confirmations} confirmations.", metrics.guardian());
+                    submitted_tx = Some(tx_hash);
                 }
             }
         }
     }
 }
 
+/// Background tasks waiting for the finality of the submitted signatures, so that the handler of a request is done
+/// once its tx is mined and the batch does not wait for the finality
+pub struct FinalityTracker {
+    slots: Arc<Semaphore>,
+    tasks: Mutex<JoinSet<()>>,
+}
+
+impl FinalityTracker {
+    pub fn new(size: usize) -> Self {
+        Self {
+            slots: Arc::new(Semaphore::new(size)),
+            tasks: Mutex::new(JoinSet::new()),
+        }
+    }
+
+    /// Runs `finality` in the background, waiting for a free slot first if all of them are taken.
+    ///
+    /// The tasks are aborted along with the tracker.
+    pub async fn track<F>(&self, finality: F) -> Result<(), AcquireError>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let slot = Arc::clone(&self.slots).acquire_owned().await?;
+
+        let mut tasks = self.tasks.lock().expect("mutex lock");
+        while tasks.try_join_next().is_some() {}
+        // keeps the span of the request, so that its finality is logged along the rest of it
+        tasks.spawn(
+            async move {
+                let _slot = slot;
+                finality.await
+            }
+            .in_current_span(),
+        );
+
+        Ok(())
+    }
+}
+
+/// Signature of a request submitted by the guardian, which has been mined but is not finalized yet
+struct PendingSignature {
+    contract: Most<SignedEthConnection>,
+    signer: Address,
+    request_hash: [u8; 32],
+    committee_id: u128,
+    started: Instant,
+}
+
+/// Waits for the finality of the signature, then forgets the submitted tx.
+///
+/// A signature which is gone before the finality, e.g. after a reorg, is moved to the dead letter queue,
+/// as the blocks of the request may already be marked as handled.
+async fn track_finality(
+    pending: PendingSignature,
+    mut eth_finalized_head_receiver: watch::Receiver<u32>,
+    submitted_txs: Arc<dyn SubmittedTxStore>,
+    dead_letters: Arc<dyn DeadLetterStore>,
+    metrics: Arc<Metrics>,
+) -> Result<(), AlephZeroEventHandlerError> {
+    let PendingSignature {
+        contract,
+        signer,
+        request_hash,
+        committee_id,
+        started,
+    } = pending;
+    let request_hash_hex = hex::encode(request_hash);
+    let waiting_since = Instant::now();
+    let mut checked_at = waiting_since;
+
+    loop {
+        next_finality_check(&mut eth_finalized_head_receiver, checked_at).await?;
+        checked_at = Instant::now();
+
+        match contract_signature_state(&contract, request_hash, signer, committee_id).await? {
+            SignatureState::Signed { finalized: true } => {
+                info!("Guardian signature for 0x{request_hash_hex} has been finalized");
+                submitted_txs.clear(request_hash)?;
+                metrics.record_request_relayed(
+                    "AlephZero",
+                    started.elapsed(),
+                    waiting_since.elapsed(),
+                );
+                return Ok(());
+            }
+            SignatureState::Signed { finalized: false } => {
+                debug!("Request 0x{request_hash_hex} not yet finalized.");
+            }
+            SignatureState::NeedSignature => {
+                error!("Guardian signature for 0x{request_hash_hex} is gone before the finality. Moving it to the dead letter queue for a manual review");
+                dead_letters.push(request_hash, "signature gone before the finality")?;
+                return Ok(());
+            }
+        }
+    }
+}
+
+/// Waits for a new finalized head, but no sooner than `ETH_WAIT_FOR_FINALITY_CHECK_SEC` after the previous check,
+/// so that the signature state is not read again for every head while many requests await their finality.
+async fn next_finality_check(
+    eth_finalized_head_receiver: &mut watch::Receiver<u32>,
+    checked_at: Instant,
+) -> Result<(), watch::error::RecvError> {
+    sleep_until(checked_at + Duration::from_secs(ETH_WAIT_FOR_FINALITY_CHECK_SEC)).await;
+    eth_finalized_head_receiver.changed().await
+}
+
+/// Defers a request while the AlephZero Most contract is halted, rechecking every `recheck_interval`.
+///
+/// A halted bridge may still deliver the events emitted before the halt, which are not to be forwarded until it is unhalted.
+async fn await_unhalted(
+    most: &dyn AzeroMost,
+    request_hash: [u8; 32],
+    recheck_interval: Duration,
+) -> Result<(), AzeroContractError> {
+    if !most.is_halted().await? {
+        return Ok(());
+    }
+
+    info!(
+        "Deferring request 0x{} until the AlephZero Most contract is unhalted",
+        hex::encode(request_hash)
+    );
+    loop {
+        sleep(recheck_interval).await;
+        if !most.is_halted().await? {
+            info!(
+                "AlephZero Most contract unhalted, resuming request 0x{}",
+                hex::encode(request_hash)
+            );
+            return Ok(());
+        }
+    }
+}
+
+/// Defers a submission while the Ethereum gas price is above `ceiling`, rechecking it after the `backoff` delays
+async fn await_gas_price_within<F, Fut>(
+    ceiling: U256,
+    request_hash: [u8; 32],
+    mut backoff: PollBackoff,
+    mut gas_price: F,
+) -> Result<(), ProviderError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<U256, ProviderError>>,
+{
+    loop {
+        let observed = gas_price().await?;
+        if observed <= ceiling {
+            return Ok(());
+        }
+
+        let delay = backoff.next_delay();
+        warn!(
+            "Deferring request 0x{} for {delay:?}: gas price of {observed} wei is above the ceiling of {ceiling} wei",
+            hex::encode(request_hash)
+        );
+        sleep(delay).await;
+    }
+}
+
+/// Keeps a record of the submitted tx until it gets the required number of confirmations.
+///
+/// If the relayer goes down or the confirmations never arrive, the record stays in the store.
+async fn await_confirmations<F>(
+    submitted_txs: &dyn SubmittedTxStore,
+    request_hash: [u8; 32],
+    tx_hash: H256,
+    confirmations: F,
+) -> Result<TransactionReceipt, AlephZeroEventHandlerError>
+where
+    F: Future<Output = Result<TransactionReceipt, AlephZeroEventHandlerError>>,
+{
+    submitted_txs.record(request_hash, tx_hash)?;
+    let receipt = confirmations.await?;
+    submitted_txs.clear(request_hash)?;
+    Ok(receipt)
+}
+
+/// State of a tx as seen by the Ethereum node
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TxStatus {
+    Pending,
+    Mined { succeeded: bool },
+    Unknown,
+}
+
+async fn eth_tx_status<P: JsonRpcClient>(
+    provider: &Provider<P>,
+    tx_hash: H256,
+) -> Result<TxStatus, ProviderError> {
+    if let Some(receipt) = provider.get_transaction_receipt(tx_hash).await? {
+        return Ok(TxStatus::Mined {
+            succeeded: receipt.status == Some(U64::one()),
+        });
+    }
+
+    Ok(match provider.get_transaction(tx_hash).await? {
+        Some(_) => TxStatus::Pending,
+        None => TxStatus::Unknown,
+    })
+}
+
+/// The tx recorded for the request, e.g. by the relayer before a restart, if it may still get the request signed.
+///
+/// A tx which reverted or is unknown to the node is forgotten, so that the request is submitted again. A tx whose status
+/// cannot be looked up is forgotten as well and the error returned, the retried request at worst gets its signature
+/// reported as already given.
+async fn pending_submission<F, Fut>(
+    submitted_txs: &dyn SubmittedTxStore,
+    request_hash: [u8; 32],
+    tx_status: F,
+) -> Result<Option<H256>, AlephZeroEventHandlerError>
+where
+    F: FnOnce(H256) -> Fut,
+    Fut: Future<Output = Result<TxStatus, ProviderError>>,
+{
+    let Some(tx_hash) = submitted_txs.get(request_hash)? else {
+        return Ok(None);
+    };
+
+    let status = match tx_status(tx_hash).await {
+        Ok(status) => status,
+        Err(why) => {
+            warn!(
+                "Forgetting tx {tx_hash:?} for request 0x{} as its status could not be looked up: {why:?}",
+                hex::encode(request_hash)
+            );
+            submitted_txs.clear(request_hash)?;
+            return Err(why.into());
+        }
+    };
+
+    match status {
+        TxStatus::Pending | TxStatus::Mined { succeeded: true } => Ok(Some(tx_hash)),
+        status => {
+            warn!(
+                "Forgetting tx {tx_hash:?} for request 0x{} as it is {status:?}",
+                hex::encode(request_hash)
+            );
+            submitted_txs.clear(request_hash)?;
+            Ok(None)
+        }
+    }
+}
+
+/// Waits for the tx recorded for the request without a confirmed finality, e.g. by the relayer before a restart,
+/// to leave the mempool, checking it again on each new finalized head.
+///
+/// Returns the tx if it got mined, so that the request is not submitted again. A tx which reverted or is unknown
+/// to the node is forgotten, and the request is submitted anew.
+async fn await_recorded_tx<F, Fut>(
+    submitted_txs: &dyn SubmittedTxStore,
+    request_hash: [u8; 32],
+    mut tx_status: F,
+    eth_finalized_head_receiver: &mut watch::Receiver<u32>,
+) -> Result<Option<H256>, AlephZeroEventHandlerError>
+where
+    F: FnMut(H256) -> Fut,
+    Fut: Future<Output = Result<TxStatus, ProviderError>>,
+{
+    let Some(tx_hash) = submitted_txs.get(request_hash)? else {
+        return Ok(None);
+    };
+
+    loop {
+        match tx_status(tx_hash).await? {
+            TxStatus::Mined { succeeded: true } => return Ok(Some(tx_hash)),
+            TxStatus::Pending => {
+                info!(
+                    "Previously submitted tx {tx_hash:?} for request 0x{} is still pending, waiting for it",
+                    hex::encode(request_hash)
+                );
+                eth_finalized_head_receiver.changed().await?;
+            }
+            status => {
+                warn!(
+                    "Forgetting tx {tx_hash:?} for request 0x{} as it is {status:?}",
+                    hex::encode(request_hash)
+                );
+                submitted_txs.clear(request_hash)?;
+                return Ok(None);
+            }
+        }
+    }
+}
+
+/// A tx included in a block may still have reverted, which only its receipt shows.
+///
+/// With `expect_log`, the receipt must also carry a log of the `contract` showing that `signer` signed the request,
+/// either anew or after it had already been signed or processed.
+fn check_receipt(
+    receipt: &TransactionReceipt,
+    contract: Address,
+    request_hash: [u8; 32],
+    signer: Address,
+    expect_log: bool,
+) -> Result<(), AlephZeroEventHandlerError> {
+    if receipt.status != Some(U64::one()) {
+        return Err(AlephZeroEventHandlerError::EthContractReverted);
+    }
+    if !expect_log {
+        return Ok(());
+    }
+
+    let signed = receipt
+        .logs
+        .iter()
+        .filter(|log| log.address == contract)
+        .filter_map(|log| parse_log::<MostEvents>(log.clone()).ok())
+        .any(|event| match event {
+            MostEvents::RequestSignedFilter(RequestSignedFilter {
+                request_hash: hash,
+                signer: by,
+            })
+            | MostEvents::ProcessedRequestSignedFilter(ProcessedRequestSignedFilter {
+                request_hash: hash,
+                signer: by,
+            })
+            | MostEvents::RequestAlreadySignedFilter(RequestAlreadySignedFilter {
+                request_hash: hash,
+                signer: by,
+            }) => hash == request_hash && by == signer,
+            _ => false,
+        });
+
+    match signed {
+        true => Ok(()),
+        false => Err(AlephZeroEventHandlerError::MissingSignatureLog(
+            receipt.transaction_hash,
+        )),
+    }
+}
+
+/// Hash of the request the same as the one computed by the Ethereum contract
+fn request_hash(
+    committee_id: u128,
+    dest_token_address: &[u8; 32],
+    amount: u128,
+    dest_receiver_address: &[u8; 32],
+    request_nonce: u128,
+) -> [u8; 32] {
+    // NOTE: for some reason, ethers-rs's `encode_packed` does not properly encode the data
+    // (it does not pad uint to 32 bytes, but uses the actual number of bytes required to store the value)
+    // so we use `abi::encode` instead (it only differs for signed and dynamic size types, which we don't use here)
+    let bytes = abi::encode(&[
+        Token::Uint(committee_id.into()),
+        Token::FixedBytes(dest_token_address.to_vec()),
+        Token::Uint(amount.into()),
+        Token::FixedBytes(dest_receiver_address.to_vec()),
+        Token::Uint(request_nonce.into()),
+    ]);
+
+    trace!("ABI compliant concatenated event bytes {bytes:?}");
+
+    keccak256(bytes)
+}
+
+/// Revert reason of a contract call, decoded from the errors of the Most contract if possible
+fn revert_reason<M: Middleware>(error: &ContractError<M>) -> String {
+    match error.decode_contract_revert::<MostErrors>() {
+        Some(reason) => reason.to_string(),
+        None => error.to_string(),
+    }
+}
+
+/// Errors of the node rejecting a submitted tx which go away once the nonce is read again or the tx is replaced
+const TRANSIENT_SUBMISSION_ERRORS: &[&str] =
+    &["nonce too low", "replacement transaction underpriced"];
+
+/// Whether forwarding a request may succeed when it is tried again, e.g. after the node connection failed or a nonce was
+/// taken. A decoded revert or a misconfiguration is not retried
+fn is_transient(error: &AlephZeroEventHandlerError) -> bool {
+    match error {
+        AlephZeroEventHandlerError::Provider(_)
+        | AlephZeroEventHandlerError::TxNotPresentInBlockOrMempool => true,
+        AlephZeroEventHandlerError::EthContractTx(why) => {
+            matches!(
+                why,
+                ContractError::MiddlewareError { .. } | ContractError::ProviderError { .. }
+            ) || is_transient_submission_error(&why.to_string())
+        }
+        AlephZeroEventHandlerError::OfflineSigning(OfflineSigningError::Provider(why)) => {
+            is_transient_submission_error(&why.to_string())
+        }
+        _ => false,
+    }
+}
+
+fn is_transient_submission_error(message: &str) -> bool {
+    let message = message.to_lowercase();
+    TRANSIENT_SUBMISSION_ERRORS
+        .iter()
+        .any(|error| message.contains(error))
+}
+
+/// Moves a request which still fails for a transient reason after the retries to the dead letter queue for a manual
+/// review. Any other failure is returned as it is
+fn dead_letter_exhausted(
+    result: Result<AuditOutcome, AlephZeroEventHandlerError>,
+    request_hash: [u8; 32],
+    dead_letters: &dyn DeadLetterStore,
+) -> Result<AuditOutcome, AlephZeroEventHandlerError> {
+    match result {
+        Err(why) if is_transient(&why) => {
+            error!(
+                "Request 0x{} still fails with {why:?} after the retries. Moving it to the dead letter queue for a manual review",
+                hex::encode(request_hash)
+            );
+            let violation = format!("forwarding still fails after the retries: {why:?}");
+            dead_letters.push(request_hash, &violation)?;
+            Ok(AuditOutcome::NotSubmitted {
+                reason: format!("moved to the dead letter queue: {violation}"),
+            })
+        }
+        result => result,
+    }
+}
+
+/// Runs `forward` again after a transient failure, for as long as the `backoff` has retries left.
+///
+/// Any other failure is returned right away, as is the last one once the retries are exhausted. Each attempt holds
+/// one of the `submission_permits`, which is given back for the backoff so that the other requests are not held up.
+async fn retry_transient<T, F, Fut>(
+    mut backoff: PollBackoff,
+    request_hash: [u8; 32],
+    submission_permits: &Semaphore,
+    mut forward: F,
+) -> Result<T, AlephZeroEventHandlerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, AlephZeroEventHandlerError>>,
+{
+    loop {
+        let submission_permit = submission_permits.acquire().await?;
+        let result = forward().await;
+        drop(submission_permit);
+
+        match result {
+            Err(why) if is_transient(&why) && backoff.retries_left() => {
+                let delay = backoff.next_delay();
+                warn!(
+                    "Forwarding request 0x{} failed with {why:?}, retrying in {delay:?}",
+                    hex::encode(request_hash)
+                );
+                sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Whether the same `receive_request` call is going to revert no matter how many times it is retried
+fn is_permanent_revert<M: Middleware>(error: &ContractError<M>) -> bool {
+    matches!(
+        error.decode_contract_revert::<MostErrors>(),
+        Some(
+            MostErrors::DataHashMismatch(_)
+                | MostErrors::UnsupportedPair(_)
+                | MostErrors::ZeroAmount(_)
+                | MostErrors::ZeroAddress(_)
+        )
+    )
+}
+
+/// Whether `amount` is below `percent` percent of the `minimum`
+fn is_suspiciously_small(amount: u128, minimum: U256, percent: u64) -> bool {
+    U256::from(amount).saturating_mul(100.into()) < minimum.saturating_mul(percent.into())
+}
+
 async fn not_in_committee(
     most: &Most<SignedEthConnection>,
     committee_id: U256,
@@ -237,6 +1150,9 @@ pub enum AlephZeroEventsHandlerError {
 
     #[error("ack receiver dropped before response could be sent")]
     AckSend,
+
+    #[error("batch semaphore closed")]
+    Acquire(#[from] AcquireError),
 }
 
 pub struct AlephZeroEventsHandler;
@@ -244,23 +1160,32 @@ pub struct AlephZeroEventsHandler;
 impl AlephZeroEventsHandler {
     pub async fn run(
         config: Arc<Config>,
-        eth_signed_connection: Arc<SignedEthConnection>,
+        event_handler: Arc<dyn AzeroEventHandler>,
         mut azero_events_receiver: mpsc::Receiver<AzeroMostEvents>,
         circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
         mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
     ) -> Result<CircuitBreakerEvent, AlephZeroEventsHandlerError> {
         let mut event_handler_tasks = JoinSet::new();
+        let batch_permits = batch_permits(config.sequential_processing);
 
         loop {
             debug!("Ping");
 
             select! {
-                cb_event = circuit_breaker_receiver.recv() => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!("Exiting due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
 
-                Some(azero_events) = azero_events_receiver.recv() => {
+                azero_events = azero_events_receiver.recv() => {
+                    // the listener stopped, e.g. for a shutdown, after all the batches it sent were acknowledged or abandoned
+                    let Some(azero_events) = azero_events else {
+                        info!("No more events to handle, exiting after {} in-flight batches", event_handler_tasks.len());
+                        while let Some(task_result) = event_handler_tasks.join_next().await {
+                            debug!("Event handler task finished with result {task_result:?}");
+                        }
+                        return Ok(CircuitBreakerEvent::Shutdown);
+                    };
                     let AzeroMostEvents {
                         events,
                         from_block,
@@ -270,35 +1195,50 @@ impl AlephZeroEventsHandler {
 
                     info!("Received a batch of {} events from blocks {from_block} to {to_block}", events.len());
 
+                    // Acquired here rather than in the spawned task, so that the batches start in the order they were received
+                    let batch_permit = select! {
+                        cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
+                            warn!("Exiting due to a circuit breaker event {cb_event:?}");
+                            return Ok(cb_event?);
+                        },
+
+                        permit = Arc::clone(&batch_permits).acquire_owned() => permit?,
+                    };
+
                     let config = Arc::clone(&config);
-                    let eth_signed_connection = Arc::clone(&eth_signed_connection);
+                    let event_handler = Arc::clone(&event_handler);
                     let circuit_breaker_sender = circuit_breaker_sender.clone ();
 
                     // spawn non-blocking task to handle all events w-out blocking the events publisher
                     event_handler_tasks.spawn(async move {
-                        let mut tasks = JoinSet::new();
-                        for event in events {
-                            // spawn each handler in separate task as it's time consuming
-                            tasks.spawn(AlephZeroEventHandler::handle_event(
-                                event,
-                                Arc::clone(&config),
-                                Arc::clone(&eth_signed_connection),
-                            ));
-                        }
+                        let _batch_permit = batch_permit;
 
-                        // wait for all concurrent handler tasks to finish
                         info!("Awaiting all event handler tasks for blocks {}-{} to finish", from_block, to_block);
 
-                        while let Some(result) = tasks.join_next().await {
-                            match result? {
-                                Ok(_) => {},
-                                Err(why) => {
-                                    warn!("Event handler failed {why:?}, opening circuit breaker");
-                                    circuit_breaker_sender.send(CircuitBreakerEvent::AlephZeroEventHandlerFailure)?;
-                                },
+                        let results = handle_batch(
+                            events,
+                            config.sequential_processing,
+                            config.azero_max_requests_per_block as usize,
+                            |event| {
+                                let event_handler = Arc::clone(&event_handler);
+                                async move { event_handler.handle(event).await }
+                            },
+                        )
+                        .await?;
+
+                        let mut failed = false;
+                        for result in results {
+                            if let Err(why) = result {
+                                warn!("Event handler failed {why:?}, opening circuit breaker");
+                                open_circuit_breaker(&circuit_breaker_sender, CircuitBreakerEvent::AlephZeroEventHandlerFailure);
+                                failed = true;
                             }
                         }
 
+                        // not acknowledged, so that the batch is fetched again once the relayer restarts
+                        if failed {
+                            return Ok(());
+                        }
                         ack.send(to_block).map_err(|_| AlephZeroEventsHandlerError::AckSend)?;
                         Ok::<(), AlephZeroEventsHandlerError> (())
                     });
@@ -312,3 +1252,1051 @@ impl AlephZeroEventsHandler {
         }
     }
 }
+
+/// Limits the number of batches handled at once - in the sequential mode a batch waits until the previous one is done
+fn batch_permits(sequential: bool) -> Arc<Semaphore> {
+    Arc::new(Semaphore::new(if sequential {
+        1
+    } else {
+        Semaphore::MAX_PERMITS
+    }))
+}
+
+/// Handles the events of a single batch, each in a separate task as it's time consuming, or one by one in the sequential mode.
+///
+/// The tasks are spawned in chunks of `max_requests`, each awaited before the next one is spawned,
+/// so that an unusually busy batch is handled at a throttled rate.
+async fn handle_batch<T, F, Fut>(
+    events: Vec<T>,
+    sequential: bool,
+    max_requests: usize,
+    handle: F,
+) -> Result<Vec<Result<(), AlephZeroEventHandlerError>>, JoinError>
+where
+    F: Fn(T) -> Fut,
+    Fut: Future<Output = Result<(), AlephZeroEventHandlerError>> + Send + 'static,
+{
+    let mut results = Vec::with_capacity(events.len());
+
+    if sequential {
+        for event in events {
+            results.push(handle(event).await);
+        }
+        return Ok(results);
+    }
+
+    let mut events = events.into_iter().peekable();
+    while events.peek().is_some() {
+        let mut tasks = JoinSet::new();
+        for event in events.by_ref().take(max_requests) {
+            tasks.spawn(handle(event));
+        }
+        while let Some(result) = tasks.join_next().await {
+            results.push(result?);
+        }
+    }
+
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Mutex,
+        },
+        time::Duration,
+    };
+
+    use contracts_azero_client::contract_transcode::Value;
+    use ethers::{contract::EthEvent, types::Log};
+    use futures::future;
+    use tokio::{sync::oneshot, time::sleep};
+
+    use super::*;
+    use crate::{
+        config::test_config,
+        contracts::RequestStatus,
+        handlers::{request_hash_vectors, ReceiveRequest},
+    };
+
+    /// The default of `--azero-max-requests-per-block`
+    const MAX_REQUESTS: usize = 50;
+
+    fn event(name: &str, data: HashMap<String, Value>) -> ContractEvent {
+        ContractEvent {
+            contract: AccountId::from([0; 32]),
+            name: Some(name.to_owned()),
+            data,
+            block_number: None,
+        }
+    }
+
+    fn transfer_request(request_nonce: u128, amount: u128) -> ContractEvent {
+        let address = |byte| Value::Seq(vec![Value::UInt(byte); 32].into());
+
+        event(
+            "CrosschainTransferRequest",
+            HashMap::from([
+                ("committee_id".to_owned(), Value::UInt(0)),
+                ("dest_token_address".to_owned(), address(1)),
+                ("amount".to_owned(), Value::UInt(amount)),
+                ("dest_receiver_address".to_owned(), address(2)),
+                ("request_nonce".to_owned(), Value::UInt(request_nonce)),
+            ]),
+        )
+    }
+
+    /// Records the name of every event it is given, along with the nonce and amount of the transfer requests
+    #[derive(Default)]
+    struct RecordingHandler(Mutex<Vec<(String, Option<(u128, u128)>)>>);
+
+    #[async_trait::async_trait]
+    impl AzeroEventHandler for RecordingHandler {
+        async fn handle(&self, event: ContractEvent) -> Result<(), AlephZeroEventHandlerError> {
+            let request = match route(event.name.as_deref()) {
+                Some(EventRoute::CrosschainTransferRequest) => {
+                    let data = get_request_event_data(&event.data)?;
+                    Some((data.request_nonce, data.amount))
+                }
+                _ => None,
+            };
+            self.0
+                .lock()
+                .unwrap()
+                .push((event.name.unwrap_or_default(), request));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeadLetterStore(Mutex<Vec<([u8; 32], String)>>);
+
+    impl DeadLetterStore for InMemoryDeadLetterStore {
+        fn push(&self, request_hash: [u8; 32], reason: &str) -> Result<(), redis::RedisError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((request_hash, reason.to_owned()));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemorySubmittedTxStore(Mutex<HashMap<[u8; 32], H256>>);
+
+    impl SubmittedTxStore for InMemorySubmittedTxStore {
+        fn record(&self, request_hash: [u8; 32], tx_hash: H256) -> Result<(), redis::RedisError> {
+            self.0.lock().unwrap().insert(request_hash, tx_hash);
+            Ok(())
+        }
+
+        fn get(&self, request_hash: [u8; 32]) -> Result<Option<H256>, redis::RedisError> {
+            Ok(self.0.lock().unwrap().get(&request_hash).copied())
+        }
+
+        fn clear(&self, request_hash: [u8; 32]) -> Result<(), redis::RedisError> {
+            self.0.lock().unwrap().remove(&request_hash);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn batches_are_dispatched_to_the_injected_handler() {
+        let handler = Arc::new(RecordingHandler::default());
+        let (events_sender, events_receiver) = mpsc::channel(1);
+        let (circuit_breaker_sender, circuit_breaker_receiver) = broadcast::channel(1);
+
+        let events_handler = tokio::spawn(AlephZeroEventsHandler::run(
+            Arc::new(test_config(&["--sequential-processing"])),
+            Arc::clone(&handler) as Arc<dyn AzeroEventHandler>,
+            events_receiver,
+            circuit_breaker_sender.clone(),
+            circuit_breaker_receiver,
+        ));
+
+        let batches = [
+            (
+                10,
+                vec![
+                    transfer_request(1, 100),
+                    event("ConfigChanged", HashMap::new()),
+                ],
+            ),
+            (20, vec![transfer_request(2, 200)]),
+        ];
+        for (to_block, events) in batches {
+            let (ack, acked) = oneshot::channel();
+            events_sender
+                .send(AzeroMostEvents {
+                    events,
+                    from_block: to_block - 9,
+                    to_block,
+                    ack,
+                })
+                .await
+                .unwrap();
+            assert_eq!(acked.await.unwrap(), to_block);
+        }
+
+        circuit_breaker_sender
+            .send(CircuitBreakerEvent::BridgeHaltAlephZero)
+            .unwrap();
+        assert!(matches!(
+            events_handler.await.unwrap(),
+            Ok(CircuitBreakerEvent::BridgeHaltAlephZero)
+        ));
+
+        assert_eq!(
+            *handler.0.lock().unwrap(),
+            [
+                ("CrosschainTransferRequest".to_owned(), Some((1, 100))),
+                ("ConfigChanged".to_owned(), None),
+                ("CrosschainTransferRequest".to_owned(), Some((2, 200))),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn in_flight_batch_is_finished_once_the_listener_stopped() {
+        let handler = Arc::new(RecordingHandler::default());
+        let (events_sender, events_receiver) = mpsc::channel(1);
+        let (circuit_breaker_sender, circuit_breaker_receiver) = broadcast::channel(1);
+
+        let events_handler = tokio::spawn(AlephZeroEventsHandler::run(
+            Arc::new(test_config(&[])),
+            Arc::clone(&handler) as Arc<dyn AzeroEventHandler>,
+            events_receiver,
+            circuit_breaker_sender,
+            circuit_breaker_receiver,
+        ));
+
+        let (ack, acked) = oneshot::channel();
+        events_sender
+            .send(AzeroMostEvents {
+                events: vec![transfer_request(1, 100)],
+                from_block: 1,
+                to_block: 10,
+                ack,
+            })
+            .await
+            .unwrap();
+        // the listener stopped while the batch is still being handled
+        drop(events_sender);
+
+        assert!(matches!(
+            events_handler.await.unwrap(),
+            Ok(CircuitBreakerEvent::Shutdown)
+        ));
+        assert_eq!(acked.await.unwrap(), 10);
+        assert_eq!(handler.0.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn known_events_are_routed_to_their_handlers() {
+        assert_eq!(
+            route(Some("CrosschainTransferRequest")),
+            Some(EventRoute::CrosschainTransferRequest)
+        );
+        assert_eq!(
+            route(Some("ConfigChanged")),
+            Some(EventRoute::ConfigChanged)
+        );
+    }
+
+    #[test]
+    fn unknown_events_are_ignored() {
+        assert_eq!(route(Some("RequestProcessed")), None);
+        assert_eq!(route(Some("crosschaintransferrequest")), None);
+        assert_eq!(route(None), None);
+    }
+
+    #[test]
+    fn unnamed_event_of_the_most_contract_is_reported() {
+        let config = test_config(&[]);
+        let metrics = Metrics::default();
+        let most = AccountId::from_str(&config.azero_contract_address).unwrap();
+        let unnamed = |contract| ContractEvent {
+            contract,
+            name: None,
+            data: HashMap::new(),
+            block_number: Some(7),
+        };
+
+        assert!(report_unnamed_event(&config, &metrics, &unnamed(most)));
+        assert!(!report_unnamed_event(
+            &config,
+            &metrics,
+            &unnamed(AccountId::from([9; 32]))
+        ));
+        assert!(!report_unnamed_event(
+            &config,
+            &metrics,
+            &event("RequestProcessed", HashMap::new())
+        ));
+
+        assert_eq!(
+            metrics.render(true),
+            "redis_up 1\nunnamed_events_total{chain=\"AlephZero\"} 1\n"
+        );
+    }
+
+    #[test]
+    fn request_hash_matches_the_golden_vectors() {
+        for vector in request_hash_vectors("azero_to_eth") {
+            assert_eq!(
+                request_hash(
+                    vector.committee_id,
+                    &vector.dest_token_address,
+                    vector.amount,
+                    &vector.dest_receiver_address,
+                    vector.request_nonce,
+                ),
+                vector.request_hash,
+                "{vector:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn revert_reason_is_decoded() {
+        let revert_string = [
+            &[0x08, 0xc3, 0x79, 0xa0][..],
+            &abi::encode(&[Token::String("unknown token".to_owned())]),
+        ]
+        .concat();
+        let reverted = ContractError::<SignedEthConnection>::Revert(revert_string.into());
+
+        assert_eq!(revert_reason(&reverted), "unknown token");
+    }
+
+    #[test]
+    fn only_reverts_which_can_not_resolve_are_permanent() {
+        let custom_error = |signature: &str| {
+            ContractError::<SignedEthConnection>::Revert(keccak256(signature)[..4].to_vec().into())
+        };
+        let revert_string = [
+            &[0x08, 0xc3, 0x79, 0xa0][..],
+            &abi::encode(&[Token::String("paused".to_owned())]),
+        ]
+        .concat();
+
+        assert!(is_permanent_revert(&custom_error("DataHashMismatch()")));
+        assert!(is_permanent_revert(&custom_error("ZeroAddress()")));
+        assert!(!is_permanent_revert(&custom_error("NotInCommittee()")));
+        assert!(!is_permanent_revert(
+            &ContractError::<SignedEthConnection>::Revert(revert_string.into())
+        ));
+    }
+
+    #[test]
+    fn amount_far_below_minimum_is_suspicious() {
+        let minimum = U256::from(1_000_000);
+
+        assert!(is_suspiciously_small(99_999, minimum, 10));
+        assert!(is_suspiciously_small(0, minimum, 10));
+        assert!(!is_suspiciously_small(100_000, minimum, 10));
+        assert!(!is_suspiciously_small(999_999, minimum, 10));
+        assert!(!is_suspiciously_small(u128::MAX, minimum, 10));
+    }
+
+    #[test]
+    fn nothing_is_suspicious_without_minimum() {
+        assert!(!is_suspiciously_small(0, U256::zero(), 10));
+        assert!(!is_suspiciously_small(1, U256::from(1_000_000), 0));
+    }
+
+    /// Most contract halted for the given number of `is_halted` reads
+    struct HaltedMost(AtomicU32);
+
+    #[async_trait::async_trait]
+    impl AzeroMost for HaltedMost {
+        async fn request_status(
+            &self,
+            _request_hash: [u8; 32],
+        ) -> Result<RequestStatus, AzeroContractError> {
+            Ok(RequestStatus::Unknown)
+        }
+
+        async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+            Ok(self
+                .0
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |reads| {
+                    reads.checked_sub(1)
+                })
+                .is_ok())
+        }
+
+        async fn is_in_committee(&self, _committee_id: u128) -> Result<bool, AzeroContractError> {
+            Ok(true)
+        }
+
+        async fn current_committee_id(&self) -> Result<u128, AzeroContractError> {
+            Ok(0)
+        }
+
+        async fn max_receive_amount(
+            &self,
+            _dest_token_address: [u8; 32],
+        ) -> Result<Option<u128>, AzeroContractError> {
+            Ok(None)
+        }
+
+        async fn needs_signature(
+            &self,
+            _request_hash: [u8; 32],
+            _committee_id: u128,
+            _block_finalized: bool,
+        ) -> Result<bool, AzeroContractError> {
+            Ok(true)
+        }
+
+        async fn receive_request(
+            &self,
+            _request: &ReceiveRequest,
+        ) -> Result<(), AzeroContractError> {
+            panic!("requests to Ethereum are not received on AlephZero");
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_is_deferred_while_halted() {
+        const RECHECK: Duration = Duration::from_secs(30);
+        let most = HaltedMost(AtomicU32::new(3));
+        let started = Instant::now();
+
+        await_unhalted(&most, [1; 32], RECHECK).await.unwrap();
+
+        assert_eq!(started.elapsed(), 3 * RECHECK);
+        assert_eq!(most.0.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_is_not_deferred_when_not_halted() {
+        let started = Instant::now();
+
+        await_unhalted(
+            &HaltedMost(AtomicU32::new(0)),
+            [1; 32],
+            Duration::from_secs(30),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(started.elapsed(), Duration::ZERO);
+    }
+
+    /// Ethereum node quoting the given gas prices in gwei, the last one from then on
+    fn gas_prices(gwei: &[u64]) -> impl FnMut() -> future::Ready<Result<U256, ProviderError>> {
+        let mut prices = gwei
+            .iter()
+            .map(|&gwei| U256::from(gwei) * U256::exp10(9))
+            .collect::<Vec<_>>();
+        prices.reverse();
+        move || {
+            let price = if prices.len() > 1 {
+                prices.pop()
+            } else {
+                prices.last().copied()
+            };
+            future::ready(Ok(price.expect("a gas price")))
+        }
+    }
+
+    fn gas_price_backoff() -> PollBackoff {
+        PollBackoff::new(Duration::from_secs(12), Duration::from_secs(300), 0)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn submission_is_not_sent_while_gas_price_is_above_the_ceiling() {
+        let ceiling = U256::from(100) * U256::exp10(9);
+        let sent = AtomicU32::new(0);
+
+        let submission = async {
+            await_gas_price_within(ceiling, [1; 32], gas_price_backoff(), gas_prices(&[250]))
+                .await
+                .unwrap();
+            sent.fetch_add(1, Ordering::SeqCst);
+        };
+
+        assert!(tokio::time::timeout(Duration::from_secs(3600), submission)
+            .await
+            .is_err());
+        assert_eq!(sent.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn submission_resumes_once_gas_price_drops() {
+        let ceiling = U256::from(100) * U256::exp10(9);
+        let started = Instant::now();
+
+        await_gas_price_within(
+            ceiling,
+            [1; 32],
+            gas_price_backoff(),
+            gas_prices(&[250, 150, 100]),
+        )
+        .await
+        .unwrap();
+
+        // two rechecks, after 12 and 24 seconds plus the jitter
+        assert!(started.elapsed() >= Duration::from_secs(36));
+        assert!(started.elapsed() < Duration::from_secs(54));
+    }
+
+    fn forwarding_backoff(retries: u32) -> PollBackoff {
+        PollBackoff::new(Duration::from_secs(12), Duration::from_secs(120), retries)
+    }
+
+    /// Forwarding which fails with a dropped node connection the given number of times, and then succeeds
+    fn failing_forward(
+        failures: u32,
+        attempts: &AtomicU32,
+    ) -> impl FnMut() -> future::Ready<Result<(), AlephZeroEventHandlerError>> + '_ {
+        move || {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            future::ready(if attempt < failures {
+                Err(ProviderError::CustomError("connection reset".to_owned()).into())
+            } else {
+                Ok(())
+            })
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn transient_failures_are_retried() {
+        let attempts = AtomicU32::new(0);
+
+        retry_transient(
+            forwarding_backoff(2),
+            [1; 32],
+            &Semaphore::new(1),
+            failing_forward(2, &attempts),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn retries_are_capped() {
+        let attempts = AtomicU32::new(0);
+
+        let result = retry_transient(
+            forwarding_backoff(2),
+            [1; 32],
+            &Semaphore::new(1),
+            failing_forward(5, &attempts),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(AlephZeroEventHandlerError::Provider(_))
+        ));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn taken_nonce_and_underpriced_replacement_are_transient() {
+        let rejected = |message: &str| {
+            AlephZeroEventHandlerError::OfflineSigning(OfflineSigningError::Provider(
+                ProviderError::CustomError(message.to_owned()),
+            ))
+        };
+
+        assert!(is_transient(&rejected("nonce too low")));
+        assert!(is_transient(&rejected(
+            "replacement transaction underpriced"
+        )));
+        assert!(!is_transient(&rejected("insufficient funds for gas")));
+    }
+
+    #[test]
+    fn exhausted_request_is_dead_lettered() {
+        let dead_letters = InMemoryDeadLetterStore::default();
+
+        let outcome = dead_letter_exhausted(
+            Err(ProviderError::CustomError("connection reset".to_owned()).into()),
+            [1; 32],
+            &dead_letters,
+        )
+        .unwrap();
+
+        assert!(matches!(outcome, AuditOutcome::NotSubmitted { .. }));
+        let dead_letters = dead_letters.0.into_inner().unwrap();
+        assert_eq!(dead_letters.len(), 1);
+        assert_eq!(dead_letters[0].0, [1; 32]);
+    }
+
+    #[test]
+    fn permanent_failure_is_not_dead_lettered() {
+        let dead_letters = InMemoryDeadLetterStore::default();
+
+        let result = dead_letter_exhausted(
+            Err(AlephZeroEventHandlerError::CommitteeIdMismatch),
+            [1; 32],
+            &dead_letters,
+        );
+
+        assert!(matches!(
+            result,
+            Err(AlephZeroEventHandlerError::CommitteeIdMismatch)
+        ));
+        assert!(dead_letters.0.into_inner().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn revert_is_not_retried() {
+        let attempts = AtomicU32::new(0);
+
+        let result = retry_transient(forwarding_backoff(2), [1; 32], &Semaphore::new(1), || {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            future::ready(Err::<(), _>(AlephZeroEventHandlerError::EthContractTx(
+                ContractError::Revert(Default::default()),
+            )))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn submitted_tx_is_recorded_until_confirmed() {
+        let submitted_txs = Arc::new(InMemorySubmittedTxStore::default());
+        let request_hash = [1; 32];
+        let tx_hash = H256::repeat_byte(2);
+        let (confirm, confirmed) = oneshot::channel::<TransactionReceipt>();
+
+        let waiting = tokio::spawn({
+            let submitted_txs = Arc::clone(&submitted_txs);
+            async move {
+                await_confirmations(submitted_txs.as_ref(), request_hash, tx_hash, async {
+                    Ok(confirmed.await.unwrap())
+                })
+                .await
+            }
+        });
+
+        tokio::task::yield_now().await;
+        assert_eq!(submitted_txs.get(request_hash).unwrap(), Some(tx_hash));
+
+        confirm.send(TransactionReceipt::default()).unwrap();
+        waiting.await.unwrap().unwrap();
+        assert_eq!(submitted_txs.get(request_hash).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn submitted_tx_is_kept_when_confirmations_fail() {
+        let submitted_txs = InMemorySubmittedTxStore::default();
+        let request_hash = [1; 32];
+        let tx_hash = H256::repeat_byte(2);
+
+        let result = await_confirmations(&submitted_txs, request_hash, tx_hash, async {
+            Err(AlephZeroEventHandlerError::TxNotPresentInBlockOrMempool)
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(submitted_txs.get(request_hash).unwrap(), Some(tx_hash));
+    }
+
+    #[tokio::test]
+    async fn pending_submission_from_before_a_restart_is_not_sent_again() {
+        // the store outlives the relayer, as redis does
+        let submitted_txs = InMemorySubmittedTxStore::default();
+        let request_hash = [1; 32];
+        let tx_hash = H256::repeat_byte(2);
+        submitted_txs.record(request_hash, tx_hash).unwrap();
+
+        for status in [TxStatus::Pending, TxStatus::Mined { succeeded: true }] {
+            let pending =
+                pending_submission(&submitted_txs, request_hash, |_| async move { Ok(status) })
+                    .await
+                    .unwrap();
+
+            assert_eq!(pending, Some(tx_hash));
+            assert_eq!(submitted_txs.get(request_hash).unwrap(), Some(tx_hash));
+        }
+    }
+
+    #[tokio::test]
+    async fn recorded_tx_is_awaited_until_mined() {
+        let submitted_txs = InMemorySubmittedTxStore::default();
+        let request_hash = [1; 32];
+        let tx_hash = H256::repeat_byte(2);
+        submitted_txs.record(request_hash, tx_hash).unwrap();
+        let (head_sender, mut head_receiver) = watch::channel(0);
+
+        let mined = await_recorded_tx(
+            &submitted_txs,
+            request_hash,
+            |_| {
+                // the next check follows a new finalized head
+                let head = *head_sender.borrow();
+                head_sender.send_replace(head + 1);
+                let status = match head {
+                    0 | 1 => TxStatus::Pending,
+                    _ => TxStatus::Mined { succeeded: true },
+                };
+                async move { Ok(status) }
+            },
+            &mut head_receiver,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(mined, Some(tx_hash));
+        assert_eq!(*head_receiver.borrow(), 3);
+        // kept until the finality of the request
+        assert_eq!(submitted_txs.get(request_hash).unwrap(), Some(tx_hash));
+    }
+
+    #[tokio::test]
+    async fn recorded_tx_which_is_dropped_is_submitted_again() {
+        let submitted_txs = InMemorySubmittedTxStore::default();
+        let request_hash = [1; 32];
+        submitted_txs
+            .record(request_hash, H256::repeat_byte(2))
+            .unwrap();
+        let (head_sender, mut head_receiver) = watch::channel(0);
+
+        let mined = await_recorded_tx(
+            &submitted_txs,
+            request_hash,
+            |_| {
+                // the next check follows a new finalized head
+                let head = *head_sender.borrow();
+                head_sender.send_replace(head + 1);
+                let status = match head {
+                    0 => TxStatus::Pending,
+                    _ => TxStatus::Unknown,
+                };
+                async move { Ok(status) }
+            },
+            &mut head_receiver,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(mined, None);
+        assert_eq!(submitted_txs.get(request_hash).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn failed_submission_is_forgotten() {
+        let submitted_txs = InMemorySubmittedTxStore::default();
+        let request_hash = [1; 32];
+
+        for status in [TxStatus::Mined { succeeded: false }, TxStatus::Unknown] {
+            submitted_txs
+                .record(request_hash, H256::repeat_byte(2))
+                .unwrap();
+
+            let pending =
+                pending_submission(&submitted_txs, request_hash, |_| async move { Ok(status) })
+                    .await
+                    .unwrap();
+
+            assert_eq!(pending, None);
+            assert_eq!(submitted_txs.get(request_hash).unwrap(), None);
+        }
+
+        submitted_txs
+            .record(request_hash, H256::repeat_byte(2))
+            .unwrap();
+        let result = pending_submission(&submitted_txs, request_hash, |_| async {
+            Err(ProviderError::CustomError("connection reset".to_owned()))
+        })
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(AlephZeroEventHandlerError::Provider(_))
+        ));
+        assert_eq!(submitted_txs.get(request_hash).unwrap(), None);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn finality_is_not_checked_more_often_than_the_interval() {
+        let (head_sender, mut head_receiver) = watch::channel(0);
+        let checked_at = Instant::now();
+        head_sender.send_replace(1);
+
+        next_finality_check(&mut head_receiver, checked_at)
+            .await
+            .unwrap();
+        assert_eq!(
+            checked_at.elapsed(),
+            Duration::from_secs(ETH_WAIT_FOR_FINALITY_CHECK_SEC)
+        );
+
+        // the head seen by the last check is not a reason for another one
+        let checked_at = Instant::now();
+        tokio::spawn(async move {
+            sleep(Duration::from_secs(2 * ETH_WAIT_FOR_FINALITY_CHECK_SEC)).await;
+            head_sender.send_replace(2);
+        });
+        next_finality_check(&mut head_receiver, checked_at)
+            .await
+            .unwrap();
+        assert_eq!(
+            checked_at.elapsed(),
+            Duration::from_secs(2 * ETH_WAIT_FOR_FINALITY_CHECK_SEC)
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batches_are_not_held_by_the_finality_waits() {
+        const FINALITY: Duration = Duration::from_secs(15 * 60);
+        let finality_tracker = Arc::new(FinalityTracker::new(8));
+        let finalized = Arc::new(AtomicU32::new(0));
+        let started = Instant::now();
+
+        for events in [vec![1, 2, 3], vec![4, 5]] {
+            let results = handle_batch(events, false, MAX_REQUESTS, |_| {
+                let finality_tracker = Arc::clone(&finality_tracker);
+                let finalized = Arc::clone(&finalized);
+                async move {
+                    // the tx is sent and mined
+                    sleep(Duration::from_secs(12)).await;
+                    finality_tracker
+                        .track(async move {
+                            sleep(FINALITY).await;
+                            finalized.fetch_add(1, Ordering::SeqCst);
+                        })
+                        .await?;
+                    Ok(())
+                }
+            })
+            .await
+            .unwrap();
+            assert!(results.iter().all(Result::is_ok));
+        }
+
+        assert_eq!(started.elapsed(), Duration::from_secs(24));
+        assert_eq!(finalized.load(Ordering::SeqCst), 0);
+
+        sleep(FINALITY + Duration::from_secs(1)).await;
+        assert_eq!(finalized.load(Ordering::SeqCst), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn finality_tracking_is_bounded() {
+        let finality_tracker = FinalityTracker::new(1);
+        let started = Instant::now();
+
+        finality_tracker
+            .track(sleep(Duration::from_secs(60)))
+            .await
+            .unwrap();
+        assert_eq!(started.elapsed(), Duration::ZERO);
+
+        // waits for the free slot
+        finality_tracker.track(async {}).await.unwrap();
+        assert_eq!(started.elapsed(), Duration::from_secs(60));
+    }
+
+    /// Handles two batches of slow events the way `AlephZeroEventsHandler` does, returns the peak number of concurrent handlers
+    async fn max_concurrent_handlers(sequential: bool) -> u32 {
+        let running = Arc::new(AtomicU32::new(0));
+        let max_running = Arc::new(AtomicU32::new(0));
+        let batch_permits = batch_permits(sequential);
+        let mut batches = JoinSet::new();
+
+        for events in [vec![1, 2, 3], vec![4, 5]] {
+            let batch_permit = Arc::clone(&batch_permits).acquire_owned().await.unwrap();
+            let running = Arc::clone(&running);
+            let max_running = Arc::clone(&max_running);
+
+            batches.spawn(async move {
+                let _batch_permit = batch_permit;
+                handle_batch(events, sequential, MAX_REQUESTS, |_event: u32| {
+                    let running = Arc::clone(&running);
+                    let max_running = Arc::clone(&max_running);
+                    async move {
+                        let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                        max_running.fetch_max(now_running, Ordering::SeqCst);
+                        sleep(Duration::from_secs(1)).await;
+                        running.fetch_sub(1, Ordering::SeqCst);
+                        Ok(())
+                    }
+                })
+                .await
+                .unwrap()
+            });
+        }
+
+        while let Some(results) = batches.join_next().await {
+            assert!(results.unwrap().iter().all(Result::is_ok));
+        }
+        max_running.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sequential_processing_runs_one_handler_at_a_time() {
+        assert_eq!(max_concurrent_handlers(true).await, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn events_are_handled_concurrently_by_default() {
+        assert!(max_concurrent_handlers(false).await > 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn concurrent_submissions_are_capped_across_batches() {
+        let submission_permits = Arc::new(Semaphore::new(2));
+        let running = Arc::new(AtomicU32::new(0));
+        let max_running = Arc::new(AtomicU32::new(0));
+        let mut batches = JoinSet::new();
+
+        // each of the two batches alone would fit under the cap
+        for events in [vec![1, 2], vec![3, 4]] {
+            let submission_permits = Arc::clone(&submission_permits);
+            let running = Arc::clone(&running);
+            let max_running = Arc::clone(&max_running);
+
+            batches.spawn(async move {
+                handle_batch(events, false, MAX_REQUESTS, |_event: u32| {
+                    let submission_permits = Arc::clone(&submission_permits);
+                    let running = Arc::clone(&running);
+                    let max_running = Arc::clone(&max_running);
+                    async move {
+                        retry_transient(
+                            forwarding_backoff(0),
+                            [1; 32],
+                            &submission_permits,
+                            || async {
+                                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                                max_running.fetch_max(now_running, Ordering::SeqCst);
+                                sleep(Duration::from_secs(1)).await;
+                                running.fetch_sub(1, Ordering::SeqCst);
+                                Ok(())
+                            },
+                        )
+                        .await
+                    }
+                })
+                .await
+                .unwrap()
+            });
+        }
+
+        while let Some(results) = batches.join_next().await {
+            assert!(results.unwrap().iter().all(Result::is_ok));
+        }
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn submission_permit_is_given_back_for_the_backoff() {
+        let submission_permits = Semaphore::new(1);
+        let attempts = AtomicU32::new(0);
+        let started = Instant::now();
+
+        let (retried, other) = tokio::join!(
+            retry_transient(
+                forwarding_backoff(2),
+                [1; 32],
+                &submission_permits,
+                failing_forward(1, &attempts),
+            ),
+            async {
+                // submitted while the first request backs off
+                sleep(Duration::from_millis(1)).await;
+                retry_transient(
+                    forwarding_backoff(2),
+                    [2; 32],
+                    &submission_permits,
+                    || async { Ok(started.elapsed()) },
+                )
+                .await
+            },
+        );
+
+        retried.unwrap();
+        assert!(other.unwrap() < forwarding_backoff(2).next_delay());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn busy_batch_is_handled_in_chunks() {
+        let running = Arc::new(AtomicU32::new(0));
+        let max_running = Arc::new(AtomicU32::new(0));
+        let events: Vec<_> = (0..2 * MAX_REQUESTS + 1).collect();
+
+        let results = handle_batch(events, false, MAX_REQUESTS, |_event| {
+            let running = Arc::clone(&running);
+            let max_running = Arc::clone(&max_running);
+            async move {
+                let now_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now_running, Ordering::SeqCst);
+                sleep(Duration::from_secs(1)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                Ok(())
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2 * MAX_REQUESTS + 1);
+        assert!(results.iter().all(Result::is_ok));
+        assert_eq!(max_running.load(Ordering::SeqCst) as usize, MAX_REQUESTS);
+    }
+
+    const CONTRACT: Address = Address::repeat_byte(1);
+    const SIGNER: Address = Address::repeat_byte(2);
+
+    fn receipt(status: u64, logs: Vec<Log>) -> TransactionReceipt {
+        TransactionReceipt {
+            status: Some(status.into()),
+            logs,
+            ..Default::default()
+        }
+    }
+
+    fn request_signed_log(request_hash: [u8; 32], signer: Address) -> Log {
+        Log {
+            address: CONTRACT,
+            topics: vec![RequestSignedFilter::signature()],
+            data: abi::encode(&[
+                Token::FixedBytes(request_hash.to_vec()),
+                Token::Address(signer),
+            ])
+            .into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn reverted_receipt_is_an_error() {
+        let receipt = receipt(0, vec![request_signed_log([1; 32], SIGNER)]);
+
+        for expect_log in [false, true] {
+            assert!(matches!(
+                check_receipt(&receipt, CONTRACT, [1; 32], SIGNER, expect_log),
+                Err(AlephZeroEventHandlerError::EthContractReverted)
+            ));
+        }
+    }
+
+    #[test]
+    fn successful_receipt_needs_the_signature_log_only_when_asked() {
+        let without_logs = receipt(1, vec![]);
+        let signed = receipt(1, vec![request_signed_log([1; 32], SIGNER)]);
+
+        assert!(check_receipt(&without_logs, CONTRACT, [1; 32], SIGNER, false).is_ok());
+        assert!(matches!(
+            check_receipt(&without_logs, CONTRACT, [1; 32], SIGNER, true),
+            Err(AlephZeroEventHandlerError::MissingSignatureLog(_))
+        ));
+        assert!(check_receipt(&signed, CONTRACT, [1; 32], SIGNER, true).is_ok());
+        // a signature of another request or by another guardian does not count
+        assert!(check_receipt(&signed, CONTRACT, [2; 32], SIGNER, true).is_err());
+        assert!(check_receipt(&signed, CONTRACT, [1; 32], Address::repeat_byte(3), true).is_err());
+    }
+}
@@ -1,22 +1,34 @@
 use std::{str::FromStr, sync::Arc};
 
 use contracts_azero_client::{AccountId, ClientWithSigner};
-use ethers::{core::types::H256, utils::keccak256};
+use ethers::{
+    core::types::{H256, U256},
+    utils::keccak256,
+};
 use log::{debug, error, info, trace, warn};
 use rustc_hex::FromHexError;
 use thiserror::Error;
 use tokio::{
     select,
     sync::{broadcast, mpsc},
-    time::{sleep, Duration},
+    time::{sleep, Duration, Instant},
 };
+use tracing::Instrument;
 
 use crate::{
+    admin::{SubmissionGate, SubmissionGateError},
     config::Config,
     connections::azero::AzeroSigner,
-    contracts::{AzeroContractError, CrosschainTransferRequestFilter, MostEvents, MostInstance},
-    helpers::concat_u8_arrays,
+    contracts::{
+        AzeroContractError, CrosschainTransferRequestFilter, MostError, MostEvents, MostInstance,
+        RequestStatus,
+    },
+    dedup::RequestDeduplicator,
+    handlers::{record_request, request_span},
+    helpers::{concat_u8_arrays, next_circuit_breaker_event, open_circuit_breaker, LastKnownGood},
     listeners::EthMostEvents,
+    metrics::Metrics,
+    redis::{DeadLetterStore, RelayedRequestStore},
     CircuitBreakerEvent,
 };
 
@@ -48,6 +60,174 @@ pub enum EthereumEventHandlerError {
 
     #[error("Bridge misconfiguration: committee id mismatch")]
     CommitteeIdMismatch,
+
+    #[error("Redis error")]
+    Redis(#[from] redis::RedisError),
+
+    #[error("Event field does not fit into u128")]
+    Overflow { field: &'static str, value: U256 },
+
+    #[error("Manual confirmation of the submission failed")]
+    SubmissionGate(#[from] SubmissionGateError),
+}
+
+/// Arguments of the `receive_request` call which submits the guardian's signature of a transfer request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiveRequest {
+    pub request_hash: [u8; 32],
+    pub committee_id: u128,
+    pub dest_token_address: [u8; 32],
+    pub amount: u128,
+    pub dest_receiver_address: [u8; 32],
+    pub request_nonce: u128,
+}
+
+/// The AlephZero Most contract as seen by the guardian's account.
+///
+/// Injected into [`EthereumEventHandler`], so that Ethereum events can be handled without an AlephZero node.
+#[async_trait::async_trait]
+pub trait AzeroMost: Send + Sync {
+    async fn request_status(
+        &self,
+        request_hash: [u8; 32],
+    ) -> Result<RequestStatus, AzeroContractError>;
+
+    async fn is_halted(&self) -> Result<bool, AzeroContractError>;
+
+    /// Whether the guardian is a member of the committee
+    async fn is_in_committee(&self, committee_id: u128) -> Result<bool, AzeroContractError>;
+
+    async fn current_committee_id(&self) -> Result<u128, AzeroContractError>;
+
+    /// Largest amount of the token a single request can transfer, `None` if there is no limit
+    async fn max_receive_amount(
+        &self,
+        dest_token_address: [u8; 32],
+    ) -> Result<Option<u128>, AzeroContractError>;
+
+    /// Whether the request still needs the guardian's signature, as of the last finalized block if `block_finalized`
+    async fn needs_signature(
+        &self,
+        request_hash: [u8; 32],
+        committee_id: u128,
+        block_finalized: bool,
+    ) -> Result<bool, AzeroContractError>;
+
+    async fn receive_request(&self, request: &ReceiveRequest) -> Result<(), AzeroContractError>;
+}
+
+/// Most contract called through the signed AlephZero connection
+pub struct SignedMostInstance {
+    contract: MostInstance,
+    connection: Arc<ClientWithSigner<AzeroSigner>>,
+    guardian: AccountId,
+    current_committee_id: LastKnownGood<(), u128>,
+    is_in_committee: LastKnownGood<u128, bool>,
+}
+
+impl SignedMostInstance {
+    pub fn new(
+        config: &Config,
+        connection: Arc<ClientWithSigner<AzeroSigner>>,
+    ) -> Result<Self, AzeroContractError> {
+        let guard_read_ttl = Duration::from_secs(config.azero_guard_read_ttl_sec);
+
+        Ok(Self {
+            contract: MostInstance::new(
+                &config.azero_contract_address,
+                &config.azero_contract_metadata,
+                config.azero_ref_time_limit,
+                config.azero_proof_size_limit,
+            )?,
+            guardian: connection.account_id().clone(),
+            connection,
+            current_committee_id: LastKnownGood::new(guard_read_ttl),
+            is_in_committee: LastKnownGood::new(guard_read_ttl),
+        })
+    }
+
+    /// Reads the committee membership and the signatures of `guardian` instead of the signer's account
+    pub fn observing(self, guardian: AccountId) -> Self {
+        Self { guardian, ..self }
+    }
+}
+
+#[async_trait::async_trait]
+impl AzeroMost for SignedMostInstance {
+    async fn request_status(
+        &self,
+        request_hash: [u8; 32],
+    ) -> Result<RequestStatus, AzeroContractError> {
+        self.contract
+            .request_status(self.connection.client(), request_hash)
+            .await
+    }
+
+    async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+        self.contract.is_halted(self.connection.client()).await
+    }
+
+    async fn is_in_committee(&self, committee_id: u128) -> Result<bool, AzeroContractError> {
+        self.is_in_committee
+            .read(
+                committee_id,
+                self.contract.is_in_committee(
+                    self.connection.client(),
+                    committee_id,
+                    self.guardian.clone(),
+                ),
+            )
+            .await
+    }
+
+    async fn current_committee_id(&self) -> Result<u128, AzeroContractError> {
+        self.current_committee_id
+            .read(
+                (),
+                self.contract.current_committee_id(self.connection.client()),
+            )
+            .await
+    }
+
+    async fn max_receive_amount(
+        &self,
+        dest_token_address: [u8; 32],
+    ) -> Result<Option<u128>, AzeroContractError> {
+        self.contract
+            .max_receive_amount(self.connection.client(), dest_token_address)
+            .await
+    }
+
+    async fn needs_signature(
+        &self,
+        request_hash: [u8; 32],
+        committee_id: u128,
+        block_finalized: bool,
+    ) -> Result<bool, AzeroContractError> {
+        self.contract
+            .needs_signature(
+                self.connection.client(),
+                request_hash,
+                self.guardian.clone(),
+                committee_id,
+                block_finalized,
+            )
+            .await
+    }
+
+    async fn receive_request(&self, request: &ReceiveRequest) -> Result<(), AzeroContractError> {
+        self.contract
+            .receive_request(
+                &self.connection,
+                request.request_hash,
+                request.committee_id,
+                request.dest_token_address,
+                request.amount,
+                request.dest_receiver_address,
+                request.request_nonce,
+            )
+            .await
+    }
 }
 
 pub struct EthereumEventHandler;
@@ -56,11 +236,14 @@ impl EthereumEventHandler {
     pub async fn handle_event(
         event: MostEvents,
         config: &Config,
-        azero_connection: &ClientWithSigner<AzeroSigner>,
+        most: &dyn AzeroMost,
+        dead_letters: &dyn DeadLetterStore,
+        relayed: &dyn RelayedRequestStore,
+        dedup: &RequestDeduplicator,
+        submission_gate: &SubmissionGate,
+        metrics: &Metrics,
     ) -> Result<(), EthereumEventHandlerError> {
         let Config {
-            azero_contract_address,
-            azero_contract_metadata,
             blacklisted_requests,
             ..
         } = config;
@@ -77,23 +260,37 @@ impl EthereumEventHandler {
         ) = event
         {
             debug!("Handling eth contract event: {crosschain_transfer_event:?}");
+            metrics.record_event_seen("Ethereum");
+            let started = Instant::now();
 
-            // concat bytes
-            let bytes = concat_u8_arrays(vec![
-                &committee_id.as_u128().to_le_bytes(),
+            let committee_id = to_u128(committee_id, "committee_id")?;
+            let amount = to_u128(amount, "amount")?;
+            let request_nonce = to_u128(request_nonce, "request_nonce")?;
+
+            let request_hash = request_hash(
+                committee_id,
                 &dest_token_address,
-                &amount.as_u128().to_le_bytes(),
+                amount,
                 &dest_receiver_address,
-                &request_nonce.as_u128().to_le_bytes(),
-            ]);
-
-            trace!("Concatenated event bytes: {bytes:?}");
-
-            let request_hash = keccak256(bytes);
+                request_nonce,
+            );
             debug!("Hashed event data: {request_hash:?}");
+            record_request(request_nonce, &request_hash);
 
             let request_hash_hex = hex::encode(request_hash);
 
+            if !dedup.first_delivery("Ethereum", request_hash) {
+                info!("Skipping request 0x{request_hash_hex} - delivered again within the deduplication window");
+                return Ok(());
+            }
+
+            if relayed.is_request_relayed(request_nonce)? {
+                info!(
+                    "Skipping request 0x{request_hash_hex} - nonce {request_nonce} already relayed"
+                );
+                return Ok(());
+            }
+
             info!(
                 "Decoded event data: [request_hash: 0x{request_hash_hex}, dest_token_address: {}, amount: {amount}, dest_receiver_address: {}, request_nonce: {request_nonce}, committee_id: {committee_id}]",
                 AccountId::from(dest_token_address),
@@ -107,93 +304,155 @@ impl EthereumEventHandler {
                 }
             }
 
-            let contract = MostInstance::new(
-                azero_contract_address,
-                azero_contract_metadata,
-                config.azero_ref_time_limit,
-                config.azero_proof_size_limit,
-            )?;
+            if let Some(violation) =
+                config.forwarding_violation(&dest_token_address, amount, &dest_receiver_address)
+            {
+                error!("Request 0x{request_hash_hex} not forwarded: {violation}. Moving it to the dead letter queue for a manual review");
+                dead_letters.push(request_hash, &violation)?;
+                return Ok(());
+            }
+
+            // the contract would revert the signature anyway
+            if let Some(max_receive_amount) = most.max_receive_amount(dest_token_address).await? {
+                if amount > max_receive_amount {
+                    error!("Request 0x{request_hash_hex} not forwarded: amount {amount} above the contract maximum of {max_receive_amount}. Moving it to the dead letter queue for a manual review");
+                    dead_letters.push(
+                        request_hash,
+                        &format!("amount above the contract maximum of {max_receive_amount}"),
+                    )?;
+                    return Ok(());
+                }
+            }
 
-            let committee_id = committee_id.as_u128();
-            let amount = amount.as_u128();
-            let request_nonce = request_nonce.as_u128();
+            if most.request_status(request_hash).await? == RequestStatus::Processed {
+                info!("Guardian signature for 0x{request_hash_hex} not needed - request already processed");
+                return Ok(());
+            }
 
-            if not_in_committee(&contract, azero_connection, committee_id).await? {
+            if not_in_committee(most, committee_id).await? {
                 info!("Guardian signature for 0x{request_hash_hex} not needed - request from a different committee");
                 return Ok(());
             }
 
-            while contract
-                .needs_signature(
-                    azero_connection.client(),
-                    request_hash,
-                    azero_connection.account_id().clone(),
-                    committee_id,
-                    true,
-                )
+            let request = ReceiveRequest {
+                request_hash,
+                committee_id,
+                dest_token_address,
+                amount,
+                dest_receiver_address,
+                request_nonce,
+            };
+
+            let mut submitted = false;
+            let mut finality_wait = Duration::ZERO;
+            while most
+                .needs_signature(request_hash, committee_id, true)
                 .await?
             {
                 debug!("Azero: request 0x{request_hash_hex} not yet finalized.");
 
-                if !contract
-                    .needs_signature(
-                        azero_connection.client(),
-                        request_hash,
-                        azero_connection.account_id().clone(),
-                        committee_id,
-                        false,
-                    )
+                if !most
+                    .needs_signature(request_hash, committee_id, false)
                     .await?
                 {
                     sleep(AZERO_WAIT_FOR_FINALITY_CHECK).await;
+                    finality_wait += AZERO_WAIT_FOR_FINALITY_CHECK;
                     continue;
                 }
-                // send vote
-                contract
-                    .receive_request(
-                        azero_connection,
-                        request_hash,
-                        committee_id,
-                        dest_token_address,
-                        amount,
-                        dest_receiver_address,
-                        request_nonce,
+                submission_gate
+                    .wait_for_confirmation(
+                        &request_hash_hex,
+                        &format!(
+                            "receive_request(0x{request_hash_hex}, {committee_id}, 0x{}, {amount}, 0x{}, {request_nonce}) on AlephZero",
+                            hex::encode(dest_token_address),
+                            hex::encode(dest_receiver_address)
+                        ),
                     )
-                    .await
-                    // default AlephClient error is MBs large and useless, dumps the entire runtime for some reason
-                    .map_err(|_| EthereumEventHandlerError::ReceiveRequestTxFailure {
-                        request_hash: hex::encode(request_hash),
-                        committee_id,
-                        dest_token_address: hex::encode(dest_token_address),
-                        amount,
-                        dest_receiver_address: hex::encode(dest_receiver_address),
-                        request_nonce,
-                    })?;
+                    .await?;
+
+                // send vote
+                info!(
+                    "{}: submitting signature for request 0x{request_hash_hex}",
+                    metrics.guardian()
+                );
+                match most.receive_request(&request).await {
+                    Ok(()) => submitted = true,
+                    // checked again by `needs_signature`
+                    Err(AzeroContractError::ContractReverted(MostError::RequestAlreadySigned)) => {
+                        info!("Request 0x{request_hash_hex} already signed by the guardian");
+                    }
+                    Err(AzeroContractError::ContractReverted(reason)) if reason.is_permanent() => {
+                        error!("Request 0x{request_hash_hex} not forwarded: receive_request reverted with {reason:?}. Moving it to the dead letter queue for a manual review");
+                        dead_letters.push(
+                            request_hash,
+                            &format!("receive_request reverted with {reason:?}"),
+                        )?;
+                        return Ok(());
+                    }
+                    Err(why) => {
+                        if let AzeroContractError::ContractReverted(reason) = why {
+                            warn!("Request 0x{request_hash_hex}: receive_request reverted with {reason:?}");
+                        }
+                        // default AlephClient error is MBs large and useless, dumps the entire runtime for some reason
+                        return Err(EthereumEventHandlerError::ReceiveRequestTxFailure {
+                            request_hash: hex::encode(request_hash),
+                            committee_id,
+                            dest_token_address: hex::encode(dest_token_address),
+                            amount,
+                            dest_receiver_address: hex::encode(dest_receiver_address),
+                            request_nonce,
+                        });
+                    }
+                }
             }
             info!("Guardian signature for 0x{request_hash_hex} no longer needed");
+            if submitted {
+                metrics.record_request_relayed("Ethereum", started.elapsed(), finality_wait);
+            }
+            relayed.mark_request_relayed(request_nonce)?;
         }
 
         Ok(())
     }
 }
 
+/// Hash of the request the same as `hash_request_data` of the AlephZero contract.
+///
+/// `committee_id` is the one the request was emitted for, not the current one, as a request from a past committee
+/// is kept by the contract under a hash of its own committee id.
+fn request_hash(
+    committee_id: u128,
+    dest_token_address: &[u8; 32],
+    amount: u128,
+    dest_receiver_address: &[u8; 32],
+    request_nonce: u128,
+) -> [u8; 32] {
+    let bytes = concat_u8_arrays(vec![
+        &committee_id.to_le_bytes(),
+        dest_token_address,
+        &amount.to_le_bytes(),
+        dest_receiver_address,
+        &request_nonce.to_le_bytes(),
+    ]);
+    trace!("Concatenated event bytes: {bytes:?}");
+
+    keccak256(bytes)
+}
+
+/// AlephZero contract accepts at most u128 values, so wider ones are rejected instead of being truncated
+fn to_u128(value: U256, field: &'static str) -> Result<u128, EthereumEventHandlerError> {
+    u128::try_from(value).map_err(|_| EthereumEventHandlerError::Overflow { field, value })
+}
+
 async fn not_in_committee(
-    most: &MostInstance,
-    connection: &ClientWithSigner<AzeroSigner>,
+    most: &dyn AzeroMost,
     committee_id: u128,
 ) -> Result<bool, EthereumEventHandlerError> {
-    if most
-        .is_in_committee(
-            connection.client(),
-            committee_id,
-            connection.account_id().clone(),
-        )
-        .await?
-    {
+    if most.is_in_committee(committee_id).await? {
         return Ok(false);
     }
 
-    if committee_id > most.current_committee_id(connection.client()).await? {
+    if committee_id > most.current_committee_id().await? {
         error!("Request from a future committee {committee_id} - this likely indicates MOST contracts misconfiguration");
         return Err(EthereumEventHandlerError::CommitteeIdMismatch);
     }
@@ -220,7 +479,12 @@ impl EthereumEventsHandler {
     pub async fn run(
         config: Arc<Config>,
         mut eth_events_receiver: mpsc::Receiver<EthMostEvents>,
-        azero_signed_connection: Arc<ClientWithSigner<AzeroSigner>>,
+        most: Arc<dyn AzeroMost>,
+        dead_letters: Arc<dyn DeadLetterStore>,
+        relayed: Arc<dyn RelayedRequestStore>,
+        dedup: Arc<RequestDeduplicator>,
+        submission_gate: Arc<SubmissionGate>,
+        metrics: Arc<Metrics>,
         circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
         mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
     ) -> Result<CircuitBreakerEvent, EthereumEventsHandlerError> {
@@ -230,12 +494,17 @@ impl EthereumEventsHandler {
             debug!("Ping");
 
             select! {
-                cb_event = circuit_breaker_receiver.recv() => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!("Exiting due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
 
-                Some(eth_events) = eth_events_receiver.recv() => {
+                eth_events = eth_events_receiver.recv() => {
+                    // the listener stopped, e.g. for a shutdown, after all the batches it sent were acknowledged
+                    let Some(eth_events) = eth_events else {
+                        info!("No more events to handle, exiting");
+                        return Ok(CircuitBreakerEvent::Shutdown);
+                    };
                     let EthMostEvents {
                         events,
                         events_ack_sender,
@@ -247,14 +516,15 @@ impl EthereumEventsHandler {
 
                     for event in events {
                         select! {
-                            cb_event = circuit_breaker_receiver.recv () => {
+                            cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                                 warn!("Exiting due to a circuit breaker event {cb_event:?}");
                                 return Ok(cb_event?);
                             },
 
-                            result = EthereumEventHandler::handle_event(event, &config, &azero_signed_connection) => {
+                            result = EthereumEventHandler::handle_event(event, &config, most.as_ref(), dead_letters.as_ref(), relayed.as_ref(), &dedup, &submission_gate, &metrics).instrument(request_span("Ethereum")) => {
                                 if let Err(why) = result {
-                                    circuit_breaker_sender.send(CircuitBreakerEvent::EthEventHandlerFailure)?;
+                                    metrics.record_relay_failure("Ethereum");
+                                    open_circuit_breaker(&circuit_breaker_sender, CircuitBreakerEvent::EthEventHandlerFailure);
                                     warn!("Event handler failed {why:?}, exiting");
                                     return Ok (CircuitBreakerEvent::EthEventHandlerFailure);
                                 }
@@ -274,3 +544,441 @@ impl EthereumEventsHandler {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashSet, sync::Mutex};
+
+    use super::*;
+    use crate::{config::test_config, handlers::request_hash_vectors};
+
+    const TOKEN: [u8; 32] = [1; 32];
+    const RECEIVER: [u8; 32] = [2; 32];
+
+    fn transfer_request(
+        committee_id: u128,
+        dest_receiver_address: [u8; 32],
+        amount: u128,
+        request_nonce: u128,
+    ) -> MostEvents {
+        MostEvents::CrosschainTransferRequestFilter(CrosschainTransferRequestFilter {
+            committee_id: committee_id.into(),
+            dest_token_address: TOKEN,
+            amount: amount.into(),
+            dest_receiver_address,
+            request_nonce: request_nonce.into(),
+        })
+    }
+
+    /// Answers the contract reads with scripted values and records the submitted signatures.
+    ///
+    /// A request needs the signature until one is submitted, unless the guardian signed it before.
+    struct ScriptedMost {
+        status: RequestStatus,
+        member_of: u128,
+        current_committee_id: u128,
+        max_receive_amount: Option<u128>,
+        revert: Option<MostError>,
+        signed_before: bool,
+        submitted: Mutex<Vec<ReceiveRequest>>,
+    }
+
+    impl Default for ScriptedMost {
+        fn default() -> Self {
+            Self {
+                status: RequestStatus::Unknown,
+                member_of: 0,
+                current_committee_id: 0,
+                max_receive_amount: None,
+                revert: None,
+                signed_before: false,
+                submitted: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AzeroMost for ScriptedMost {
+        async fn request_status(
+            &self,
+            _request_hash: [u8; 32],
+        ) -> Result<RequestStatus, AzeroContractError> {
+            Ok(self.status)
+        }
+
+        async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+            Ok(false)
+        }
+
+        async fn is_in_committee(&self, committee_id: u128) -> Result<bool, AzeroContractError> {
+            Ok(committee_id == self.member_of)
+        }
+
+        async fn current_committee_id(&self) -> Result<u128, AzeroContractError> {
+            Ok(self.current_committee_id)
+        }
+
+        async fn max_receive_amount(
+            &self,
+            _dest_token_address: [u8; 32],
+        ) -> Result<Option<u128>, AzeroContractError> {
+            Ok(self.max_receive_amount)
+        }
+
+        async fn needs_signature(
+            &self,
+            _request_hash: [u8; 32],
+            _committee_id: u128,
+            _block_finalized: bool,
+        ) -> Result<bool, AzeroContractError> {
+            Ok(!self.signed_before && self.submitted.lock().unwrap().is_empty())
+        }
+
+        async fn receive_request(
+            &self,
+            request: &ReceiveRequest,
+        ) -> Result<(), AzeroContractError> {
+            if let Some(reason) = self.revert {
+                return Err(AzeroContractError::ContractReverted(reason));
+            }
+            self.submitted.lock().unwrap().push(request.clone());
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryDeadLetterStore(Mutex<Vec<([u8; 32], String)>>);
+
+    impl DeadLetterStore for InMemoryDeadLetterStore {
+        fn push(&self, request_hash: [u8; 32], reason: &str) -> Result<(), redis::RedisError> {
+            self.0
+                .lock()
+                .unwrap()
+                .push((request_hash, reason.to_owned()));
+            Ok(())
+        }
+    }
+
+    #[derive(Default)]
+    struct InMemoryRelayedRequestStore(Mutex<HashSet<u128>>);
+
+    impl RelayedRequestStore for InMemoryRelayedRequestStore {
+        fn mark_request_relayed(&self, request_nonce: u128) -> Result<(), redis::RedisError> {
+            self.0.lock().unwrap().insert(request_nonce);
+            Ok(())
+        }
+
+        fn is_request_relayed(&self, request_nonce: u128) -> Result<bool, redis::RedisError> {
+            Ok(self.0.lock().unwrap().contains(&request_nonce))
+        }
+    }
+
+    async fn handle(
+        event: MostEvents,
+        most: &ScriptedMost,
+        dead_letters: &InMemoryDeadLetterStore,
+    ) -> Result<(), EthereumEventHandlerError> {
+        handle_relayed(
+            event,
+            most,
+            dead_letters,
+            &InMemoryRelayedRequestStore::default(),
+        )
+        .await
+    }
+
+    async fn handle_relayed(
+        event: MostEvents,
+        most: &ScriptedMost,
+        dead_letters: &InMemoryDeadLetterStore,
+        relayed: &InMemoryRelayedRequestStore,
+    ) -> Result<(), EthereumEventHandlerError> {
+        let metrics = Arc::new(Metrics::default());
+
+        EthereumEventHandler::handle_event(
+            event,
+            &test_config(&[]),
+            most,
+            dead_letters,
+            relayed,
+            &RequestDeduplicator::new(Duration::ZERO, Arc::clone(&metrics)),
+            &SubmissionGate::new(0),
+            &metrics,
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn transfer_request_is_signed_with_the_event_fields() {
+        let most = ScriptedMost::default();
+
+        handle(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most,
+            &InMemoryDeadLetterStore::default(),
+        )
+        .await
+        .unwrap();
+
+        let request_hash = keccak256(concat_u8_arrays(vec![
+            &0u128.to_le_bytes(),
+            &TOKEN,
+            &100u128.to_le_bytes(),
+            &RECEIVER,
+            &7u128.to_le_bytes(),
+        ]));
+        assert_eq!(
+            *most.submitted.lock().unwrap(),
+            [ReceiveRequest {
+                request_hash,
+                committee_id: 0,
+                dest_token_address: TOKEN,
+                amount: 100,
+                dest_receiver_address: RECEIVER,
+                request_nonce: 7,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn request_replayed_after_a_restart_is_skipped() {
+        let relayed = InMemoryRelayedRequestStore::default();
+        let dead_letters = InMemoryDeadLetterStore::default();
+        let most = ScriptedMost::default();
+        handle_relayed(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most,
+            &dead_letters,
+            &relayed,
+        )
+        .await
+        .unwrap();
+        assert_eq!(most.submitted.lock().unwrap().len(), 1);
+
+        // the chain state is read anew after the restart, the relayed nonce is what stops the second submission
+        let most_after_restart = ScriptedMost::default();
+        handle_relayed(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most_after_restart,
+            &dead_letters,
+            &relayed,
+        )
+        .await
+        .unwrap();
+
+        assert!(most_after_restart.submitted.lock().unwrap().is_empty());
+        assert_eq!(*relayed.0.lock().unwrap(), HashSet::from([7]));
+    }
+
+    #[tokio::test]
+    async fn processed_request_is_not_signed() {
+        let most = ScriptedMost {
+            status: RequestStatus::Processed,
+            ..Default::default()
+        };
+
+        handle(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most,
+            &InMemoryDeadLetterStore::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(most.submitted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_signed_by_the_guardian_before_is_not_signed_again() {
+        let most = ScriptedMost {
+            status: RequestStatus::Pending {
+                collected_signatures: 1,
+            },
+            signed_before: true,
+            ..Default::default()
+        };
+
+        handle(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most,
+            &InMemoryDeadLetterStore::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(most.submitted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_from_a_past_committee_is_left_to_its_members() {
+        let most = ScriptedMost {
+            member_of: 1,
+            current_committee_id: 1,
+            ..Default::default()
+        };
+
+        handle(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most,
+            &InMemoryDeadLetterStore::default(),
+        )
+        .await
+        .unwrap();
+
+        assert!(most.submitted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_from_a_future_committee_is_an_error() {
+        let most = ScriptedMost::default();
+
+        let result = handle(
+            transfer_request(1, RECEIVER, 100, 7),
+            &most,
+            &InMemoryDeadLetterStore::default(),
+        )
+        .await;
+
+        assert!(matches!(
+            result,
+            Err(EthereumEventHandlerError::CommitteeIdMismatch)
+        ));
+        assert!(most.submitted.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn request_to_the_zero_receiver_is_dead_lettered() {
+        let most = ScriptedMost::default();
+        let dead_letters = InMemoryDeadLetterStore::default();
+
+        handle(transfer_request(0, [0; 32], 100, 7), &most, &dead_letters)
+            .await
+            .unwrap();
+
+        assert!(most.submitted.lock().unwrap().is_empty());
+        assert_eq!(dead_letters.0.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn request_above_the_contract_maximum_is_dead_lettered() {
+        let most = ScriptedMost {
+            max_receive_amount: Some(99),
+            ..Default::default()
+        };
+        let dead_letters = InMemoryDeadLetterStore::default();
+
+        handle(transfer_request(0, RECEIVER, 100, 7), &most, &dead_letters)
+            .await
+            .unwrap();
+
+        assert!(most.submitted.lock().unwrap().is_empty());
+        assert_eq!(dead_letters.0.lock().unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn permanently_reverted_request_is_dead_lettered() {
+        let most = ScriptedMost {
+            revert: Some(MostError::UnsupportedPair),
+            ..Default::default()
+        };
+        let dead_letters = InMemoryDeadLetterStore::default();
+
+        handle(transfer_request(0, RECEIVER, 100, 7), &most, &dead_letters)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            dead_letters.0.lock().unwrap()[0].1,
+            "receive_request reverted with UnsupportedPair"
+        );
+    }
+
+    #[tokio::test]
+    async fn transiently_reverted_request_fails() {
+        let most = ScriptedMost {
+            revert: Some(MostError::IsHalted),
+            ..Default::default()
+        };
+        let dead_letters = InMemoryDeadLetterStore::default();
+
+        let result = handle(transfer_request(0, RECEIVER, 100, 7), &most, &dead_letters).await;
+
+        assert!(matches!(
+            result,
+            Err(EthereumEventHandlerError::ReceiveRequestTxFailure { .. })
+        ));
+        assert!(dead_letters.0.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn request_hash_matches_the_golden_vectors() {
+        for vector in request_hash_vectors("eth_to_azero") {
+            assert_eq!(
+                request_hash(
+                    vector.committee_id,
+                    &vector.dest_token_address,
+                    vector.amount,
+                    &vector.dest_receiver_address,
+                    vector.request_nonce,
+                ),
+                vector.request_hash,
+                "{vector:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn u128_values_are_converted() {
+        assert_eq!(to_u128(U256::zero(), "amount").unwrap(), 0);
+        assert_eq!(to_u128(U256::from(u128::MAX), "amount").unwrap(), u128::MAX);
+    }
+
+    #[test]
+    fn values_wider_than_u128_are_rejected() {
+        let value = U256::from(u128::MAX) + 1;
+
+        assert!(matches!(
+            to_u128(value, "amount"),
+            Err(EthereumEventHandlerError::Overflow { field: "amount", value: v }) if v == value
+        ));
+        assert!(to_u128(U256::MAX, "amount").is_err());
+    }
+
+    #[test]
+    fn request_hash_matches_the_contract() {
+        // `hash_request_data(committee_id, [1; 32], 100, [2; 32], 7)` of the AlephZero contract
+        assert_eq!(
+            hex::encode(request_hash(0, &TOKEN, 100, &RECEIVER, 7)),
+            "665999df151b35f7983bb00fe912765850bed4d484ff8850cb820f7e2b21f5fc"
+        );
+        assert_eq!(
+            hex::encode(request_hash(3, &TOKEN, 100, &RECEIVER, 7)),
+            "23af4bf5761735b4e24009544f6f0e380dca5b90b79a5cd788d51fc6d2da96e1"
+        );
+    }
+
+    #[tokio::test]
+    async fn request_from_an_older_committee_is_hashed_with_its_committee_id() {
+        let most = ScriptedMost {
+            member_of: 0,
+            current_committee_id: 3,
+            ..Default::default()
+        };
+
+        handle(
+            transfer_request(0, RECEIVER, 100, 7),
+            &most,
+            &InMemoryDeadLetterStore::default(),
+        )
+        .await
+        .unwrap();
+
+        let submitted = most.submitted.lock().unwrap();
+        assert_eq!(submitted.len(), 1);
+        assert_eq!(submitted[0].committee_id, 0);
+        assert_eq!(
+            submitted[0].request_hash,
+            request_hash(0, &TOKEN, 100, &RECEIVER, 7)
+        );
+    }
+}
@@ -1,5 +1,75 @@
+use std::fmt::Display;
+
+use tracing::{field, info_span, Span};
+
 mod azero;
 mod eth;
+mod shadow;
 
 pub use azero::*;
 pub use eth::*;
+pub use shadow::*;
+
+/// Span of handling a single request sent from `source_chain`, so that the log lines of one transfer can be told apart
+/// from the others, from decoding the event to the finality of the signature.
+///
+/// The nonce and the hash are filled in by [`record_request`] once the event is decoded.
+pub fn request_span(source_chain: &'static str) -> Span {
+    info_span!(
+        "request",
+        source_chain,
+        request_nonce = field::Empty,
+        request_hash = field::Empty
+    )
+}
+
+/// Fills in the request the current [`request_span`] is about
+fn record_request(request_nonce: impl Display, request_hash: &[u8; 32]) {
+    let span = Span::current();
+    span.record("request_nonce", field::display(request_nonce));
+    span.record(
+        "request_hash",
+        field::display(format_args!("0x{}", hex::encode(request_hash))),
+    );
+}
+
+/// Request with its hash, shared with the tests of the contracts so that both sides hash the requests the same
+#[cfg(test)]
+#[derive(Debug)]
+pub(crate) struct RequestHashVector {
+    pub committee_id: u128,
+    pub dest_token_address: [u8; 32],
+    pub amount: u128,
+    pub dest_receiver_address: [u8; 32],
+    pub request_nonce: u128,
+    pub request_hash: [u8; 32],
+}
+
+/// Golden vectors of the requests sent in the given `direction`
+#[cfg(test)]
+pub(crate) fn request_hash_vectors(direction: &str) -> Vec<RequestHashVector> {
+    let bytes32 = |hex: &str| -> [u8; 32] {
+        hex::decode(hex.trim_start_matches("0x"))
+            .unwrap()
+            .try_into()
+            .unwrap()
+    };
+
+    let vectors: Vec<_> = include_str!("../../../../cfg/request_hash_vectors.txt")
+        .lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| line.split_whitespace().collect::<Vec<_>>())
+        .filter(|fields| fields[0] == direction)
+        .map(|fields| RequestHashVector {
+            committee_id: fields[1].parse().unwrap(),
+            dest_token_address: bytes32(fields[2]),
+            amount: fields[3].parse().unwrap(),
+            dest_receiver_address: bytes32(fields[4]),
+            request_nonce: fields[5].parse().unwrap(),
+            request_hash: bytes32(fields[6]),
+        })
+        .collect();
+    assert!(!vectors.is_empty(), "no golden vectors for {direction}");
+
+    vectors
+}
@@ -0,0 +1,244 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use contracts_azero_client::ContractEvent;
+use log::{error, info};
+use tokio::time::{sleep, Duration, Instant};
+
+use crate::{
+    contracts::{AzeroContractError, RequestStatus},
+    handlers::{AlephZeroEventHandlerError, AzeroEventHandler, AzeroMost, ReceiveRequest},
+};
+
+const LOG_TARGET: &str = "Shadow";
+// How often the production guardian's signature is looked up during the grace period
+const SIGNATURE_CHECK_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// The AlephZero Most contract as seen by a production guardian, which never submits anything.
+///
+/// Instead of submitting a signature, waits for the production guardian to submit one for the same request hash.
+/// As the hash covers all the arguments of `receive_request`, a signature missing after `grace_period` means the
+/// production relayer decided differently, which is reported as a divergence.
+pub struct ShadowMost {
+    production: Arc<dyn AzeroMost>,
+    grace_period: Duration,
+    diverged: Mutex<HashSet<[u8; 32]>>,
+}
+
+impl ShadowMost {
+    /// `production` should read the contract on behalf of the production guardian's account
+    pub fn new(production: Arc<dyn AzeroMost>, grace_period: Duration) -> Self {
+        Self {
+            production,
+            grace_period,
+            diverged: Mutex::new(HashSet::new()),
+        }
+    }
+
+    async fn signed_by_production(
+        &self,
+        request: &ReceiveRequest,
+    ) -> Result<bool, AzeroContractError> {
+        let deadline = Instant::now() + self.grace_period;
+
+        loop {
+            if !self
+                .production
+                .needs_signature(request.request_hash, request.committee_id, false)
+                .await?
+            {
+                return Ok(true);
+            }
+            if Instant::now() >= deadline {
+                return Ok(false);
+            }
+            sleep(SIGNATURE_CHECK_INTERVAL).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl AzeroMost for ShadowMost {
+    async fn request_status(
+        &self,
+        request_hash: [u8; 32],
+    ) -> Result<RequestStatus, AzeroContractError> {
+        self.production.request_status(request_hash).await
+    }
+
+    async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+        self.production.is_halted().await
+    }
+
+    async fn is_in_committee(&self, committee_id: u128) -> Result<bool, AzeroContractError> {
+        self.production.is_in_committee(committee_id).await
+    }
+
+    async fn current_committee_id(&self) -> Result<u128, AzeroContractError> {
+        self.production.current_committee_id().await
+    }
+
+    async fn max_receive_amount(
+        &self,
+        dest_token_address: [u8; 32],
+    ) -> Result<Option<u128>, AzeroContractError> {
+        self.production.max_receive_amount(dest_token_address).await
+    }
+
+    /// A diverged request is never going to be signed, so it is not waited for
+    async fn needs_signature(
+        &self,
+        request_hash: [u8; 32],
+        committee_id: u128,
+        block_finalized: bool,
+    ) -> Result<bool, AzeroContractError> {
+        if self
+            .diverged
+            .lock()
+            .expect("mutex lock")
+            .contains(&request_hash)
+        {
+            return Ok(false);
+        }
+
+        self.production
+            .needs_signature(request_hash, committee_id, block_finalized)
+            .await
+    }
+
+    async fn receive_request(&self, request: &ReceiveRequest) -> Result<(), AzeroContractError> {
+        let request_hash_hex = hex::encode(request.request_hash);
+
+        if self.signed_by_production(request).await? {
+            info!(target: LOG_TARGET, "Request 0x{request_hash_hex} signed by the production guardian as computed");
+            return Ok(());
+        }
+
+        error!(
+            target: LOG_TARGET,
+            "Divergence: the production guardian did not sign receive_request(0x{request_hash_hex}, {}, 0x{}, {}, 0x{}, {}) on AlephZero within {:?}",
+            request.committee_id,
+            hex::encode(request.dest_token_address),
+            request.amount,
+            hex::encode(request.dest_receiver_address),
+            request.request_nonce,
+            self.grace_period
+        );
+        self.diverged
+            .lock()
+            .expect("mutex lock")
+            .insert(request.request_hash);
+
+        Ok(())
+    }
+}
+
+/// Leaves the AlephZero transfer requests to the production relayer, without forwarding them to Ethereum
+pub struct ShadowAzeroEventHandler;
+
+#[async_trait::async_trait]
+impl AzeroEventHandler for ShadowAzeroEventHandler {
+    async fn handle(&self, event: ContractEvent) -> Result<(), AlephZeroEventHandlerError> {
+        info!(
+            target: LOG_TARGET,
+            "Not forwarding AlephZero event {:?} to Ethereum", event.name
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GRACE_PERIOD: Duration = Duration::from_secs(60);
+
+    /// Production guardian which signed the requests with the given hashes
+    struct ProductionMost(Mutex<HashSet<[u8; 32]>>);
+
+    impl ProductionMost {
+        fn signed(request_hashes: &[[u8; 32]]) -> Arc<Self> {
+            Arc::new(Self(Mutex::new(request_hashes.iter().copied().collect())))
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl AzeroMost for ProductionMost {
+        async fn request_status(
+            &self,
+            _request_hash: [u8; 32],
+        ) -> Result<RequestStatus, AzeroContractError> {
+            Ok(RequestStatus::Unknown)
+        }
+
+        async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+            Ok(false)
+        }
+
+        async fn is_in_committee(&self, _committee_id: u128) -> Result<bool, AzeroContractError> {
+            Ok(true)
+        }
+
+        async fn current_committee_id(&self) -> Result<u128, AzeroContractError> {
+            Ok(0)
+        }
+
+        async fn max_receive_amount(
+            &self,
+            _dest_token_address: [u8; 32],
+        ) -> Result<Option<u128>, AzeroContractError> {
+            Ok(None)
+        }
+
+        async fn needs_signature(
+            &self,
+            request_hash: [u8; 32],
+            _committee_id: u128,
+            _block_finalized: bool,
+        ) -> Result<bool, AzeroContractError> {
+            Ok(!self.0.lock().unwrap().contains(&request_hash))
+        }
+
+        async fn receive_request(
+            &self,
+            _request: &ReceiveRequest,
+        ) -> Result<(), AzeroContractError> {
+            panic!("nothing is submitted in the shadow mode");
+        }
+    }
+
+    fn request(request_hash: [u8; 32]) -> ReceiveRequest {
+        ReceiveRequest {
+            request_hash,
+            committee_id: 0,
+            dest_token_address: [1; 32],
+            amount: 100,
+            dest_receiver_address: [2; 32],
+            request_nonce: 7,
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_signed_by_production_matches() {
+        let shadow = ShadowMost::new(ProductionMost::signed(&[[3; 32]]), GRACE_PERIOD);
+
+        shadow.receive_request(&request([3; 32])).await.unwrap();
+
+        assert!(shadow.diverged.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn request_signed_differently_by_production_is_a_divergence() {
+        // production computed a different hash for the same event
+        let shadow = ShadowMost::new(ProductionMost::signed(&[[4; 32]]), GRACE_PERIOD);
+        let started = Instant::now();
+
+        shadow.receive_request(&request([3; 32])).await.unwrap();
+
+        assert!(started.elapsed() >= GRACE_PERIOD);
+        assert_eq!(*shadow.diverged.lock().unwrap(), HashSet::from([[3; 32]]));
+        assert!(!shadow.needs_signature([3; 32], 0, true).await.unwrap());
+    }
+}
@@ -1,3 +1,23 @@
+use std::{
+    collections::{hash_map::RandomState, HashMap},
+    fmt::Debug,
+    future::{pending, Future},
+    hash::{BuildHasher, Hash, Hasher},
+    sync::Mutex,
+};
+
+use log::{debug, warn};
+use tokio::{
+    select,
+    sync::{
+        broadcast::{self, error::RecvError},
+        watch,
+    },
+    time::{sleep, Duration, Instant},
+};
+
+use crate::CircuitBreakerEvent;
+
 pub fn concat_u8_arrays(arrays: Vec<&[u8]>) -> Vec<u8> {
     let mut result = Vec::new();
     for array in arrays {
@@ -5,3 +25,420 @@ pub fn concat_u8_arrays(arrays: Vec<&[u8]>) -> Vec<u8> {
     }
     result
 }
+
+/// Delay before querying a node again after a failed query.
+///
+/// Starts at `base` and doubles with every failure in a row up to `max`, with a random jitter of up to a half of the
+/// delay on top, so that the relayers do not query a node recovering from an outage in lockstep.
+#[derive(Debug, Clone, Copy)]
+pub struct PollBackoff {
+    base: Duration,
+    max: Duration,
+    max_retries: u32,
+    failures: u32,
+}
+
+impl PollBackoff {
+    pub fn new(base: Duration, max: Duration, max_retries: u32) -> Self {
+        Self {
+            base,
+            max,
+            max_retries,
+            failures: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.failures = 0;
+    }
+
+    /// Whether an error should be retried rather than returned, `max_retries` failures in a row being the limit
+    pub fn retries_left(&self) -> bool {
+        self.failures < self.max_retries
+    }
+
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self
+            .base
+            .saturating_mul(2u32.saturating_pow(self.failures))
+            .min(self.max);
+        self.failures = self.failures.saturating_add(1);
+
+        delay + jitter(delay / 2)
+    }
+}
+
+/// Random duration of up to `max`, good enough to spread the queries of the relayers apart
+fn jitter(max: Duration) -> Duration {
+    let random = RandomState::new().build_hasher().finish();
+    max.mul_f64(random as f64 / u64::MAX as f64)
+}
+
+/// Calls `fetch` once every `interval` and publishes the finalized head whenever it moves forward.
+///
+/// A head lower than the already published one is never propagated to the subscribers, it is only reported.
+/// `fetch` may return `Ok(None)` if the head is temporarily unavailable. After an unavailable head or an error
+/// the next call is delayed by the `backoff` instead, and the error is returned once there are no retries left.
+pub async fn poll_finalized_head<F, Fut, E>(
+    mut fetch: F,
+    interval: Duration,
+    mut backoff: PollBackoff,
+    finalized_head_sender: watch::Sender<u32>,
+) -> E
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Option<u32>, E>>,
+    E: std::fmt::Debug,
+{
+    loop {
+        let delay = match fetch().await {
+            Ok(Some(finalized_head)) => {
+                finalized_head_sender.send_if_modified(|current| {
+                    if finalized_head > *current {
+                        debug!("New finalized head {finalized_head}");
+                        *current = finalized_head;
+                        true
+                    } else {
+                        if finalized_head < *current {
+                            warn!(
+                                "Finalized head went backwards from {current} to {finalized_head}"
+                            );
+                        }
+                        false
+                    }
+                });
+                backoff.reset();
+                interval
+            }
+            Ok(None) => backoff.next_delay(),
+            Err(why) if backoff.retries_left() => {
+                let delay = backoff.next_delay();
+                warn!("Failed to get the finalized head: {why:?}, retrying in {delay:?}");
+                delay
+            }
+            Err(why) => return why,
+        };
+
+        sleep(delay).await;
+    }
+}
+
+/// Broadcasts `event` to the other components.
+///
+/// There being no one left to receive it is not an error: the circuit breaker is already open and the
+/// other components have exited, which is where the event would lead them anyway.
+pub fn open_circuit_breaker(
+    circuit_breaker_sender: &broadcast::Sender<CircuitBreakerEvent>,
+    event: CircuitBreakerEvent,
+) {
+    if let Err(broadcast::error::SendError(event)) = circuit_breaker_sender.send(event) {
+        debug!("Circuit breaker already open, not broadcasting {event:?}");
+    }
+}
+
+/// Waits for the next circuit breaker event.
+///
+/// Events which were sent in a burst, e.g. by several components tripping the circuit breaker at once,
+/// collapse into the most recent one instead of failing the receiver which fell behind.
+pub async fn next_circuit_breaker_event(
+    circuit_breaker_receiver: &mut broadcast::Receiver<CircuitBreakerEvent>,
+) -> Result<CircuitBreakerEvent, RecvError> {
+    loop {
+        match circuit_breaker_receiver.recv().await {
+            Err(RecvError::Lagged(skipped)) => {
+                debug!("Skipped {skipped} circuit breaker events sent in a burst")
+            }
+            event => return event,
+        }
+    }
+}
+
+/// Resolves once a graceful shutdown of the relayer is requested, never if `shutdown` can no longer be requested
+pub async fn shutdown_requested(shutdown: &mut watch::Receiver<bool>) {
+    if shutdown.wait_for(|requested| *requested).await.is_err() {
+        pending::<()>().await;
+    }
+}
+
+/// Calls `check` once every `interval` until it reports a circuit breaker event, which is then broadcast to the other components.
+///
+/// Returns early with the received event if another component trips the circuit breaker first.
+pub async fn poll_circuit_breaker<F, Fut, E>(
+    mut check: F,
+    interval: Duration,
+    circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
+    mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+) -> Result<CircuitBreakerEvent, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<CircuitBreakerEvent>>,
+    E: From<RecvError>,
+{
+    loop {
+        debug!("Ping");
+
+        select! {
+            cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
+                warn!("Exiting due to a circuit breaker event {cb_event:?}");
+                return Ok(cb_event?);
+            },
+
+            status = check() => {
+                if let Some(status) = status {
+                    open_circuit_breaker(&circuit_breaker_sender, status.clone());
+                    return Ok(status);
+                }
+            }
+        }
+
+        sleep(interval).await;
+    }
+}
+
+/// Last values read successfully, served in place of a failed read for up to `ttl` after they were read.
+///
+/// Every read goes to the node, so a change is seen as soon as a read succeeds. A zero `ttl` never serves a stale value.
+pub struct LastKnownGood<K, V> {
+    ttl: Duration,
+    values: Mutex<HashMap<K, (Instant, V)>>,
+}
+
+impl<K: Eq + Hash, V: Clone> LastKnownGood<K, V> {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            values: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Result of `read`, or the last value read for `key` within the ttl if it fails
+    pub async fn read<E, Fut>(&self, key: K, read: Fut) -> Result<V, E>
+    where
+        E: Debug,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        match read.await {
+            Ok(value) => {
+                self.values
+                    .lock()
+                    .expect("mutex lock")
+                    .insert(key, (Instant::now(), value.clone()));
+                Ok(value)
+            }
+            Err(why) => {
+                let values = self.values.lock().expect("mutex lock");
+                match values.get(&key) {
+                    Some((read_at, value)) if read_at.elapsed() < self.ttl => {
+                        warn!(
+                            "Serving the value read {:?} ago after a failed read: {why:?}",
+                            read_at.elapsed()
+                        );
+                        Ok(value.clone())
+                    }
+                    _ => Err(why),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        sync::{
+            atomic::{AtomicU32, Ordering},
+            Arc,
+        },
+    };
+
+    use super::*;
+    use crate::listeners::AdvisoryListenerError;
+
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    #[tokio::test(start_paused = true)]
+    async fn polls_once_per_interval_regardless_of_subscriber_count() {
+        let polls = Arc::new(AtomicU32::new(0));
+        let (finalized_head_sender, finalized_head_receiver) = watch::channel(0);
+
+        let poller_polls = Arc::clone(&polls);
+        tokio::spawn(poll_finalized_head(
+            move || {
+                let polls = Arc::clone(&poller_polls);
+                async move { Ok::<_, Infallible>(Some(polls.fetch_add(1, Ordering::SeqCst) + 1)) }
+            },
+            INTERVAL,
+            PollBackoff::new(INTERVAL, INTERVAL, 0),
+            finalized_head_sender,
+        ));
+
+        let subscribers = (0..100)
+            .map(|_| {
+                let mut receiver = finalized_head_receiver.clone();
+                tokio::spawn(async move { *receiver.wait_for(|head| *head >= 3).await.unwrap() })
+            })
+            .collect::<Vec<_>>();
+
+        sleep(INTERVAL * 5 / 2).await;
+
+        assert_eq!(polls.load(Ordering::SeqCst), 3);
+        for subscriber in subscribers {
+            assert_eq!(subscriber.await.unwrap(), 3);
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn does_not_publish_regressed_head_and_returns_error() {
+        let mut heads = vec![Err("connection lost"), Ok(None), Ok(Some(4)), Ok(Some(7))];
+        let (finalized_head_sender, mut finalized_head_receiver) = watch::channel(0);
+
+        let poller = tokio::spawn(poll_finalized_head(
+            move || std::future::ready(heads.pop().unwrap()),
+            INTERVAL,
+            PollBackoff::new(INTERVAL, INTERVAL, 0),
+            finalized_head_sender,
+        ));
+
+        finalized_head_receiver.changed().await.unwrap();
+        assert_eq!(*finalized_head_receiver.borrow_and_update(), 7);
+
+        assert_eq!(poller.await.unwrap(), "connection lost");
+        assert!(!finalized_head_receiver.has_changed().unwrap_or(false));
+        assert_eq!(*finalized_head_receiver.borrow(), 7);
+    }
+
+    #[test]
+    fn poll_backoff_doubles_up_to_the_maximum_with_jitter() {
+        let mut backoff = PollBackoff::new(INTERVAL, INTERVAL * 4, 0);
+
+        for expected in [1, 2, 4, 4].map(|delay| INTERVAL * delay) {
+            let delay = backoff.next_delay();
+            assert!(delay >= expected && delay <= expected * 3 / 2, "{delay:?}");
+        }
+
+        backoff.reset();
+        assert!(backoff.next_delay() <= INTERVAL * 3 / 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn errors_are_retried_until_there_are_no_retries_left() {
+        let mut heads = vec![
+            Err("node down"),
+            Err("node down"),
+            Err("node down"),
+            Ok(Some(5)),
+            Err("node restarting"),
+            Err("node restarting"),
+        ];
+        let (finalized_head_sender, finalized_head_receiver) = watch::channel(0);
+
+        let poller = tokio::spawn(poll_finalized_head(
+            move || std::future::ready(heads.pop().unwrap()),
+            INTERVAL,
+            PollBackoff::new(INTERVAL, INTERVAL * 4, 2),
+            finalized_head_sender,
+        ));
+
+        assert_eq!(poller.await.unwrap(), "node down");
+        assert_eq!(*finalized_head_receiver.borrow(), 5);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn rapid_duplicate_emergencies_open_the_circuit_breaker_once() {
+        let (circuit_breaker_sender, mut consumer_receiver) = broadcast::channel(1);
+        let listener_receiver = circuit_breaker_sender.subscribe();
+
+        // the same emergency reported on every poll before anyone reacts
+        for _ in 0..5 {
+            open_circuit_breaker(
+                &circuit_breaker_sender,
+                CircuitBreakerEvent::AdvisoryEmergency(vec![]),
+            );
+        }
+
+        assert!(matches!(
+            next_circuit_breaker_event(&mut consumer_receiver).await,
+            Ok(CircuitBreakerEvent::AdvisoryEmergency(_))
+        ));
+        assert!(consumer_receiver.try_recv().is_err());
+
+        // a listener which fell behind the burst exits with the event rather than an error
+        let exit = poll_circuit_breaker::<_, _, AdvisoryListenerError>(
+            || std::future::ready(None),
+            INTERVAL,
+            circuit_breaker_sender.clone(),
+            listener_receiver,
+        )
+        .await;
+        assert!(matches!(
+            exit,
+            Ok(CircuitBreakerEvent::AdvisoryEmergency(_))
+        ));
+
+        // everyone has already exited
+        drop(consumer_receiver);
+        open_circuit_breaker(
+            &circuit_breaker_sender,
+            CircuitBreakerEvent::AdvisoryEmergency(vec![]),
+        );
+    }
+
+    #[tokio::test]
+    async fn every_subscriber_receives_the_emergency() {
+        let (circuit_breaker_sender, mut handler_receiver) = broadcast::channel(1);
+        let mut halted_listener_receiver = circuit_breaker_sender.subscribe();
+
+        open_circuit_breaker(
+            &circuit_breaker_sender,
+            CircuitBreakerEvent::AdvisoryEmergency(vec![]),
+        );
+
+        for receiver in [&mut handler_receiver, &mut halted_listener_receiver] {
+            assert!(matches!(
+                next_circuit_breaker_event(receiver).await,
+                Ok(CircuitBreakerEvent::AdvisoryEmergency(_))
+            ));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn failed_read_is_served_from_the_last_known_good_value_within_ttl() {
+        let halted = LastKnownGood::new(Duration::from_secs(10));
+
+        assert_eq!(
+            halted.read((), async { Ok::<_, &str>(false) }).await,
+            Ok(false)
+        );
+
+        sleep(Duration::from_secs(9)).await;
+        assert_eq!(
+            halted.read((), async { Err("connection reset") }).await,
+            Ok(false)
+        );
+
+        sleep(Duration::from_secs(1)).await;
+        assert_eq!(
+            halted.read((), async { Err("connection reset") }).await,
+            Err("connection reset")
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn successful_read_is_never_replaced_by_the_cached_value() {
+        let committee_ids = LastKnownGood::new(Duration::from_secs(10));
+
+        assert_eq!(
+            committee_ids.read((), async { Ok::<_, &str>(1) }).await,
+            Ok(1)
+        );
+        assert_eq!(
+            committee_ids.read((), async { Ok::<_, &str>(2) }).await,
+            Ok(2)
+        );
+        assert_eq!(
+            committee_ids.read((), async { Err("timeout") }).await,
+            Ok(2)
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! Relays the transfer requests between AlephZero and Ethereum.
+//!
+//! The `relayer` binary runs the listeners and handlers of this library through a [`supervisor::Relayer`], which can be
+//! embedded in another process as well.
+
+use contracts_azero_client::AccountId;
+
+pub mod admin;
+pub mod audit;
+pub mod check_guardian;
+pub mod commands;
+pub mod config;
+pub mod connections;
+pub mod contracts;
+pub mod dedup;
+pub mod fees;
+pub mod handlers;
+pub mod helpers;
+pub mod listeners;
+pub mod metadata;
+pub mod metrics;
+pub mod offline_signing;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod redis;
+pub mod supervisor;
+pub mod watchdog;
+
+/// Stops the relayer components, which are then restarted after a backoff
+#[derive(Debug, Clone)]
+pub enum CircuitBreakerEvent {
+    EthEventHandlerFailure,
+    AlephZeroEventHandlerFailure,
+    BridgeHaltAlephZero,
+    BridgeHaltEthereum,
+    AdvisoryEmergency(Vec<AccountId>), // the advisories in emergency, for the logs
+    AlephClientError,                  // signifies a connection error
+    EthConnectionError,
+    CommitteeMembershipLost,
+    Shutdown, // not broadcast, reported by the components which stopped after a graceful shutdown was requested
+}
@@ -1,16 +1,25 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+};
 
 use contracts_azero_client::AccountId;
-use futures::future::join_all;
+use futures::{
+    future::{join_all, BoxFuture},
+    FutureExt, TryFutureExt,
+};
 use log::{debug, info, warn};
 use thiserror::Error;
-use tokio::{select, sync::broadcast, time::sleep};
+use tokio::{
+    sync::broadcast,
+    time::{sleep, timeout, Duration},
+};
 
-use super::ALEPH_BLOCK_PROD_TIME_SEC;
 use crate::{
     config::Config,
     connections::azero::AzeroWsConnection,
     contracts::{AdvisoryInstance, AzeroContractError},
+    helpers::poll_circuit_breaker,
     CircuitBreakerEvent,
 };
 
@@ -26,75 +35,148 @@ pub enum AdvisoryListenerError {
 
     #[error("broadcast receive error")]
     BroadcastReceive(#[from] broadcast::error::RecvError),
+
+    #[error("advisory query timed out after {0:?}")]
+    Timeout(Duration),
+}
+
+/// Advisories in emergency as of the last check, kept across the restarts of the relayer to log when an emergency
+/// starts and when it is lifted
+#[derive(Debug, Default)]
+pub struct AdvisoryEmergencies(Mutex<Vec<AccountId>>);
+
+impl AdvisoryEmergencies {
+    /// Records the advisories in emergency, returns the ones which entered it and the ones which left it since the last check
+    pub fn update(&self, active: &[AccountId]) -> (Vec<AccountId>, Vec<AccountId>) {
+        let mut last_active = self.0.lock().expect("mutex lock");
+        let detected: Vec<_> = active
+            .iter()
+            .filter(|address| !last_active.contains(address))
+            .cloned()
+            .collect();
+        let lifted: Vec<_> = last_active
+            .iter()
+            .filter(|address| !active.contains(address))
+            .cloned()
+            .collect();
+
+        for address in &detected {
+            warn!("Emergency detected in advisory {address}");
+        }
+        for address in &lifted {
+            info!("Emergency lifted in advisory {address}");
+        }
+        *last_active = active.to_vec();
+
+        (detected, lifted)
+    }
+}
+
+/// Reads of the advisory contracts which the [`AdvisoryListener`] polls
+pub trait AdvisoryReads: Send + Sync {
+    /// Emergency status of every advisory, keyed by its address
+    fn is_emergency(&self) -> Vec<(AccountId, BoxFuture<'_, Result<bool, AzeroContractError>>)>;
+}
+
+/// Advisory contracts read through the AlephZero connection
+pub struct AzeroAdvisoryReads {
+    advisories: Vec<AdvisoryInstance>,
+    connection: Arc<AzeroWsConnection>,
+}
+
+impl AzeroAdvisoryReads {
+    pub fn new(advisories: Vec<AdvisoryInstance>, connection: Arc<AzeroWsConnection>) -> Self {
+        Self {
+            advisories,
+            connection,
+        }
+    }
+}
+
+impl AdvisoryReads for AzeroAdvisoryReads {
+    fn is_emergency(&self) -> Vec<(AccountId, BoxFuture<'_, Result<bool, AzeroContractError>>)> {
+        self.advisories
+            .iter()
+            .map(|advisory| {
+                (
+                    advisory.address.clone(),
+                    advisory
+                        .is_emergency(&self.connection)
+                        .map_ok(|(is_emergency, _)| is_emergency)
+                        .boxed(),
+                )
+            })
+            .collect()
+    }
 }
 
 pub struct AdvisoryListener;
 
 impl AdvisoryListener {
+    /// Trips the circuit breaker once an advisory is in emergency.
+    ///
+    /// An advisory which can not be read in time is skipped for the round, with the next round delayed by a backoff.
+    /// The circuit breaker is opened once there are no retries of the backoff left.
     pub async fn run(
-        advisories: Arc<Vec<AdvisoryInstance>>,
-        azero_connection: Arc<AzeroWsConnection>,
+        config: Arc<Config>,
+        advisories: Arc<dyn AdvisoryReads>,
+        emergencies: Arc<AdvisoryEmergencies>,
         circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
-        mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+        circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
     ) -> Result<CircuitBreakerEvent, AdvisoryListenerError> {
-        loop {
-            debug!("Ping");
+        let advisories = &advisories;
+        let emergencies = &emergencies;
+        let query_timeout = Duration::from_secs(config.advisory_query_timeout_sec);
+        let backoff = &Mutex::new(config.advisory_backoff());
 
-            select! {
-                cb_event = circuit_breaker_receiver.recv() => {
-                    warn!("Exiting due to a circuit breaker event {cb_event:?}");
-                    return Ok(cb_event?);
-                },
+        poll_circuit_breaker(
+            move || async move {
+                let statuses = read_with_timeout(advisories.is_emergency(), query_timeout).await;
+                let (active, failures) = in_emergency_skipping_failures(statuses);
 
-                active_advisories_res = Self::query_active_advisories(
-                    advisories.clone(),
-                    azero_connection.clone(),
-                ) => {
-                    debug!("Querying");
-
-                    match active_advisories_res {
-                        Err(why) => {
-                            warn!("Exiting due to an error querying active advisories {why:?}");
-                            let status = CircuitBreakerEvent::AlephClientError;
-                            circuit_breaker_sender.send(status.clone())?;
-                            return Ok(status.clone());
-                        },
-                        Ok(advisories) => {
-                            if advisories.is_empty() {
-                                debug!("No active advisories");
-                            } else {
-                                warn!("Exiting due to activation of advisories {advisories:?}");
-                                let status = CircuitBreakerEvent::AdvisoryEmergency(advisories);
-                                circuit_breaker_sender.send(status.clone())?;
-                                return Ok(status.clone());
-                            }
-                        },
-                    }
+                if !active.is_empty() {
+                    emergencies.update(&active);
+                    warn!("Exiting due to activation of advisories {active:?}");
+                    return Some(CircuitBreakerEvent::AdvisoryEmergency(active));
+                }
+                if failures == 0 {
+                    emergencies.update(&active);
+                    backoff.lock().expect("mutex lock").reset();
+                    debug!("No active advisories");
+                    return None;
                 }
-            }
 
-            sleep(Duration::from_secs(ALEPH_BLOCK_PROD_TIME_SEC)).await;
-        }
+                let delay = {
+                    let mut backoff = backoff.lock().expect("mutex lock");
+                    if !backoff.retries_left() {
+                        warn!("Exiting due to {failures} advisories failing to be read repeatedly");
+                        return Some(CircuitBreakerEvent::AlephClientError);
+                    }
+                    backoff.next_delay()
+                };
+                debug!("No active advisories among the ones read, next round in {delay:?} on top of the poll interval");
+                sleep(delay).await;
+                None
+            },
+            Duration::from_secs(config.advisory_poll_interval_sec),
+            circuit_breaker_sender,
+            circuit_breaker_receiver,
+        )
+        .await
     }
 
+    /// Advisories in emergency, failing if any of them can not be read
     pub async fn query_active_advisories(
-        advisories: Arc<Vec<AdvisoryInstance>>,
-        azero_connection: Arc<AzeroWsConnection>,
+        config: &Config,
+        advisories: &dyn AdvisoryReads,
     ) -> Result<Vec<AccountId>, AdvisoryListenerError> {
-        join_all(
-            advisories
-                .iter()
-                .map(|advisory| advisory.is_emergency(&azero_connection))
-                .collect::<Vec<_>>(),
+        in_emergency(
+            read_with_timeout(
+                advisories.is_emergency(),
+                Duration::from_secs(config.advisory_query_timeout_sec),
+            )
+            .await,
         )
-        .await
-        .into_iter()
-        .filter_map(|maybe_emergency| match maybe_emergency {
-            Ok((true, address)) => Some(Ok(address)),
-            Ok((false, _)) => None,
-            Err(why) => Some(Err(AdvisoryListenerError::AzeroContract(why))),
-        })
-        .collect()
     }
 
     pub fn parse_advisory_addresses(config: Arc<Config>) -> Vec<AdvisoryInstance> {
@@ -120,3 +202,142 @@ impl AdvisoryListener {
             .expect("Advisory addresses list")
     }
 }
+
+/// Reads the emergency status of every advisory at once, giving up on a read which takes longer than `query_timeout`
+async fn read_with_timeout<Fut>(
+    reads: impl IntoIterator<Item = (AccountId, Fut)>,
+    query_timeout: Duration,
+) -> Vec<(AccountId, Result<bool, AdvisoryListenerError>)>
+where
+    Fut: Future<Output = Result<bool, AzeroContractError>>,
+{
+    join_all(reads.into_iter().map(|(address, read)| async move {
+        let status = match timeout(query_timeout, read).await {
+            Ok(status) => status.map_err(AdvisoryListenerError::from),
+            Err(_) => Err(AdvisoryListenerError::Timeout(query_timeout)),
+        };
+        (address, status)
+    }))
+    .await
+}
+
+/// Every advisory in emergency, or the first error if any of them could not be read
+fn in_emergency(
+    statuses: Vec<(AccountId, Result<bool, AdvisoryListenerError>)>,
+) -> Result<Vec<AccountId>, AdvisoryListenerError> {
+    statuses
+        .into_iter()
+        .filter_map(|(address, status)| match status {
+            Ok(true) => Some(Ok(address)),
+            Ok(false) => None,
+            Err(why) => Some(Err(why)),
+        })
+        .collect()
+}
+
+/// Every advisory in emergency among the ones read, along with the number of the ones which could not be read
+fn in_emergency_skipping_failures(
+    statuses: Vec<(AccountId, Result<bool, AdvisoryListenerError>)>,
+) -> (Vec<AccountId>, usize) {
+    let mut active = Vec::new();
+    let mut failures = 0;
+
+    for (address, status) in statuses {
+        match status {
+            Ok(true) => active.push(address),
+            Ok(false) => {}
+            Err(why) => {
+                warn!("Skipping advisory {address} for this round: {why:?}");
+                failures += 1;
+            }
+        }
+    }
+
+    (active, failures)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::future::{pending, ready};
+
+    use futures::FutureExt;
+    use tokio::time::Instant;
+
+    use super::*;
+
+    const CALM: [u8; 32] = [1; 32];
+    const ALARMED: [u8; 32] = [2; 32];
+
+    #[test]
+    fn only_the_advisory_in_emergency_is_reported() {
+        let active = in_emergency(vec![
+            (AccountId::from(CALM), Ok(false)),
+            (AccountId::from(ALARMED), Ok(true)),
+        ])
+        .unwrap();
+
+        assert_eq!(active, [AccountId::from(ALARMED)]);
+    }
+
+    #[test]
+    fn unreadable_advisory_is_an_error() {
+        let statuses = vec![
+            (AccountId::from(ALARMED), Ok(true)),
+            (
+                AccountId::from(CALM),
+                Err(AdvisoryListenerError::AzeroContract(
+                    AzeroContractError::MissingOrInvalidField("is_emergency".to_owned()),
+                )),
+            ),
+        ];
+
+        assert!(in_emergency(statuses).is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn hanging_read_times_out_without_holding_back_the_others() {
+        let query_timeout = Duration::from_secs(10);
+        let started = Instant::now();
+
+        let statuses = read_with_timeout(
+            [
+                (AccountId::from(CALM), pending().boxed()),
+                (AccountId::from(ALARMED), ready(Ok(true)).boxed()),
+            ],
+            query_timeout,
+        )
+        .await;
+
+        assert_eq!(started.elapsed(), query_timeout);
+        assert!(matches!(
+            statuses[0],
+            (_, Err(AdvisoryListenerError::Timeout(_)))
+        ));
+        assert!(matches!(statuses[1], (_, Ok(true))));
+    }
+
+    #[test]
+    fn unreadable_advisory_is_skipped_for_the_round() {
+        let (active, failures) = in_emergency_skipping_failures(vec![
+            (AccountId::from(ALARMED), Ok(true)),
+            (
+                AccountId::from(CALM),
+                Err(AdvisoryListenerError::Timeout(Duration::from_secs(10))),
+            ),
+        ]);
+
+        assert_eq!(active, [AccountId::from(ALARMED)]);
+        assert_eq!(failures, 1);
+    }
+
+    #[test]
+    fn emergency_transitions_are_reported_once() {
+        let emergencies = AdvisoryEmergencies::default();
+        let alarmed = [AccountId::from(ALARMED)];
+
+        assert_eq!(emergencies.update(&alarmed), (alarmed.to_vec(), vec![]));
+        assert_eq!(emergencies.update(&alarmed), (vec![], vec![]));
+        assert_eq!(emergencies.update(&[]), (vec![], alarmed.to_vec()));
+        assert_eq!(emergencies.update(&[]), (vec![], vec![]));
+    }
+}
@@ -1,6 +1,14 @@
-use std::{cmp::min, sync::Arc, time::Duration};
+use std::{
+    cmp::min,
+    collections::VecDeque,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
-use contracts_azero_client::{Client, ContractEvent, ContractInstance};
+use contracts_azero_client::{
+    AccountId, Client, ClientWithSigner, ContractEvent, ContractInstance,
+};
 use futures::{
     future::join_all,
     stream::{FuturesOrdered, StreamExt},
@@ -9,7 +17,7 @@ use log::{debug, error, info, warn};
 use thiserror::Error;
 use tokio::{
     select,
-    sync::{broadcast, mpsc, oneshot},
+    sync::{broadcast, mpsc, oneshot, watch},
     task::JoinError,
     time::sleep,
 };
@@ -17,13 +25,16 @@ use tokio::{
 use super::AzeroMostEvents;
 use crate::{
     config::Config,
-    connections::azero::AzeroWsConnection,
-    contracts::{AzeroContractError, MostInstance},
+    connections::azero::{self, AzeroSigner, AzeroWsConnection},
+    contracts::{get_request_event_data, AzeroContractError, MostInstance},
+    helpers::{
+        next_circuit_breaker_event, open_circuit_breaker, poll_circuit_breaker,
+        poll_finalized_head, shutdown_requested, LastKnownGood, PollBackoff,
+    },
+    metrics::{Guardian, Metrics},
     CircuitBreakerEvent,
 };
 
-pub const ALEPH_BLOCK_PROD_TIME_SEC: u64 = 1;
-
 #[derive(Debug, Error)]
 #[error(transparent)]
 #[non_exhaustive]
@@ -60,6 +71,9 @@ pub enum AlephZeroListenerError {
 
     #[error("One-shot receive error")]
     OneShotReceive(#[from] oneshot::error::RecvError),
+
+    #[error("finalized head channel closed")]
+    FinalizedHead(#[from] watch::error::RecvError),
 }
 
 #[derive(Copy, Clone)]
@@ -73,9 +87,12 @@ impl AlephZeroListener {
         azero_events_sender: mpsc::Sender<AzeroMostEvents>,
         next_block_to_process_sender: broadcast::Sender<u32>,
         mut next_block_to_process_receiver: broadcast::Receiver<u32>,
+        awaiting_handler: watch::Sender<bool>,
         block_seal_sender: mpsc::Sender<u32>,
-        circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
+        mut finalized_head_receiver: watch::Receiver<u32>,
+        mut shutdown: watch::Receiver<bool>,
         mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+        base_fee: Arc<CachedBaseFee>,
     ) -> Result<CircuitBreakerEvent, AlephZeroListenerError> {
         let Config {
             azero_contract_metadata,
@@ -83,10 +100,14 @@ impl AlephZeroListener {
             azero_ref_time_limit,
             azero_proof_size_limit,
             sync_step,
+            azero_catch_up_window,
+            azero_min_confirmations,
             ..
         } = &*config;
 
         let mut event_batch_ack_receiver = FuturesOrdered::new();
+        let mut catch_up_window = CatchUpWindow::new(*azero_catch_up_window);
+        info!(target: "AlephZeroListener", "Processing blocks with at least {azero_min_confirmations} confirmations on top of finality");
 
         let most_azero = MostInstance::new(
             azero_contract_address,
@@ -97,35 +118,35 @@ impl AlephZeroListener {
 
         loop {
             debug!(target: "AlephZeroListener", "Ping");
+            // with the window full, nothing is fetched until the handler acknowledges a batch
+            awaiting_handler.send_replace(!catch_up_window.has_room());
 
             select! {
-                cb_event = circuit_breaker_receiver.recv() => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!(target: "AlephZeroListener", "Exiting before handling next block due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
 
-                Ok (unprocessed_block_number) = next_block_to_process_receiver.recv() => {
-                    // Query for the next unknown finalized block number, if not present we wait for it
-                    let next_finalized_block_number = match get_next_finalized_block_number_azero(
-                        azero_connection.clone(),
-                        unprocessed_block_number,
-                    )
-                        .await {
-                            Ok(number) => number,
-                            Err(AlephZeroListenerError::AlephClient(_)) => {
-                                warn!("Aleph client failed when getting next finalized block number. Opening circuit breaker");
-                                let status = CircuitBreakerEvent::AlephClientError;
-                                circuit_breaker_sender.send(status.clone ())?;
-                                return Ok (status);
-                            },
-                            Err (other) => {
-                                return Err (other);
-                            }
-                        };
+                _ = shutdown_requested(&mut shutdown) => {
+                    seal_handled_batches(&mut event_batch_ack_receiver, &mut catch_up_window, &block_seal_sender).await?;
+                    return Ok(CircuitBreakerEvent::Shutdown);
+                },
 
-                    let to_block = min(
+                Ok (unprocessed_block_number) = next_block_to_process_receiver.recv(), if catch_up_window.has_room() => {
+                    // Wait for the next unknown finalized block number, if not present yet
+                    let finalized_head = *finalized_head_receiver
+                        .wait_for(|finalized_head| {
+                            confirmed_head(*finalized_head, *azero_min_confirmations)
+                                .is_some_and(|confirmed_head| confirmed_head >= unprocessed_block_number)
+                        })
+                        .await?;
+                    // the confirmations were just checked
+                    let next_finalized_block_number = finalized_head - azero_min_confirmations;
+
+                    let to_block = catch_up_window.batch_end(
+                        unprocessed_block_number,
                         next_finalized_block_number,
-                        unprocessed_block_number + sync_step - 1,
+                        *sync_step,
                     );
 
                     info!(target: "AlephZeroListener",
@@ -133,19 +154,34 @@ impl AlephZeroListener {
                           unprocessed_block_number, to_block
                     );
 
-                    let events = fetch_events_in_block_range(&azero_connection, unprocessed_block_number, to_block, &[&most_azero.contract]).await?;
+                    let contracts = [&most_azero.contract];
+                    // the node is retried the way its finalized head is polled
+                    let events = fetch_events_reconnecting(
+                        config.finalized_head_backoff(config.azero_block_time()),
+                        || fetch_events_in_block_range(&azero_connection, unprocessed_block_number, to_block, &contracts, config.sequential_processing),
+                    ).await?;
+                    log_base_fee(&events, &base_fee, &most_azero, &azero_connection).await;
                     // Fetch the events in parallel.
 
 
                     let (ack_sender, ack_receiver) = oneshot::channel::<u32>();
                     event_batch_ack_receiver.push_back(ack_receiver);
+                    catch_up_window.fetched(unprocessed_block_number, to_block);
+                    // the send waits for the handler while it is busy with the batches sent before
+                    awaiting_handler.send_replace(true);
 
                     select! {
-                        cb_event = circuit_breaker_receiver.recv() => {
+                        cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                             warn!(target: "AlephZeroListener", "Exiting before sending events due to a circuit breaker event {cb_event:?}");
                             return Ok(cb_event?);
                         },
 
+                        // the batch which was not sent is never acked, so only the ones before it are sealed
+                        _ = shutdown_requested(&mut shutdown) => {
+                            seal_handled_batches(&mut event_batch_ack_receiver, &mut catch_up_window, &block_seal_sender).await?;
+                            return Ok(CircuitBreakerEvent::Shutdown);
+                        },
+
                         Ok(_) = azero_events_sender
                             .send(AzeroMostEvents {
                                 events: events.clone(),
@@ -162,6 +198,7 @@ impl AlephZeroListener {
                 },
                 Some(processed_block_res) = event_batch_ack_receiver.next() => {
                     let processed_block = processed_block_res?;
+                    catch_up_window.handled(processed_block);
                     info!("Marking all events up to block {processed_block} as handled");
                     block_seal_sender.send(processed_block).await?;
                 }
@@ -170,12 +207,194 @@ impl AlephZeroListener {
     }
 }
 
-async fn fetch_events_in_block_range(
+/// Seals the in-flight batches as the handler finishes them, so that the cached block number covers all of them.
+///
+/// Stops at the first batch the handler abandoned, as the batches after it are not handled in full.
+async fn seal_handled_batches(
+    event_batch_ack_receiver: &mut FuturesOrdered<oneshot::Receiver<u32>>,
+    catch_up_window: &mut CatchUpWindow,
+    block_seal_sender: &mpsc::Sender<u32>,
+) -> Result<(), AlephZeroListenerError> {
+    info!(target: "AlephZeroListener", "Shutting down after {} in-flight batches are handled", event_batch_ack_receiver.len());
+
+    while let Some(processed_block_res) = event_batch_ack_receiver.next().await {
+        let Ok(processed_block) = processed_block_res else {
+            warn!(target: "AlephZeroListener", "An in-flight batch was not handled, not sealing the ones after it");
+            break;
+        };
+        catch_up_window.handled(processed_block);
+        info!(target: "AlephZeroListener", "Marking all events up to block {processed_block} as handled");
+        block_seal_sender.send(processed_block).await?;
+    }
+
+    Ok(())
+}
+
+/// Last block with at least `min_confirmations` finalized blocks on top of it, if there is one yet
+fn confirmed_head(finalized_head: u32, min_confirmations: u32) -> Option<u32> {
+    finalized_head.checked_sub(min_confirmations)
+}
+
+/// Batches of blocks which were fetched, but not handled yet.
+///
+/// After a downtime the whole gap is ready to be fetched right away, so without a bound the listener would
+/// keep fetching `sync_step` batches far ahead of the handler. As the block number in redis only advances
+/// once a batch is handled, the window also keeps it close behind the fetched blocks.
+struct CatchUpWindow {
+    size: u32,
+    batches: VecDeque<(u32, u32)>,
+}
+
+impl CatchUpWindow {
+    fn new(size: u32) -> Self {
+        Self {
+            size,
+            batches: VecDeque::new(),
+        }
+    }
+
+    fn in_flight(&self) -> u32 {
+        self.batches.iter().map(|(from, to)| to - from + 1).sum()
+    }
+
+    fn has_room(&self) -> bool {
+        self.in_flight() < self.size
+    }
+
+    /// Last block of the batch starting at `from_block`, bounded by the finalized head, `sync_step` and the room left
+    fn batch_end(&self, from_block: u32, finalized_block: u32, sync_step: u32) -> u32 {
+        let room = self.size.saturating_sub(self.in_flight()).max(1);
+        min(finalized_block, from_block + min(sync_step, room) - 1)
+    }
+
+    fn fetched(&mut self, from_block: u32, to_block: u32) {
+        self.batches.push_back((from_block, to_block));
+    }
+
+    /// Batches are acknowledged in the order they were fetched
+    fn handled(&mut self, to_block: u32) {
+        if let Some((_, oldest_to_block)) = self.batches.pop_front() {
+            debug_assert_eq!(oldest_to_block, to_block);
+        }
+    }
+}
+
+/// Base fee of the AlephZero contract, kept for `ttl` so that it is not read for every request.
+///
+/// Shared with the events handler, which drops it once the parameters of the contract change.
+pub struct CachedBaseFee {
+    ttl: Duration,
+    cached: Mutex<Option<(Instant, u128)>>,
+}
+
+impl CachedBaseFee {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            cached: Mutex::new(None),
+        }
+    }
+
+    fn get(&self, now: Instant) -> Option<u128> {
+        self.cached
+            .lock()
+            .unwrap()
+            .filter(|(read_at, _)| now.duration_since(*read_at) < self.ttl)
+            .map(|(_, base_fee)| base_fee)
+    }
+
+    fn set(&self, now: Instant, base_fee: u128) {
+        *self.cached.lock().unwrap() = Some((now, base_fee));
+    }
+
+    /// The next request reads the base fee from the contract again
+    pub fn invalidate(&self) {
+        *self.cached.lock().unwrap() = None;
+    }
+}
+
+/// Logs the current base fee along every transfer request, so that a misconfigured fee shows up next to the requests paying it
+async fn log_base_fee(
+    events: &[ContractEvent],
+    base_fee: &CachedBaseFee,
+    most_azero: &MostInstance,
+    azero_connection: &Client,
+) {
+    let requests: Vec<_> = events
+        .iter()
+        .filter(|event| event.name.as_deref() == Some("CrosschainTransferRequest"))
+        .filter_map(|event| get_request_event_data(&event.data).ok())
+        .collect();
+    if requests.is_empty() {
+        return;
+    }
+
+    let now = Instant::now();
+    let current_base_fee = match base_fee.get(now) {
+        Some(current_base_fee) => current_base_fee,
+        None => match most_azero.get_base_fee(azero_connection).await {
+            Ok(current_base_fee) => {
+                base_fee.set(now, current_base_fee);
+                current_base_fee
+            }
+            Err(why) => {
+                warn!(target: "AlephZeroListener", "Could not read the base fee: {why:?}");
+                return;
+            }
+        },
+    };
+
+    for request in requests {
+        info!(target: "AlephZeroListener", "Transfer request with nonce {} of {} tokens, current base fee: {current_base_fee}", request.request_nonce, request.amount);
+    }
+}
+
+/// Fetches the events of a batch again once the client reconnected after the connection to the node dropped.
+///
+/// The batch is not skipped, so no block is processed past the one which failed. Any other failure, or a connection
+/// which is not back within the retries of the `backoff`, fails the listener as before.
+async fn fetch_events_reconnecting<F, Fut>(
+    mut backoff: PollBackoff,
+    mut fetch: F,
+) -> Result<Vec<ContractEvent>, AlephZeroListenerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<ContractEvent>, AlephZeroListenerError>>,
+{
+    loop {
+        match fetch().await {
+            Err(AlephZeroListenerError::AzeroClient(why))
+                if why.is_disconnected() && backoff.retries_left() =>
+            {
+                let delay = backoff.next_delay();
+                warn!(target: "AlephZeroListener", "Connection to the AlephZero node lost: {why}, fetching the batch again in {delay:?}");
+                sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Events of `contracts` emitted in the blocks from `from_block` to `to_block`, inclusive
+pub async fn fetch_events_in_block_range(
     azero_connection: &Arc<Client>,
     from_block: u32,
     to_block: u32,
     contracts: &[&ContractInstance],
+    sequential: bool,
 ) -> Result<Vec<ContractEvent>, AlephZeroListenerError> {
+    if sequential {
+        let mut events = Vec::new();
+        for block_number in from_block..=to_block {
+            events.extend(
+                azero_connection
+                    .fetch_events_from_contracts(block_number, contracts)
+                    .await?,
+            );
+        }
+        return Ok(events);
+    }
+
     let mut event_fetching_tasks = Vec::new();
 
     for block_number in from_block..=to_block {
@@ -199,26 +418,59 @@ async fn fetch_events_in_block_range(
     Ok(events)
 }
 
-async fn get_next_finalized_block_number_azero(
-    azero_connection: Arc<AzeroWsConnection>,
-    not_older_than: u32,
-) -> Result<u32, AlephZeroListenerError> {
-    loop {
-        let hash = azero_connection.get_finalized_block_hash().await?;
-        let best_finalized_block_number = azero_connection
-            .get_block_number(hash)
-            .await?
-            .expect("Finalized block has a number.");
-
-        if best_finalized_block_number >= not_older_than {
-            return Ok(best_finalized_block_number);
-        }
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum AlephZeroFinalizedHeadListenerError {
+    #[error("broadcast send error")]
+    BroadcastSend(#[from] broadcast::error::SendError<CircuitBreakerEvent>),
 
-        // If we are up to date, we can sleep for a longer time.
-        sleep(Duration::from_secs(10 * ALEPH_BLOCK_PROD_TIME_SEC)).await;
+    #[error("broadcast receive error")]
+    BroadcastReceive(#[from] broadcast::error::RecvError),
+}
+
+/// Single poller of the AlephZero finalized head, publishing it to all interested components.
+#[derive(Copy, Clone)]
+pub struct AlephZeroFinalizedHeadListener;
+
+impl AlephZeroFinalizedHeadListener {
+    pub async fn run(
+        config: Arc<Config>,
+        azero_connection: Arc<AzeroWsConnection>,
+        finalized_head_sender: watch::Sender<u32>,
+        circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
+        mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+    ) -> Result<CircuitBreakerEvent, AlephZeroFinalizedHeadListenerError> {
+        info!(target: "AlephZeroFinalizedHeadListener", "Starting");
+
+        select! {
+            cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
+                warn!(target: "AlephZeroFinalizedHeadListener", "Exiting due to a circuit breaker event {cb_event:?}");
+                Ok(cb_event?)
+            },
+
+            why = poll_finalized_head(
+                || get_finalized_block_number_azero(&azero_connection),
+                10 * config.azero_block_time(),
+                config.finalized_head_backoff(config.azero_block_time()),
+                finalized_head_sender,
+            ) => {
+                warn!(target: "AlephZeroFinalizedHeadListener", "Aleph client failed when getting finalized block number: {why:?}. Opening circuit breaker");
+                let status = CircuitBreakerEvent::AlephClientError;
+                open_circuit_breaker(&circuit_breaker_sender, status.clone());
+                Ok(status)
+            }
+        }
     }
 }
 
+async fn get_finalized_block_number_azero(
+    azero_connection: &AzeroWsConnection,
+) -> Result<Option<u32>, contracts_azero_client::ClientError> {
+    let hash = azero_connection.get_finalized_block_hash().await?;
+    azero_connection.get_block_number(hash).await
+}
+
 #[derive(Debug, Error)]
 #[error(transparent)]
 #[non_exhaustive]
@@ -233,21 +485,126 @@ pub enum AlephZeroHaltedListenerError {
     BroadcastReceive(#[from] broadcast::error::RecvError),
 }
 
+/// Read of the halted flag of the AlephZero Most contract which the [`AlephZeroHaltedListener`] polls
+#[async_trait::async_trait]
+pub trait HaltedRead: Send + Sync {
+    async fn is_halted(&self) -> Result<bool, AzeroContractError>;
+}
+
+/// Most contract read through the AlephZero connection
+pub struct AzeroHaltedRead {
+    most: MostInstance,
+    connection: Arc<AzeroWsConnection>,
+}
+
+impl AzeroHaltedRead {
+    pub fn new(
+        config: &Config,
+        connection: Arc<AzeroWsConnection>,
+    ) -> Result<Self, AzeroContractError> {
+        Ok(Self {
+            most: MostInstance::new(
+                &config.azero_contract_address,
+                &config.azero_contract_metadata,
+                config.azero_ref_time_limit,
+                config.azero_proof_size_limit,
+            )?,
+            connection,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl HaltedRead for AzeroHaltedRead {
+    async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+        self.most.is_halted(&self.connection).await
+    }
+}
+
 #[derive(Copy, Clone)]
 pub struct AlephZeroHaltedListener;
 
 impl AlephZeroHaltedListener {
     pub async fn run(
         config: Arc<Config>,
-        azero_connection: Arc<AzeroWsConnection>,
+        most_azero: Arc<dyn HaltedRead>,
         circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
-        mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+        circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
     ) -> Result<CircuitBreakerEvent, AlephZeroHaltedListenerError> {
+        info!(
+            target: "AlephZeroHaltedListener",
+            "Starting"
+        );
+
+        let most_azero = &most_azero;
+        let is_halted = &LastKnownGood::new(Duration::from_secs(config.azero_guard_read_ttl_sec));
+
+        poll_circuit_breaker(
+            move || async move {
+                debug!(target: "AlephZeroHaltedListener", "Querying");
+                match is_halted
+                    .read((), most_azero.is_halted())
+                    .await
+                {
+                    Ok(true) => {
+                        warn!(target: "AlephZeroHaltedListener", "Most is halted, exiting");
+                        Some(CircuitBreakerEvent::BridgeHaltAlephZero)
+                    }
+                    Ok(false) => None,
+                    Err(why) => {
+                        warn!(target: "AlephZeroHaltedListener", "Exiting due to a connection error {why:?}");
+                        Some(CircuitBreakerEvent::AlephClientError)
+                    }
+                }
+            },
+            config.azero_block_time(),
+            circuit_breaker_sender,
+            circuit_breaker_receiver,
+        )
+        .await
+    }
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum AlephZeroCommitteeMembershipListenerError {
+    #[error("Azero contract error")]
+    AzeroContract(#[from] AzeroContractError),
+
+    #[error("broadcast send error")]
+    BroadcastSend(#[from] broadcast::error::SendError<CircuitBreakerEvent>),
+
+    #[error("broadcast receive error")]
+    BroadcastReceive(#[from] broadcast::error::RecvError),
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+enum MembershipQueryError {
+    #[error("Azero contract error")]
+    AzeroContract(#[from] AzeroContractError),
+
+    #[error("Signer error")]
+    Signer(#[from] azero::Error),
+}
+
+pub struct AlephZeroCommitteeMembershipListener;
+
+impl AlephZeroCommitteeMembershipListener {
+    pub async fn run(
+        config: Arc<Config>,
+        azero_signed_connection: Arc<ClientWithSigner<AzeroSigner>>,
+        metrics: Arc<Metrics>,
+        circuit_breaker_sender: broadcast::Sender<CircuitBreakerEvent>,
+        circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+    ) -> Result<CircuitBreakerEvent, AlephZeroCommitteeMembershipListenerError> {
         let Config {
             azero_contract_metadata,
             azero_contract_address,
             azero_ref_time_limit,
             azero_proof_size_limit,
+            committee_membership_check_interval_sec,
             ..
         } = &*config;
 
@@ -258,44 +615,309 @@ impl AlephZeroHaltedListener {
             *azero_proof_size_limit,
         )?;
 
-        info!(
-            target: "AlephZeroHaltedListener",
-            "Starting"
+        info!(target: "AlephZeroCommitteeMembershipListener", "Starting");
+
+        let most_azero = &most_azero;
+        let azero_signed_connection = &azero_signed_connection;
+        let metrics = &metrics;
+
+        poll_circuit_breaker(
+            move || async move {
+                let membership = async {
+                    let account_id = azero_signed_connection.signer().fetch_account_id().await?;
+                    let committee_id = most_azero
+                        .current_committee_id(azero_signed_connection.client())
+                        .await?;
+                    let is_member = most_azero
+                        .is_in_committee(
+                            azero_signed_connection.client(),
+                            committee_id,
+                            account_id.clone(),
+                        )
+                        .await?;
+                    // checked on every poll, so that the index follows the committee changes
+                    let committee = most_azero
+                        .committee(azero_signed_connection.client(), committee_id)
+                        .await
+                        .unwrap_or_else(|why| {
+                            debug!(target: "AlephZeroCommitteeMembershipListener", "Could not read the members of committee {committee_id}: {why:?}");
+                            Vec::new()
+                        });
+                    // membership is told by `is_in_committee`, an empty list only leaves the index unknown
+                    if committee.is_empty() {
+                        debug!(target: "AlephZeroCommitteeMembershipListener", "Members of committee {committee_id} are not recorded, e.g. before a backfill after an upgrade");
+                    }
+                    let index = guardian_index(&committee, &account_id);
+                    if metrics.set_guardian_index(index) != index {
+                        info!(target: "AlephZeroCommitteeMembershipListener", "Account {account_id} is {} in committee {committee_id}", Guardian(index));
+                    }
+
+                    Ok::<_, MembershipQueryError>((account_id, is_member))
+                };
+
+                match membership.await {
+                    Ok((account_id, is_member)) => membership_status(
+                        azero_signed_connection.account_id(),
+                        &account_id,
+                        is_member,
+                    ),
+                    Err(why) => {
+                        warn!(target: "AlephZeroCommitteeMembershipListener", "Exiting due to an error when checking committee membership {why:?}");
+                        Some(CircuitBreakerEvent::AlephClientError)
+                    }
+                }
+            },
+            Duration::from_secs(*committee_membership_check_interval_sec),
+            circuit_breaker_sender,
+            circuit_breaker_receiver,
+        )
+        .await
+    }
+}
+
+/// Position of `account_id` in the `committee` list, which identifies the relayer among the guardians
+fn guardian_index(committee: &[AccountId], account_id: &AccountId) -> Option<usize> {
+    committee.iter().position(|member| member == account_id)
+}
+
+/// Submissions are signed with the account the relayer started with, so they have to stop once the signer
+/// reports a different one or the account is no longer a member of the current committee.
+fn membership_status(
+    expected_account_id: &AccountId,
+    account_id: &AccountId,
+    is_member: bool,
+) -> Option<CircuitBreakerEvent> {
+    if account_id != expected_account_id {
+        error!(target: "AlephZeroCommitteeMembershipListener", "Signer account changed from {expected_account_id} to {account_id}, exiting");
+        Some(CircuitBreakerEvent::CommitteeMembershipLost)
+    } else if !is_member {
+        error!(target: "AlephZeroCommitteeMembershipListener", "Account {account_id} is no longer a member of the current committee, exiting");
+        Some(CircuitBreakerEvent::CommitteeMembershipLost)
+    } else {
+        debug!(target: "AlephZeroCommitteeMembershipListener", "Account {account_id} is a member of the current committee");
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use tokio::time::Instant;
+
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(60);
+
+    fn account(byte: u8) -> AccountId {
+        AccountId::from([byte; 32])
+    }
+
+    fn dropped_connection() -> AlephZeroListenerError {
+        contracts_azero_client::ClientError::Rpc(subxt::error::RpcError::DisconnectedWillReconnect(
+            "connection reset".to_owned(),
+        ))
+        .into()
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batch_is_fetched_again_after_the_connection_dropped() {
+        let fetches = Mutex::new(0);
+
+        let events = fetch_events_reconnecting(PollBackoff::new(INTERVAL, INTERVAL, 3), || {
+            let mut fetches = fetches.lock().unwrap();
+            *fetches += 1;
+            // the connection drops mid-loop, while fetching the first batch
+            let result = match *fetches {
+                1 => Err(dropped_connection()),
+                _ => Ok(Vec::new()),
+            };
+            async move { result }
+        })
+        .await;
+
+        assert!(events.unwrap().is_empty());
+        assert_eq!(*fetches.lock().unwrap(), 2);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn connection_which_is_not_back_fails_the_listener() {
+        let fetches = Mutex::new(0);
+
+        let events = fetch_events_reconnecting(PollBackoff::new(INTERVAL, INTERVAL, 2), || {
+            *fetches.lock().unwrap() += 1;
+            async { Err(dropped_connection()) }
+        })
+        .await;
+
+        assert!(matches!(
+            events,
+            Err(AlephZeroListenerError::AzeroClient(_))
+        ));
+        assert_eq!(*fetches.lock().unwrap(), 3);
+    }
+
+    #[test]
+    fn guardian_index_is_the_position_in_the_committee() {
+        let committee = [account(3), account(1), account(2)];
+        let metrics = Metrics::default();
+
+        metrics.set_guardian_index(guardian_index(&committee, &account(2)));
+        assert_eq!(metrics.guardian(), Guardian(Some(2)));
+        assert_eq!(metrics.guardian().to_string(), "guardian 2");
+        assert_eq!(metrics.render(true), "redis_up 1\nguardian_index 2\n");
+
+        // the committee changed and the account was moved
+        let committee = [account(2), account(3)];
+        assert_eq!(
+            metrics.set_guardian_index(guardian_index(&committee, &account(2))),
+            Some(2)
         );
+        assert_eq!(metrics.guardian().to_string(), "guardian 0");
 
-        loop {
-            debug!(target: "AlephZeroHaltedListener", "Ping");
+        assert_eq!(guardian_index(&committee, &account(1)), None);
+        assert_eq!(Guardian(None).to_string(), "guardian (index unknown)");
+    }
 
-            select! {
-                cb_event = circuit_breaker_receiver.recv () => {
-                    warn!(target: "AlephZeroHaltedListener","Exiting due to a circuit breaker event {cb_event:?}");
-                    return Ok(cb_event?);
-                },
+    #[tokio::test(start_paused = true)]
+    async fn account_change_mid_run_opens_circuit_breaker() {
+        // the signer's key is rotated after the third check
+        let mut responses = vec![
+            (account(2), true),
+            (account(1), true),
+            (account(1), true),
+            (account(1), true),
+        ];
+        let checks = Arc::new(Mutex::new(0));
+        let (circuit_breaker_sender, mut other_component_receiver) = broadcast::channel(1);
+
+        let start = Instant::now();
+        let listener_checks = Arc::clone(&checks);
+        let status = poll_circuit_breaker::<_, _, AlephZeroCommitteeMembershipListenerError>(
+            move || {
+                *listener_checks.lock().unwrap() += 1;
+                let (account_id, is_member) = responses.pop().unwrap();
+                std::future::ready(membership_status(&account(1), &account_id, is_member))
+            },
+            INTERVAL,
+            circuit_breaker_sender.clone(),
+            circuit_breaker_sender.subscribe(),
+        )
+        .await
+        .unwrap();
+
+        assert!(matches!(
+            status,
+            CircuitBreakerEvent::CommitteeMembershipLost
+        ));
+        assert!(matches!(
+            other_component_receiver.recv().await.unwrap(),
+            CircuitBreakerEvent::CommitteeMembershipLost
+        ));
+        assert_eq!(*checks.lock().unwrap(), 4);
+        assert_eq!(start.elapsed(), INTERVAL * 3);
+    }
 
-                is_halted = most_azero.is_halted(&azero_connection) => {
-                    debug!(target: "AlephZeroHaltedListener", "Querying");
-                    match is_halted {
-                        Ok(is_halted) => {
-                            if is_halted {
-                                circuit_breaker_sender.send(CircuitBreakerEvent::BridgeHaltAlephZero)?;
-                                warn!(target: "AlephZeroHaltedListener",
-                                      "Most is halted, exiting");
-                                return Ok(CircuitBreakerEvent::BridgeHaltAlephZero);
-                            }
-                        },
+    #[test]
+    fn base_fee_is_read_again_after_the_ttl() {
+        let base_fee = CachedBaseFee::new(Duration::from_secs(60));
+        let read_at = Instant::now();
+        assert_eq!(base_fee.get(read_at), None);
 
-                        Err(why) => {
-                            warn!("Exiting due to a connection error {why:?}");
-                            let status = CircuitBreakerEvent::AlephClientError;
-                            circuit_breaker_sender.send(status.clone())?;
-                            return Ok(status.clone());
-                        }
-                    }
+        base_fee.set(read_at, 100);
+        assert_eq!(base_fee.get(read_at + Duration::from_secs(59)), Some(100));
+        assert_eq!(base_fee.get(read_at + Duration::from_secs(60)), None);
+    }
 
-                    // sleep before making another query
-                    sleep(Duration::from_secs(ALEPH_BLOCK_PROD_TIME_SEC)).await;
-                }
+    #[test]
+    fn base_fee_is_read_again_after_a_config_change() {
+        let base_fee = CachedBaseFee::new(Duration::from_secs(60));
+        let read_at = Instant::now();
+        base_fee.set(read_at, 100);
+
+        base_fee.invalidate();
+        assert_eq!(base_fee.get(read_at), None);
+    }
+
+    #[test]
+    fn blocks_need_the_confirmations_on_top_of_finality() {
+        assert_eq!(confirmed_head(100, 0), Some(100));
+        assert_eq!(confirmed_head(100, 10), Some(90));
+        assert_eq!(confirmed_head(5, 10), None);
+    }
+
+    #[test]
+    fn catching_up_proceeds_in_bounded_batches() {
+        const SYNC_STEP: u32 = 100;
+        const WINDOW: u32 = 250;
+        const FINALIZED: u32 = 10_000;
+
+        let mut window = CatchUpWindow::new(WINDOW);
+        let mut next_unprocessed = 1;
+        let mut sealed = Vec::new();
+
+        while next_unprocessed <= FINALIZED || window.in_flight() > 0 {
+            // fetch as far ahead as the window allows, then let the handler acknowledge the oldest batch
+            while window.has_room() && next_unprocessed <= FINALIZED {
+                let to_block = window.batch_end(next_unprocessed, FINALIZED, SYNC_STEP);
+                assert!(to_block - next_unprocessed < SYNC_STEP);
+                window.fetched(next_unprocessed, to_block);
+                next_unprocessed = to_block + 1;
+                assert!(window.in_flight() <= WINDOW);
             }
+
+            let (_, to_block) = window.batches[0];
+            window.handled(to_block);
+            sealed.push(to_block);
+        }
+
+        // the pointer advances after every batch, which is cut short when the window is almost full
+        assert_eq!(sealed.len(), 120);
+        assert_eq!(sealed[..6], [100, 200, 250, 350, 450, 500]);
+        assert!(sealed.windows(2).all(|pair| pair[1] - pair[0] <= SYNC_STEP));
+        assert_eq!(*sealed.last().unwrap(), FINALIZED);
+        assert_eq!(window.in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn shutdown_seals_only_the_batches_handled_in_full() {
+        let mut window = CatchUpWindow::new(100);
+        let mut acks = FuturesOrdered::new();
+        let mut ack_senders = Vec::new();
+        for (from_block, to_block) in [(1, 10), (11, 20), (21, 30), (31, 40)] {
+            let (ack_sender, ack_receiver) = oneshot::channel::<u32>();
+            acks.push_back(ack_receiver);
+            ack_senders.push(ack_sender);
+            window.fetched(from_block, to_block);
         }
+        let (block_seal_sender, mut block_seal_receiver) = mpsc::channel(4);
+
+        // the shutdown comes in the middle of the third batch, which is abandoned, while the fourth is finished
+        let [first, second, third, fourth]: [_; 4] = ack_senders.try_into().unwrap();
+        second.send(20).unwrap();
+        fourth.send(40).unwrap();
+        let drained = tokio::spawn(async move {
+            seal_handled_batches(&mut acks, &mut window, &block_seal_sender)
+                .await
+                .map(|()| window.in_flight())
+        });
+        first.send(10).unwrap();
+        drop(third);
+
+        assert_eq!(drained.await.unwrap().unwrap(), 20);
+        let mut sealed = Vec::new();
+        while let Some(block) = block_seal_receiver.recv().await {
+            sealed.push(block);
+        }
+        assert_eq!(sealed, [10, 20]);
+    }
+
+    #[test]
+    fn removal_from_committee_opens_circuit_breaker() {
+        assert!(membership_status(&account(1), &account(1), true).is_none());
+        assert!(matches!(
+            membership_status(&account(1), &account(1), false),
+            Some(CircuitBreakerEvent::CommitteeMembershipLost)
+        ));
     }
 }
@@ -1,27 +1,31 @@
-use std::{cmp::min, sync::Arc};
+use std::{cmp::min, convert::Infallible, future::Future, sync::Arc};
 
 use ethers::{
     core::types::Address,
     prelude::ContractError,
     providers::{Http, Provider},
 };
+use futures::FutureExt;
 use log::{debug, error, info, warn};
 use thiserror::Error;
 use tokio::{
     select,
-    sync::{broadcast, mpsc, oneshot},
-    time::{sleep, Duration},
+    sync::{broadcast, mpsc, oneshot, watch},
+    time::sleep,
 };
 
 use super::EthMostEvents;
 use crate::{
     config::Config,
-    connections::eth::{get_next_finalized_block_number, EthConnection},
-    contracts::Most,
+    connections::eth::{get_finalized_block_number, EthConnection},
+    contracts::{Most, MostEvents},
+    helpers::{
+        next_circuit_breaker_event, open_circuit_breaker, poll_finalized_head, shutdown_requested,
+        PollBackoff,
+    },
     CircuitBreakerEvent,
 };
 
-pub const ETH_BLOCK_PROD_TIME_SEC: u64 = 12;
 const LOG_TARGET: &str = "EthereumListener";
 
 pub struct EthereumListener;
@@ -44,15 +48,22 @@ pub enum EthereumListenerError {
 
     #[error("channel receive error")]
     Receive(#[from] broadcast::error::RecvError),
+
+    #[error("finalized head channel closed")]
+    FinalizedHead(#[from] watch::error::RecvError),
 }
 
 impl EthereumListener {
+    #[allow(clippy::too_many_arguments)]
     pub async fn run(
         config: Arc<Config>,
         eth_connection: Arc<EthConnection>,
         eth_events_sender: mpsc::Sender<EthMostEvents>,
         last_processed_block_number: broadcast::Sender<u32>,
         mut next_unprocessed_block_number: broadcast::Receiver<u32>,
+        awaiting_handler: watch::Sender<bool>,
+        mut finalized_head_receiver: watch::Receiver<u32>,
+        mut shutdown: watch::Receiver<bool>,
         mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
     ) -> Result<CircuitBreakerEvent, EthereumListenerError> {
         let Config {
@@ -65,15 +76,21 @@ impl EthereumListener {
         let most_eth = Most::new(address, Arc::clone(&eth_connection));
 
         info!(target: LOG_TARGET, "Starting");
+        // a listener which was restarted while held back by the handler is not anymore
+        awaiting_handler.send_replace(false);
 
         loop {
             debug!(target: LOG_TARGET, "Ping");
 
             let unprocessed_block_number = select! {
-                cb_event = circuit_breaker_receiver.recv() => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!(target: LOG_TARGET,"Exiting before handling next block due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
+                _ = shutdown_requested(&mut shutdown) => {
+                    info!(target: LOG_TARGET, "Exiting before handling next block due to a shutdown");
+                    return Ok(CircuitBreakerEvent::Shutdown);
+                },
                 Ok(unprocessed_block_number) = next_unprocessed_block_number.recv() => {
                     unprocessed_block_number
                 }
@@ -83,15 +100,18 @@ impl EthereumListener {
             info!(target: LOG_TARGET, "Waiting for the next finalized block number");
 
             let next_finalized_block_number = select! {
-                cb_event = circuit_breaker_receiver.recv () => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!(target: LOG_TARGET, "Exiting before sending events due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
-                next_finalized_block_number = get_next_finalized_block_number(
-                    eth_connection.clone(),
-                    unprocessed_block_number,
+                _ = shutdown_requested(&mut shutdown) => {
+                    info!(target: LOG_TARGET, "Exiting before sending events due to a shutdown");
+                    return Ok(CircuitBreakerEvent::Shutdown);
+                },
+                next_finalized_block_number = finalized_head_receiver.wait_for(
+                    |finalized_head| *finalized_head >= unprocessed_block_number
                 ) => {
-                    next_finalized_block_number
+                    *next_finalized_block_number?
                 }
             };
 
@@ -112,13 +132,17 @@ impl EthereumListener {
                 .from_block(unprocessed_block_number)
                 .to_block(to_block);
 
+            // a failed query must not disable its branch, or the listener would only wait for a circuit breaker event
             let events = select! {
-                cb_event = circuit_breaker_receiver.recv () => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!(target: LOG_TARGET, "Exiting before sending events due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
-                Ok(events) = query.query() => {
-                    events
+                events = query_events_retrying(
+                    config.finalized_head_backoff(config.eth_block_time()),
+                    || query.query(),
+                ) => {
+                    events?
                 }
             };
 
@@ -130,6 +154,7 @@ impl EthereumListener {
             }
             let (events_ack_sender, events_ack_receiver) = oneshot::channel::<()>();
             info!(target: LOG_TARGET, "Sending a batch of {} events", &events.len());
+            awaiting_handler.send_replace(true);
 
             eth_events_sender
                 .send(EthMostEvents {
@@ -142,9 +167,10 @@ impl EthereumListener {
 
             info!(target: LOG_TARGET, "Awaiting events ack");
 
-            // select between ack and the channel, because the handler could have exited
+            // select between ack and the channel, because the handler could have exited.
+            // A shutdown waits for the ack instead, so that the block number of a handled batch is not lost
             select! {
-                cb_event = circuit_breaker_receiver.recv () => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!(target: LOG_TARGET, "Exiting before events ack due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
@@ -156,6 +182,92 @@ impl EthereumListener {
                     }
                 }
             }
+            awaiting_handler.send_replace(false);
+        }
+    }
+}
+
+/// Queries the events of a batch again after the Ethereum node failed to answer.
+///
+/// The provider holds no connection, so the same query is simply sent again and no block past the failed batch is
+/// processed. Errors which are not the node's, or a node which is not back within the retries of the `backoff`,
+/// fail the listener, which then resumes from the last processed block.
+async fn query_events_retrying<F, Fut>(
+    mut backoff: PollBackoff,
+    mut query: F,
+) -> Result<Vec<MostEvents>, EthereumListenerError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<Vec<MostEvents>, ContractError<Provider<Http>>>>,
+{
+    loop {
+        match query().await {
+            Err(
+                why @ (ContractError::MiddlewareError { .. } | ContractError::ProviderError { .. }),
+            ) if backoff.retries_left() => {
+                let delay = backoff.next_delay();
+                warn!(target: LOG_TARGET, "Querying the Ethereum node failed: {why}, querying the batch again in {delay:?}");
+                sleep(delay).await;
+            }
+            result => return Ok(result?),
+        }
+    }
+}
+
+/// Events of the Ethereum Most contract emitted in the blocks from `from_block` to `to_block`, inclusive
+pub async fn fetch_eth_events_in_block_range(
+    config: &Config,
+    eth_connection: Arc<EthConnection>,
+    from_block: u32,
+    to_block: u32,
+) -> Result<Vec<MostEvents>, EthereumListenerError> {
+    let address = config.eth_contract_address.parse::<Address>()?;
+    let most_eth = Most::new(address, eth_connection);
+
+    Ok(most_eth
+        .events()
+        .from_block(from_block)
+        .to_block(to_block)
+        .query()
+        .await?)
+}
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum EthereumFinalizedHeadListenerError {
+    #[error("broadcast receive error")]
+    BroadcastReceive(#[from] broadcast::error::RecvError),
+}
+
+/// Single poller of the ethereum finalized head.
+///
+/// Every component which needs to wait for finality subscribes to the published value
+/// instead of querying the node on its own, so the node sees one request per block time
+/// no matter how many requests are in flight.
+pub struct EthereumFinalizedHeadListener;
+
+impl EthereumFinalizedHeadListener {
+    pub async fn run(
+        config: Arc<Config>,
+        eth_connection: Arc<EthConnection>,
+        finalized_head_sender: watch::Sender<u32>,
+        mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+    ) -> Result<CircuitBreakerEvent, EthereumFinalizedHeadListenerError> {
+        info!(target: "EthereumFinalizedHeadListener", "Starting");
+
+        select! {
+            cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
+                warn!(target: "EthereumFinalizedHeadListener", "Exiting due to a circuit breaker event {cb_event:?}");
+                Ok(cb_event?)
+            },
+
+            why = poll_finalized_head(
+                || get_finalized_block_number(&*eth_connection, config.eth_finality).map(Ok::<_, Infallible>),
+                config.eth_block_time(),
+                config.finalized_head_backoff(config.eth_block_time()),
+                finalized_head_sender,
+            ) => match why {},
         }
     }
 }
@@ -202,7 +314,7 @@ impl EthereumPausedListener {
             let is_paused_call = most_eth.paused();
 
             select! {
-                cb_event = circuit_breaker_receiver.recv () => {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
                     warn!(target: "EthereumPausedListener","Exiting due to a circuit breaker event {cb_event:?}");
                     return Ok(cb_event?);
                 },
@@ -212,7 +324,7 @@ impl EthereumPausedListener {
                     match is_paused {
                         Ok(is_paused) => {
                             if is_paused {
-                                circuit_breaker_sender.send(CircuitBreakerEvent::BridgeHaltEthereum)?;
+                                open_circuit_breaker(&circuit_breaker_sender, CircuitBreakerEvent::BridgeHaltEthereum);
                                 warn!(target: "EthereumPausedListener",
                                       "Most is paused, exiting");
                                 return Ok(CircuitBreakerEvent::BridgeHaltEthereum);
@@ -222,15 +334,79 @@ impl EthereumPausedListener {
                         Err(why) => {
                             warn!("Exiting due to a connection error {why:?}");
                             let status = CircuitBreakerEvent::EthConnectionError;
-                            circuit_breaker_sender.send(status.clone())?;
+                            open_circuit_breaker(&circuit_breaker_sender, status.clone());
                             return Ok(status.clone());
                         }
                     }
 
                     // sleep before making another query
-                    sleep(Duration::from_secs(ETH_BLOCK_PROD_TIME_SEC)).await;
+                    sleep(config.eth_block_time()).await;
                 }
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Mutex, time::Duration};
+
+    use ethers::providers::ProviderError;
+
+    use super::*;
+
+    const INTERVAL: Duration = Duration::from_secs(1);
+
+    fn node_error() -> ContractError<Provider<Http>> {
+        ContractError::ProviderError {
+            e: ProviderError::CustomError("connection reset".to_owned()),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn batch_is_queried_again_once_the_node_recovers() {
+        let queries = Mutex::new(0);
+
+        let events = query_events_retrying(PollBackoff::new(INTERVAL, INTERVAL, 3), || {
+            let mut queries = queries.lock().unwrap();
+            *queries += 1;
+            let result = match *queries {
+                1 | 2 => Err(node_error()),
+                _ => Ok(Vec::new()),
+            };
+            async move { result }
+        })
+        .await;
+
+        assert!(events.unwrap().is_empty());
+        assert_eq!(*queries.lock().unwrap(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn node_which_is_not_back_fails_the_listener() {
+        let queries = Mutex::new(0);
+
+        let events = query_events_retrying(PollBackoff::new(INTERVAL, INTERVAL, 2), || {
+            *queries.lock().unwrap() += 1;
+            async { Err(node_error()) }
+        })
+        .await;
+
+        assert!(matches!(events, Err(EthereumListenerError::Contract(_))));
+        assert_eq!(*queries.lock().unwrap(), 3);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn contract_errors_are_not_queried_again() {
+        let queries = Mutex::new(0);
+
+        let events = query_events_retrying(PollBackoff::new(INTERVAL, INTERVAL, 2), || {
+            *queries.lock().unwrap() += 1;
+            async { Err(ContractError::ContractNotDeployed) }
+        })
+        .await;
+
+        assert!(matches!(events, Err(EthereumListenerError::Contract(_))));
+        assert_eq!(*queries.lock().unwrap(), 1);
+    }
+}
@@ -5,6 +5,7 @@ use crate::contracts::MostEvents;
 mod advisory;
 mod azero;
 mod eth;
+mod reconciliation;
 
 #[derive(Debug)]
 pub struct EthMostEvents {
@@ -26,3 +27,4 @@ pub use advisory::*;
 pub use azero::*;
 use contracts_azero_client::ContractEvent;
 pub use eth::*;
+pub use reconciliation::*;
@@ -0,0 +1,155 @@
+use std::{str::FromStr, sync::Arc, time::Duration};
+
+use contracts_azero_client::AccountId;
+use ethers::{core::types::Address, types::U256};
+use log::{debug, error, info, warn};
+use thiserror::Error;
+use tokio::{select, sync::broadcast, time::sleep};
+
+use crate::{
+    config::Config,
+    connections::{azero::AzeroWsConnection, eth::EthConnection},
+    contracts::{AzeroContractError, Erc20, MostInstance},
+    helpers::next_circuit_breaker_event,
+    CircuitBreakerEvent,
+};
+
+const LOG_TARGET: &str = "SupplyReconciliationListener";
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum SupplyReconciliationListenerError {
+    #[error("Azero contract error")]
+    AzeroContract(#[from] AzeroContractError),
+
+    #[error("error when parsing ethereum address")]
+    FromHex(#[from] rustc_hex::FromHexError),
+
+    #[error("invalid reconciled token pair {0}")]
+    InvalidTokenPair(String),
+
+    #[error("broadcast receive error")]
+    BroadcastReceive(#[from] broadcast::error::RecvError),
+}
+
+#[derive(Debug, Clone)]
+struct ReconciledToken {
+    azero: AccountId,
+    eth: Address,
+}
+
+/// Periodically compares the amount of tokens bridged to AlephZero with the amount locked on Ethereum.
+///
+/// A mismatch is only reported, as it requires a manual investigation.
+pub struct SupplyReconciliationListener;
+
+impl SupplyReconciliationListener {
+    pub async fn run(
+        config: Arc<Config>,
+        azero_connection: Arc<AzeroWsConnection>,
+        eth_connection: Arc<EthConnection>,
+        mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+    ) -> Result<CircuitBreakerEvent, SupplyReconciliationListenerError> {
+        let Config {
+            azero_contract_address,
+            azero_contract_metadata,
+            azero_ref_time_limit,
+            azero_proof_size_limit,
+            eth_contract_address,
+            reconciled_tokens,
+            reconciliation_interval_sec,
+            ..
+        } = &*config;
+
+        let tokens = reconciled_tokens
+            .iter()
+            .flatten()
+            .map(|pair| parse_token_pair(pair))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let most_azero = MostInstance::new(
+            azero_contract_address,
+            azero_contract_metadata,
+            *azero_ref_time_limit,
+            *azero_proof_size_limit,
+        )?;
+        let most_eth_address = eth_contract_address.parse::<Address>()?;
+
+        info!(target: LOG_TARGET, "Starting with {} reconciled tokens", tokens.len());
+
+        loop {
+            debug!(target: LOG_TARGET, "Ping");
+
+            select! {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
+                    warn!(target: LOG_TARGET, "Exiting due to a circuit breaker event {cb_event:?}");
+                    return Ok(cb_event?);
+                },
+
+                _ = async {
+                    for token in &tokens {
+                        reconcile(&most_azero, &azero_connection, &eth_connection, most_eth_address, token).await;
+                    }
+                    sleep(Duration::from_secs(*reconciliation_interval_sec)).await;
+                } => {}
+            }
+        }
+    }
+}
+
+async fn reconcile(
+    most_azero: &MostInstance,
+    azero_connection: &AzeroWsConnection,
+    eth_connection: &Arc<EthConnection>,
+    most_eth_address: Address,
+    token: &ReconciledToken,
+) {
+    let net_bridged = match most_azero
+        .get_net_bridged(azero_connection, token.azero.clone())
+        .await
+    {
+        Ok(net_bridged) => net_bridged,
+        Err(why) => {
+            warn!(target: LOG_TARGET, "Failed to read the bridged amount of {}: {why:?}", token.azero);
+            return;
+        }
+    };
+
+    let locked = match Erc20::new(token.eth, Arc::clone(eth_connection))
+        .balance_of(most_eth_address)
+        .await
+    {
+        Ok(locked) => locked,
+        Err(why) => {
+            warn!(target: LOG_TARGET, "Failed to read the locked amount of {:?}: {why:?}", token.eth);
+            return;
+        }
+    };
+
+    // Only the tokens originating on Ethereum are locked there, for the others `net_bridged` is not positive.
+    if net_bridged > 0 && U256::from(net_bridged as u128) > locked {
+        error!(
+            target: LOG_TARGET,
+            "Supply mismatch: {net_bridged} of {} bridged to AlephZero, but only {locked} of {:?} locked on Ethereum",
+            token.azero,
+            token.eth
+        );
+    } else {
+        debug!(target: LOG_TARGET, "Supply of {} reconciled: bridged {net_bridged}, locked {locked}", token.azero);
+    }
+}
+
+fn parse_token_pair(pair: &str) -> Result<ReconciledToken, SupplyReconciliationListenerError> {
+    let (azero, eth) =
+        pair.split_once('=')
+            .ok_or(SupplyReconciliationListenerError::InvalidTokenPair(
+                pair.to_string(),
+            ))?;
+
+    Ok(ReconciledToken {
+        azero: AccountId::from_str(azero)
+            .map_err(|_| SupplyReconciliationListenerError::InvalidTokenPair(pair.to_string()))?,
+        eth: eth.parse()?,
+    })
+}
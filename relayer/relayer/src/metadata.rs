@@ -0,0 +1,178 @@
+use std::{fs, path::Path};
+
+use serde_json::Value;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum MetadataError {
+    #[error("Cannot read the metadata file")]
+    Io(#[from] std::io::Error),
+
+    #[error("Metadata is not valid JSON")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Message or event of a contract along with the labels of its arguments, in order
+#[derive(Debug, Clone, Copy)]
+pub struct ExpectedItem {
+    pub label: &'static str,
+    pub args: &'static [&'static str],
+}
+
+const fn item(label: &'static str, args: &'static [&'static str]) -> ExpectedItem {
+    ExpectedItem { label, args }
+}
+
+/// Messages of the AlephZero Most contract the relayer calls
+pub const MOST_MESSAGES: &[ExpectedItem] = &[
+    item(
+        "receive_request",
+        &[
+            "request_hash",
+            "committee_id",
+            "dest_token_address",
+            "amount",
+            "dest_receiver_address",
+            "request_nonce",
+        ],
+    ),
+    item("is_halted", &[]),
+    item("get_current_committee_id", &[]),
+    item("is_in_committee", &["committee_id", "account"]),
+    item(
+        "needs_signature",
+        &["request_hash", "account", "committee_id"],
+    ),
+    item("request_status", &["hashed_request"]),
+    item("get_committee_threshold", &["committee_id"]),
+];
+
+/// Events of the AlephZero Most contract the relayer decodes
+pub const MOST_EVENTS: &[ExpectedItem] = &[
+    item(
+        "CrosschainTransferRequest",
+        &[
+            "committee_id",
+            "dest_token_address",
+            "amount",
+            "dest_receiver_address",
+            "request_nonce",
+        ],
+    ),
+    item("ConfigChanged", &["field", "old", "new"]),
+];
+
+/// Describes every expected message or event which is missing from `metadata` or has different arguments
+pub fn metadata_mismatches(
+    metadata: &Value,
+    messages: &[ExpectedItem],
+    events: &[ExpectedItem],
+) -> Vec<String> {
+    let mut mismatches = section_mismatches(metadata, "messages", "message", messages);
+    mismatches.extend(section_mismatches(metadata, "events", "event", events));
+    mismatches
+}
+
+/// Mismatches of the AlephZero Most contract metadata at `path`
+pub fn most_metadata_mismatches(path: &Path) -> Result<Vec<String>, MetadataError> {
+    let metadata: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+    Ok(metadata_mismatches(&metadata, MOST_MESSAGES, MOST_EVENTS))
+}
+
+fn section_mismatches(
+    metadata: &Value,
+    section: &str,
+    kind: &str,
+    expected: &[ExpectedItem],
+) -> Vec<String> {
+    let items = metadata["spec"][section]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    expected
+        .iter()
+        .filter_map(|ExpectedItem { label, args }| {
+            let Some(found) = items.iter().find(|found| found["label"] == *label) else {
+                return Some(format!("missing {kind} {label}"));
+            };
+            let found_args: Vec<_> = found["args"]
+                .as_array()
+                .map(Vec::as_slice)
+                .unwrap_or_default()
+                .iter()
+                .map(|arg| arg["label"].as_str().unwrap_or_default())
+                .collect();
+
+            (found_args != *args).then(|| {
+                format!(
+                    "{kind} {label} takes ({}), expected ({})",
+                    found_args.join(", "),
+                    args.join(", ")
+                )
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn section(items: &[ExpectedItem]) -> Value {
+        items
+            .iter()
+            .map(|ExpectedItem { label, args }| {
+                json!({
+                    "label": label,
+                    "args": args.iter().map(|arg| json!({ "label": arg, "type": { "type": 0 } })).collect::<Vec<_>>(),
+                })
+            })
+            .collect()
+    }
+
+    fn metadata(messages: &[ExpectedItem], events: &[ExpectedItem]) -> Value {
+        json!({ "spec": { "messages": section(messages), "events": section(events) } })
+    }
+
+    #[test]
+    fn complete_metadata_has_no_mismatches() {
+        let metadata = metadata(MOST_MESSAGES, MOST_EVENTS);
+
+        assert!(metadata_mismatches(&metadata, MOST_MESSAGES, MOST_EVENTS).is_empty());
+    }
+
+    #[test]
+    fn trimmed_metadata_is_reported() {
+        let messages: Vec<_> = MOST_MESSAGES
+            .iter()
+            .copied()
+            .filter(|message| message.label != "is_halted")
+            .map(|message| match message.label {
+                "is_in_committee" => item("is_in_committee", &["committee_id"]),
+                _ => message,
+            })
+            .collect();
+        let metadata = metadata(&messages, &MOST_EVENTS[..1]);
+
+        assert_eq!(
+            metadata_mismatches(&metadata, MOST_MESSAGES, MOST_EVENTS),
+            [
+                "missing message is_halted",
+                "message is_in_committee takes (committee_id), expected (committee_id, account)",
+                "missing event ConfigChanged",
+            ]
+        );
+    }
+
+    #[test]
+    fn metadata_without_spec_misses_everything() {
+        let mismatches = metadata_mismatches(&json!({}), MOST_MESSAGES, MOST_EVENTS);
+
+        assert_eq!(mismatches.len(), MOST_MESSAGES.len() + MOST_EVENTS.len());
+    }
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::BTreeMap,
+    fmt::{self, Write},
+    sync::Mutex,
+    time::Duration,
+};
+
+/// Upper bounds of the buckets of the duration histograms, in seconds
+const DURATION_BUCKETS_SECS: [u64; 9] = [1, 5, 15, 30, 60, 120, 300, 900, 3600];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricKind {
+    Gauge,
+    Counter,
+}
+
+/// Current value of a metric, the same for every export format
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub name: &'static str,
+    pub kind: MetricKind,
+    pub label: Option<(&'static str, &'static str)>,
+    pub value: u64,
+}
+
+/// Position of the relayer's account in the current committee, used to tell the guardians apart in the logs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Guardian(pub Option<usize>);
+
+impl fmt::Display for Guardian {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(index) => write!(f, "guardian {index}"),
+            None => write!(f, "guardian (index unknown)"),
+        }
+    }
+}
+
+/// Observed durations, bucketed the way Prometheus expects them
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+struct Histogram {
+    buckets: [u64; DURATION_BUCKETS_SECS.len()],
+    sum_millis: u64,
+    count: u64,
+}
+
+impl Histogram {
+    fn observe(&mut self, duration: Duration) {
+        for (bucket, bound) in self.buckets.iter_mut().zip(DURATION_BUCKETS_SECS) {
+            if duration <= Duration::from_secs(bound) {
+                *bucket += 1;
+            }
+        }
+        self.sum_millis += duration.as_millis() as u64;
+        self.count += 1;
+    }
+
+    fn render(&self, rendered: &mut String, name: &str, label: &str, label_value: &str) {
+        for (bucket, bound) in self.buckets.iter().zip(DURATION_BUCKETS_SECS) {
+            let _ = writeln!(
+                rendered,
+                "{name}_bucket{{{label}=\"{label_value}\",le=\"{bound}\"}} {bucket}"
+            );
+        }
+        let _ = writeln!(
+            rendered,
+            "{name}_bucket{{{label}=\"{label_value}\",le=\"+Inf\"}} {}",
+            self.count
+        );
+        let _ = writeln!(
+            rendered,
+            "{name}_sum{{{label}=\"{label_value}\"}} {}.{:03}",
+            self.sum_millis / 1000,
+            self.sum_millis % 1000
+        );
+        let _ = writeln!(
+            rendered,
+            "{name}_count{{{label}=\"{label_value}\"}} {}",
+            self.count
+        );
+    }
+}
+
+/// Counters and histograms exposed on the admin `/metrics` endpoint, in the Prometheus text format
+#[derive(Default)]
+pub struct Metrics {
+    listener_restarts: Mutex<BTreeMap<&'static str, u64>>,
+    duplicate_requests: Mutex<BTreeMap<&'static str, u64>>,
+    unnamed_events: Mutex<BTreeMap<&'static str, u64>>,
+    events_seen: Mutex<BTreeMap<&'static str, u64>>,
+    requests_relayed: Mutex<BTreeMap<&'static str, u64>>,
+    relay_failures: Mutex<BTreeMap<&'static str, u64>>,
+    relay_latency: Mutex<BTreeMap<&'static str, Histogram>>,
+    finality_wait: Mutex<BTreeMap<&'static str, Histogram>>,
+    guardian_index: Mutex<Option<usize>>,
+}
+
+impl Metrics {
+    pub fn record_listener_restart(&self, listener: &'static str) {
+        *self
+            .listener_restarts
+            .lock()
+            .expect("mutex lock")
+            .entry(listener)
+            .or_default() += 1;
+    }
+
+    pub fn record_duplicate_request(&self, chain: &'static str) {
+        *self
+            .duplicate_requests
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default() += 1;
+    }
+
+    pub fn record_unnamed_event(&self, chain: &'static str) {
+        *self
+            .unnamed_events
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default() += 1;
+    }
+
+    /// A transfer request event about to be handled, duplicates included
+    pub fn record_event_seen(&self, chain: &'static str) {
+        *self
+            .events_seen
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default() += 1;
+    }
+
+    /// A request signed by this guardian, along with how long it took since its event was handled and how much
+    /// of that was spent waiting for the finality of the signature
+    pub fn record_request_relayed(
+        &self,
+        chain: &'static str,
+        latency: Duration,
+        finality_wait: Duration,
+    ) {
+        *self
+            .requests_relayed
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default() += 1;
+        self.relay_latency
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default()
+            .observe(latency);
+        self.finality_wait
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default()
+            .observe(finality_wait);
+    }
+
+    pub fn record_relay_failure(&self, chain: &'static str) {
+        *self
+            .relay_failures
+            .lock()
+            .expect("mutex lock")
+            .entry(chain)
+            .or_default() += 1;
+    }
+
+    /// Returns the previous index, so that a change can be reported
+    pub fn set_guardian_index(&self, index: Option<usize>) -> Option<usize> {
+        std::mem::replace(&mut *self.guardian_index.lock().expect("mutex lock"), index)
+    }
+
+    pub fn guardian(&self) -> Guardian {
+        Guardian(*self.guardian_index.lock().expect("mutex lock"))
+    }
+
+    pub fn samples(&self, redis_up: bool) -> Vec<Sample> {
+        let mut samples = vec![Sample {
+            name: "redis_up",
+            kind: MetricKind::Gauge,
+            label: None,
+            value: u64::from(redis_up),
+        }];
+
+        if let Guardian(Some(index)) = self.guardian() {
+            samples.push(Sample {
+                name: "guardian_index",
+                kind: MetricKind::Gauge,
+                label: None,
+                value: index as u64,
+            });
+        }
+
+        let counters = [
+            (
+                "listener_restarts_total",
+                "listener",
+                &self.listener_restarts,
+            ),
+            (
+                "duplicate_requests_total",
+                "chain",
+                &self.duplicate_requests,
+            ),
+            ("unnamed_events_total", "chain", &self.unnamed_events),
+            ("events_seen_total", "chain", &self.events_seen),
+            ("requests_relayed_total", "chain", &self.requests_relayed),
+            ("relay_failures_total", "chain", &self.relay_failures),
+        ];
+        for (name, label, counts) in counters {
+            for (&label_value, &value) in counts.lock().expect("mutex lock").iter() {
+                samples.push(Sample {
+                    name,
+                    kind: MetricKind::Counter,
+                    label: Some((label, label_value)),
+                    value,
+                });
+            }
+        }
+
+        samples
+    }
+
+    pub fn render(&self, redis_up: bool) -> String {
+        let mut rendered = String::new();
+
+        for Sample {
+            name, label, value, ..
+        } in self.samples(redis_up)
+        {
+            let _ = match label {
+                Some((label, label_value)) => {
+                    writeln!(rendered, "{name}{{{label}=\"{label_value}\"}} {value}")
+                }
+                None => writeln!(rendered, "{name} {value}"),
+            };
+        }
+
+        let histograms = [
+            ("relay_latency_seconds", &self.relay_latency),
+            ("finality_wait_seconds", &self.finality_wait),
+        ];
+        for (name, histograms) in histograms {
+            for (chain, histogram) in histograms.lock().expect("mutex lock").iter() {
+                histogram.render(&mut rendered, name, "chain", chain);
+            }
+        }
+
+        rendered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relayed_requests_are_rendered_as_histograms() {
+        let metrics = Metrics::default();
+        metrics.record_event_seen("Ethereum");
+        metrics.record_request_relayed(
+            "Ethereum",
+            Duration::from_millis(20_500),
+            Duration::from_secs(12),
+        );
+
+        let rendered = metrics.render(true);
+
+        assert!(rendered.contains("events_seen_total{chain=\"Ethereum\"} 1\n"));
+        assert!(rendered.contains("requests_relayed_total{chain=\"Ethereum\"} 1\n"));
+        assert!(rendered.contains("relay_latency_seconds_bucket{chain=\"Ethereum\",le=\"15\"} 0\n"));
+        assert!(rendered.contains("relay_latency_seconds_bucket{chain=\"Ethereum\",le=\"30\"} 1\n"));
+        assert!(
+            rendered.contains("relay_latency_seconds_bucket{chain=\"Ethereum\",le=\"+Inf\"} 1\n")
+        );
+        assert!(rendered.contains("relay_latency_seconds_sum{chain=\"Ethereum\"} 20.500\n"));
+        assert!(rendered.contains("finality_wait_seconds_bucket{chain=\"Ethereum\",le=\"15\"} 1\n"));
+        assert!(rendered.contains("finality_wait_seconds_count{chain=\"Ethereum\"} 1\n"));
+    }
+
+    #[test]
+    fn nothing_is_rendered_for_chains_without_requests() {
+        let metrics = Metrics::default();
+        metrics.record_relay_failure("AlephZero");
+
+        assert_eq!(
+            metrics.render(true),
+            "redis_up 1\nrelay_failures_total{chain=\"AlephZero\"} 1\n"
+        );
+    }
+}
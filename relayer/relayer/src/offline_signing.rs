@@ -0,0 +1,343 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use ethers::{
+    providers::{Middleware, ProviderError},
+    types::{
+        transaction::eip2718::TypedTransaction, Address, Bytes, TransactionRequest, H256, U256, U64,
+    },
+};
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::{
+    select,
+    sync::broadcast,
+    time::{sleep, Duration},
+};
+
+use crate::{
+    connections::eth::EthConnection, helpers::next_circuit_breaker_event, CircuitBreakerEvent,
+};
+
+const UNSIGNED_DIR: &str = "unsigned";
+const SIGNED_DIR: &str = "signed";
+const BROADCAST_DIR: &str = "broadcast";
+const FAILED_DIR: &str = "failed";
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum OfflineSigningError {
+    #[error("IO error")]
+    Io(#[from] io::Error),
+
+    #[error("JSON error")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Error when decoding a hex encoded signed transaction")]
+    FromHex(#[from] hex::FromHexError),
+
+    #[error("Ethers provider error")]
+    Provider(#[from] ProviderError),
+
+    #[error("broadcast receive error")]
+    BroadcastReceive(#[from] broadcast::error::RecvError),
+}
+
+/// Fully prepared `receive_request` transaction, which only needs to be signed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UnsignedTx {
+    pub to: Address,
+    pub data: Bytes,
+    pub value: U256,
+    pub chain_id: U64,
+    pub nonce: U256,
+    pub gas: U256,
+    pub gas_price: U256,
+}
+
+impl From<&UnsignedTx> for TypedTransaction {
+    fn from(tx: &UnsignedTx) -> Self {
+        TransactionRequest::new()
+            .to(tx.to)
+            .data(tx.data.clone())
+            .value(tx.value)
+            .chain_id(tx.chain_id)
+            .nonce(tx.nonce)
+            .gas(tx.gas)
+            .gas_price(tx.gas_price)
+            .into()
+    }
+}
+
+fn unsigned_tx_path(dir: &Path, request_hash_hex: &str) -> PathBuf {
+    dir.join(UNSIGNED_DIR)
+        .join(format!("0x{request_hash_hex}.json"))
+}
+
+/// Whether the transaction for the request has already been handed out to the offline signer
+pub fn unsigned_tx_exists(dir: &Path, request_hash_hex: &str) -> bool {
+    unsigned_tx_path(dir, request_hash_hex).exists()
+}
+
+/// Writes the transaction to `{dir}/unsigned/0x{request_hash}.json` for an offline signer to pick up.
+///
+/// Returns `false` without overwriting anything if the request has already been written out,
+/// so that the nonce handed out to the signer stays the same.
+pub fn write_unsigned_tx(
+    dir: &Path,
+    request_hash_hex: &str,
+    tx: &UnsignedTx,
+) -> Result<bool, OfflineSigningError> {
+    fs::create_dir_all(dir.join(UNSIGNED_DIR))?;
+
+    let path = unsigned_tx_path(dir, request_hash_hex);
+    if path.exists() {
+        return Ok(false);
+    }
+
+    // write to a temporary file first, so that the signer never sees a partially written tx
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, serde_json::to_vec_pretty(tx)?)?;
+    fs::rename(tmp_path, path)?;
+
+    Ok(true)
+}
+
+/// Broadcasts every hex encoded signed transaction found in `{dir}/signed`, then moves it to `{dir}/broadcast`.
+///
+/// A transaction which can't be decoded or is rejected by the node is moved to `{dir}/failed` for the operator
+/// instead, so that it does not hold up the others nor fail the broadcaster on every restart.
+pub async fn broadcast_signed_txs<F, Fut>(
+    dir: &Path,
+    mut broadcast: F,
+) -> Result<Vec<H256>, OfflineSigningError>
+where
+    F: FnMut(Bytes) -> Fut,
+    Fut: std::future::Future<Output = Result<H256, ProviderError>>,
+{
+    let signed_dir = dir.join(SIGNED_DIR);
+    let broadcast_dir = dir.join(BROADCAST_DIR);
+    let failed_dir = dir.join(FAILED_DIR);
+    fs::create_dir_all(&signed_dir)?;
+    fs::create_dir_all(&broadcast_dir)?;
+    fs::create_dir_all(&failed_dir)?;
+
+    let mut paths = fs::read_dir(&signed_dir)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()?;
+    paths.retain(|path| path.extension().is_some_and(|extension| extension == "hex"));
+    paths.sort();
+
+    let mut tx_hashes = Vec::new();
+    for path in paths {
+        let file_name = path.file_name().expect("read_dir entries have file names");
+
+        match broadcast_signed_tx(&path, &mut broadcast).await {
+            Ok(tx_hash) => {
+                info!("Broadcast offline signed tx {tx_hash:?} from {path:?}");
+                fs::rename(&path, broadcast_dir.join(file_name))?;
+                tx_hashes.push(tx_hash);
+            }
+            Err(why) => {
+                error!("Failed to broadcast the offline signed tx from {path:?}, moving it to {failed_dir:?}: {why:?}");
+                fs::rename(&path, failed_dir.join(file_name))?;
+            }
+        }
+    }
+
+    Ok(tx_hashes)
+}
+
+async fn broadcast_signed_tx<F, Fut>(
+    path: &Path,
+    broadcast: &mut F,
+) -> Result<H256, OfflineSigningError>
+where
+    F: FnMut(Bytes) -> Fut,
+    Fut: std::future::Future<Output = Result<H256, ProviderError>>,
+{
+    let raw_tx = hex::decode(fs::read_to_string(path)?.trim().trim_start_matches("0x"))?;
+    Ok(broadcast(raw_tx.into()).await?)
+}
+
+pub struct SignedTxBroadcaster;
+
+impl SignedTxBroadcaster {
+    pub async fn run(
+        dir: PathBuf,
+        poll_interval: Duration,
+        eth_connection: Arc<EthConnection>,
+        mut circuit_breaker_receiver: broadcast::Receiver<CircuitBreakerEvent>,
+    ) -> Result<CircuitBreakerEvent, OfflineSigningError> {
+        info!("Watching {:?} for offline signed txs", dir.join(SIGNED_DIR));
+
+        loop {
+            debug!("Ping");
+
+            select! {
+                cb_event = next_circuit_breaker_event(&mut circuit_breaker_receiver) => {
+                    warn!("Exiting due to a circuit breaker event {cb_event:?}");
+                    return Ok(cb_event?);
+                },
+
+                result = broadcast_signed_txs(&dir, |raw_tx| {
+                    let eth_connection = Arc::clone(&eth_connection);
+                    async move {
+                        Ok(*eth_connection.send_raw_transaction(raw_tx).await?)
+                    }
+                }) => {
+                    result?;
+                }
+            }
+
+            sleep(poll_interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use ethers::{
+        signers::{LocalWallet, Signer},
+        utils::rlp::Rlp,
+    };
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "relayer-offline-signing-{name}-{}",
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn unsigned_tx() -> UnsignedTx {
+        UnsignedTx {
+            to: Address::repeat_byte(1),
+            data: Bytes::from(vec![1, 2, 3]),
+            value: U256::zero(),
+            chain_id: 1337.into(),
+            nonce: 7.into(),
+            gas: 200_000.into(),
+            gas_price: 1_000_000_000.into(),
+        }
+    }
+
+    #[test]
+    fn unsigned_tx_is_written_only_once() {
+        let dir = test_dir("write-once");
+        let tx = unsigned_tx();
+
+        assert!(!unsigned_tx_exists(&dir, "01"));
+        assert!(write_unsigned_tx(&dir, "01", &tx).unwrap());
+        assert!(unsigned_tx_exists(&dir, "01"));
+        assert!(!write_unsigned_tx(
+            &dir,
+            "01",
+            &UnsignedTx {
+                nonce: 8.into(),
+                ..tx.clone()
+            }
+        )
+        .unwrap());
+
+        let written: UnsignedTx =
+            serde_json::from_slice(&fs::read(dir.join(UNSIGNED_DIR).join("0x01.json")).unwrap())
+                .unwrap();
+        assert_eq!(written, tx);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn offline_signed_tx_round_trip() {
+        let dir = test_dir("round-trip");
+        let tx = unsigned_tx();
+        write_unsigned_tx(&dir, "01", &tx).unwrap();
+
+        // the offline signer picks up the unsigned tx and drops off the signed one
+        let wallet = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318"
+            .parse::<LocalWallet>()
+            .unwrap()
+            .with_chain_id(1337u64);
+        let unsigned: UnsignedTx =
+            serde_json::from_slice(&fs::read(dir.join(UNSIGNED_DIR).join("0x01.json")).unwrap())
+                .unwrap();
+        let typed_tx = TypedTransaction::from(&unsigned);
+        let signature = wallet.sign_transaction_sync(&typed_tx).unwrap();
+        let raw_tx = typed_tx.rlp_signed(&signature);
+        fs::create_dir_all(dir.join(SIGNED_DIR)).unwrap();
+        fs::write(
+            dir.join(SIGNED_DIR).join("0x01.hex"),
+            format!("0x{}", hex::encode(&raw_tx)),
+        )
+        .unwrap();
+
+        let broadcast = Mutex::new(Vec::new());
+        let tx_hashes = broadcast_signed_txs(&dir, |raw_tx| {
+            broadcast.lock().unwrap().push(raw_tx.clone());
+            async move { Ok(H256::from(ethers::utils::keccak256(&raw_tx))) }
+        })
+        .await
+        .unwrap();
+
+        let broadcast = broadcast.into_inner().unwrap();
+        assert_eq!(broadcast, vec![raw_tx.clone()]);
+        assert_eq!(tx_hashes, vec![typed_tx.hash(&signature)]);
+
+        let (decoded, decoded_signature) =
+            TypedTransaction::decode_signed(&Rlp::new(&broadcast[0])).unwrap();
+        assert_eq!(
+            decoded_signature.recover(decoded.sighash()).unwrap(),
+            wallet.address()
+        );
+        assert_eq!(decoded.data(), Some(&tx.data));
+        assert_eq!(decoded.nonce(), Some(&tx.nonce));
+
+        // broadcast txs are moved out of the way, so they are not sent again
+        assert!(!dir.join(SIGNED_DIR).join("0x01.hex").exists());
+        assert!(dir.join(BROADCAST_DIR).join("0x01.hex").exists());
+        assert!(broadcast_signed_txs(&dir, |_| async { unreachable!() })
+            .await
+            .unwrap()
+            .is_empty());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failing_tx_is_set_aside_and_the_others_are_broadcast() {
+        let dir = test_dir("failing");
+        let signed_dir = dir.join(SIGNED_DIR);
+        fs::create_dir_all(&signed_dir).unwrap();
+        fs::write(signed_dir.join("0x01.hex"), "0xnot-hex").unwrap();
+        fs::write(signed_dir.join("0x02.hex"), "0x0102").unwrap();
+        fs::write(signed_dir.join("0x03.hex"), "0x0303").unwrap();
+
+        let tx_hashes = broadcast_signed_txs(&dir, |raw_tx| async move {
+            match raw_tx.as_ref() {
+                [3, 3] => Err(ProviderError::CustomError("nonce too low".to_owned())),
+                _ => Ok(H256::repeat_byte(2)),
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(tx_hashes, vec![H256::repeat_byte(2)]);
+        assert!(dir.join(BROADCAST_DIR).join("0x02.hex").exists());
+        assert!(dir.join(FAILED_DIR).join("0x01.hex").exists());
+        assert!(dir.join(FAILED_DIR).join("0x03.hex").exists());
+        assert_eq!(fs::read_dir(&signed_dir).unwrap().count(), 0);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
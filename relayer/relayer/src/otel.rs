@@ -0,0 +1,365 @@
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use log::{info, warn};
+use serde_json::{json, Value};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    time::{interval, timeout, Duration},
+};
+
+use crate::{
+    config::Config,
+    metrics::{MetricKind, Metrics, Sample},
+    redis::SharedRedisConnection,
+};
+
+const LOG_TARGET: &str = "OtlpExporter";
+const DEFAULT_METRICS_PATH: &str = "/v1/metrics";
+const MAX_RESPONSE_SIZE: usize = 8192;
+// `AGGREGATION_TEMPORALITY_CUMULATIVE` in the OTLP protocol
+const CUMULATIVE: u8 = 2;
+
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum OtlpExporterError {
+    #[error("IO error")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid OTLP endpoint `{0}`, expected `http://host:port[/path]`")]
+    InvalidEndpoint(String),
+
+    #[error("Collector rejected the metrics: {0}")]
+    Rejected(String),
+
+    #[error("Collector did not answer within {0:?}")]
+    Timeout(Duration),
+}
+
+/// Collector accepting the metrics over OTLP/HTTP with JSON encoding
+#[derive(Debug, PartialEq, Eq)]
+struct Endpoint {
+    authority: String,
+    path: String,
+}
+
+impl Endpoint {
+    fn parse(endpoint: &str) -> Result<Self, OtlpExporterError> {
+        let invalid = || OtlpExporterError::InvalidEndpoint(endpoint.to_owned());
+
+        let rest = endpoint.strip_prefix("http://").ok_or_else(invalid)?;
+        let (authority, path) = match rest.find('/') {
+            Some(path_start) => rest.split_at(path_start),
+            None => (rest, ""),
+        };
+        if authority.is_empty() {
+            return Err(invalid());
+        }
+
+        Ok(Self {
+            authority: authority.to_owned(),
+            path: match path {
+                "" | "/" => DEFAULT_METRICS_PATH.to_owned(),
+                path => path.to_owned(),
+            },
+        })
+    }
+}
+
+/// Periodically pushes the metrics served on the admin `/metrics` endpoint to an OpenTelemetry collector
+pub struct OtlpExporter;
+
+impl OtlpExporter {
+    /// A failed push is only logged, the metrics are pushed again on the next tick anyway
+    pub async fn run(
+        config: Arc<Config>,
+        endpoint: String,
+        redis_connection: Arc<SharedRedisConnection>,
+        metrics: Arc<Metrics>,
+    ) -> Result<(), OtlpExporterError> {
+        let endpoint = Endpoint::parse(&endpoint)?;
+        let start_time = unix_nanos();
+        let export_interval = Duration::from_secs(config.otlp_export_interval_sec);
+        let mut ticker = interval(export_interval);
+        info!(
+            target: LOG_TARGET,
+            "Pushing metrics to {}{}", endpoint.authority, endpoint.path
+        );
+
+        loop {
+            ticker.tick().await;
+
+            let body = payload(
+                &config.name,
+                metrics.samples(redis_connection.is_up()),
+                start_time,
+                unix_nanos(),
+            )
+            .to_string();
+            // given up on by the time the next push is due
+            if let Err(why) = export_within(&endpoint, body.as_bytes(), export_interval).await {
+                warn!(target: LOG_TARGET, "Failed to push the metrics: {why:?}");
+            }
+        }
+    }
+}
+
+fn unix_nanos() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_nanos())
+        .unwrap_or_default()
+}
+
+/// `ExportMetricsServiceRequest` in the JSON encoding, one metric per sample name.
+///
+/// Counters are cumulative since `start_time`, when the relayer was started.
+fn payload(service_instance: &str, samples: Vec<Sample>, start_time: u128, time: u128) -> Value {
+    let mut metrics: Vec<(&'static str, MetricKind, Vec<Value>)> = Vec::new();
+
+    for Sample {
+        name,
+        kind,
+        label,
+        value,
+    } in samples
+    {
+        let attributes: Vec<_> = label
+            .into_iter()
+            .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+            .collect();
+        // 64 bit integers are strings in the JSON encoding
+        let data_point = json!({
+            "attributes": attributes,
+            "startTimeUnixNano": start_time.to_string(),
+            "timeUnixNano": time.to_string(),
+            "asInt": value.to_string(),
+        });
+
+        match metrics.iter_mut().find(|(metric, ..)| *metric == name) {
+            Some((.., data_points)) => data_points.push(data_point),
+            None => metrics.push((name, kind, vec![data_point])),
+        }
+    }
+
+    let metrics: Vec<_> = metrics
+        .into_iter()
+        .map(|(name, kind, data_points)| match kind {
+            MetricKind::Gauge => json!({ "name": name, "gauge": { "dataPoints": data_points } }),
+            MetricKind::Counter => json!({
+                "name": name,
+                "sum": {
+                    "dataPoints": data_points,
+                    "aggregationTemporality": CUMULATIVE,
+                    "isMonotonic": true,
+                },
+            }),
+        })
+        .collect();
+
+    json!({
+        "resourceMetrics": [{
+            "resource": {
+                "attributes": [
+                    { "key": "service.name", "value": { "stringValue": "relayer" } },
+                    { "key": "service.instance.id", "value": { "stringValue": service_instance } },
+                ],
+            },
+            "scopeMetrics": [{
+                "scope": { "name": "relayer" },
+                "metrics": metrics,
+            }],
+        }],
+    })
+}
+
+/// Pushes the metrics, giving up on a collector which does not answer within `bound`
+async fn export_within(
+    endpoint: &Endpoint,
+    body: &[u8],
+    bound: Duration,
+) -> Result<(), OtlpExporterError> {
+    timeout(bound, export(endpoint, body))
+        .await
+        .unwrap_or(Err(OtlpExporterError::Timeout(bound)))
+}
+
+async fn export(endpoint: &Endpoint, body: &[u8]) -> Result<(), OtlpExporterError> {
+    let mut stream = TcpStream::connect(&endpoint.authority).await?;
+    let head = format!(
+        "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        endpoint.path,
+        endpoint.authority,
+        body.len()
+    );
+    stream.write_all(head.as_bytes()).await?;
+    stream.write_all(body).await?;
+
+    // the status line may arrive split across several reads
+    let mut response = Vec::new();
+    let mut buffer = [0; 1024];
+    while !response.windows(2).any(|window| window == b"\r\n") && response.len() < MAX_RESPONSE_SIZE
+    {
+        let read = stream.read(&mut buffer).await?;
+        if read == 0 {
+            break;
+        }
+        response.extend_from_slice(&buffer[..read]);
+    }
+    let response = String::from_utf8_lossy(&response);
+    let status_line = response.lines().next().unwrap_or_default();
+
+    match status_line.split_whitespace().nth(1) {
+        Some(status) if status.starts_with('2') => Ok(()),
+        _ => Err(OtlpExporterError::Rejected(status_line.to_owned())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    /// Accepts a single push and answers it with `status`, returning the head and the body of the request
+    async fn receive(collector: &TcpListener, status: &str) -> (String, String) {
+        let (mut stream, _) = collector.accept().await.unwrap();
+        let request = read_request(&mut stream).await;
+        let response = format!("HTTP/1.1 {status}\r\nContent-Length: 0\r\n\r\n");
+        stream.write_all(response.as_bytes()).await.unwrap();
+        request
+    }
+
+    async fn read_request(stream: &mut TcpStream) -> (String, String) {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+
+        loop {
+            let read = stream.read(&mut buffer).await.unwrap();
+            request.extend_from_slice(&buffer[..read]);
+
+            let received = String::from_utf8_lossy(&request).to_string();
+            if let Some((head, body)) = received.split_once("\r\n\r\n") {
+                let length: usize = head
+                    .lines()
+                    .find_map(|line| line.strip_prefix("Content-Length: "))
+                    .unwrap()
+                    .parse()
+                    .unwrap();
+                if body.len() >= length || read == 0 {
+                    return (head.to_owned(), body.to_owned());
+                }
+            }
+        }
+    }
+
+    async fn collector() -> (TcpListener, Endpoint) {
+        let collector = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let endpoint =
+            Endpoint::parse(&format!("http://{}", collector.local_addr().unwrap())).unwrap();
+        (collector, endpoint)
+    }
+
+    #[tokio::test]
+    async fn metric_updates_are_pushed_to_the_collector() {
+        let (collector, endpoint) = collector().await;
+        let metrics = Metrics::default();
+        metrics.record_listener_restart("AlephZeroListener");
+        metrics.record_listener_restart("AlephZeroListener");
+
+        let body = payload("guardian_1", metrics.samples(true), 1, 2).to_string();
+        let (exported, (head, body)) = tokio::join!(
+            export(&endpoint, body.as_bytes()),
+            receive(&collector, "200 OK")
+        );
+        exported.unwrap();
+
+        assert!(head.starts_with("POST /v1/metrics HTTP/1.1"));
+        let received: Value = serde_json::from_str(&body).unwrap();
+        let resource = &received["resourceMetrics"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][1]["value"]["stringValue"],
+            "guardian_1"
+        );
+
+        let metrics = &resource["scopeMetrics"][0]["metrics"];
+        assert_eq!(metrics[0]["name"], "redis_up");
+        assert_eq!(metrics[0]["gauge"]["dataPoints"][0]["asInt"], "1");
+        assert_eq!(metrics[1]["name"], "listener_restarts_total");
+        assert_eq!(metrics[1]["sum"]["isMonotonic"], true);
+        let data_point = &metrics[1]["sum"]["dataPoints"][0];
+        assert_eq!(data_point["asInt"], "2");
+        assert_eq!(data_point["startTimeUnixNano"], "1");
+        assert_eq!(
+            data_point["attributes"][0],
+            json!({ "key": "listener", "value": { "stringValue": "AlephZeroListener" } })
+        );
+    }
+
+    #[tokio::test]
+    async fn rejected_push_is_an_error() {
+        let (collector, endpoint) = collector().await;
+
+        let (exported, _) = tokio::join!(
+            export(&endpoint, b"{}"),
+            receive(&collector, "400 Bad Request")
+        );
+
+        assert!(matches!(exported, Err(OtlpExporterError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    async fn status_line_split_across_reads_is_put_together() {
+        let (collector, endpoint) = collector().await;
+
+        let (exported, ()) = tokio::join!(export(&endpoint, b"{}"), async {
+            let (mut stream, _) = collector.accept().await.unwrap();
+            read_request(&mut stream).await;
+            stream.write_all(b"HTTP/1.1 2").await.unwrap();
+            stream.flush().await.unwrap();
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            stream
+                .write_all(b"00 OK\r\nContent-Length: 0\r\n\r\n")
+                .await
+                .unwrap();
+        });
+
+        exported.unwrap();
+    }
+
+    #[tokio::test]
+    async fn silent_collector_is_given_up_on() {
+        let (collector, endpoint) = collector().await;
+        let bound = Duration::from_millis(100);
+
+        // the connection is accepted and kept open, but never answered
+        let (exported, _stream) =
+            tokio::join!(export_within(&endpoint, b"{}", bound), collector.accept());
+
+        assert!(matches!(exported, Err(OtlpExporterError::Timeout(_))));
+    }
+
+    #[test]
+    fn endpoint_defaults_to_the_metrics_path() {
+        assert_eq!(
+            Endpoint::parse("http://collector:4318").unwrap(),
+            Endpoint {
+                authority: "collector:4318".to_owned(),
+                path: DEFAULT_METRICS_PATH.to_owned(),
+            }
+        );
+        assert_eq!(
+            Endpoint::parse("http://collector:4318/otlp/v1/metrics")
+                .unwrap()
+                .path,
+            "/otlp/v1/metrics"
+        );
+        assert!(Endpoint::parse("https://collector:4318").is_err());
+        assert!(Endpoint::parse("http:///v1/metrics").is_err());
+    }
+}
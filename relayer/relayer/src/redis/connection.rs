@@ -0,0 +1,273 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+use log::{info, warn};
+use redis::{Connection, ConnectionLike, RedisError};
+use tokio::{
+    runtime::{Handle, RuntimeFlavor},
+    task::block_in_place,
+};
+
+type Connector<C> = Box<dyn Fn() -> Result<C, RedisError> + Send + Sync>;
+
+/// Redis connection shared by all the components, re-established whenever it turns out to be broken.
+///
+/// A dropped connection does not fail the operation right away: it is reconnected and the operation is retried once.
+/// Whether redis is reachable is tracked based on the last operation, including the periodic [`Self::ping`].
+pub struct SharedRedisConnection<C = Connection> {
+    connect: Connector<C>,
+    connection: Mutex<Option<C>>,
+    up: AtomicBool,
+}
+
+impl<C: ConnectionLike> SharedRedisConnection<C> {
+    /// Connects right away, so that a misconfigured redis is reported at startup
+    pub fn new(
+        connect: impl Fn() -> Result<C, RedisError> + Send + Sync + 'static,
+    ) -> Result<Self, RedisError> {
+        let connection = connect()?;
+
+        Ok(Self {
+            connect: Box::new(connect),
+            connection: Mutex::new(Some(connection)),
+            up: AtomicBool::new(true),
+        })
+    }
+
+    pub fn is_up(&self) -> bool {
+        self.up.load(Ordering::Relaxed)
+    }
+
+    /// Runs `operation` on the connection, reconnecting first if it was lost.
+    ///
+    /// The operation may be executed twice, so it has to be idempotent - e.g. a `SET` of a block number.
+    /// Connecting and the commands block, so on a worker thread its other tasks are handed over for the time being,
+    /// not to stall the whole runtime while redis is down.
+    pub fn with<T>(
+        &self,
+        operation: impl FnMut(&mut C) -> Result<T, RedisError>,
+    ) -> Result<T, RedisError> {
+        match Handle::try_current() {
+            Ok(runtime) if runtime.runtime_flavor() == RuntimeFlavor::MultiThread => {
+                block_in_place(|| self.with_blocking(operation))
+            }
+            _ => self.with_blocking(operation),
+        }
+    }
+
+    fn with_blocking<T>(
+        &self,
+        mut operation: impl FnMut(&mut C) -> Result<T, RedisError>,
+    ) -> Result<T, RedisError> {
+        let mut connection = self.connection.lock().expect("mutex lock");
+        let mut retried = false;
+
+        loop {
+            let result = match connection.as_mut() {
+                Some(established) => operation(established),
+                None => (self.connect)().and_then(|established| {
+                    info!("Redis connection re-established");
+                    operation(connection.insert(established))
+                }),
+            };
+
+            match result {
+                Err(why) if is_connection_failure(&why) => {
+                    *connection = None;
+
+                    if !retried {
+                        warn!("Redis connection lost: {why:?}, reconnecting");
+                        retried = true;
+                        continue;
+                    }
+
+                    self.up.store(false, Ordering::Relaxed);
+                    return Err(why);
+                }
+                result => {
+                    self.up.store(true, Ordering::Relaxed);
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Health check, also re-establishes the connection if needed
+    pub fn ping(&self) -> bool {
+        self.with(|connection| redis::cmd("PING").query::<String>(connection))
+            .inspect_err(|why| warn!("Redis health check failed: {why:?}"))
+            .is_ok()
+    }
+}
+
+fn is_connection_failure(error: &RedisError) -> bool {
+    error.is_connection_dropped() || error.is_connection_refusal() || error.is_io_error()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::{atomic::AtomicU32, Arc},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use redis::{RedisResult, Value};
+
+    use super::*;
+
+    /// Answers every command with its id, until it is dropped
+    struct FakeConnection {
+        id: i64,
+        dropped: AtomicBool,
+    }
+
+    impl ConnectionLike for FakeConnection {
+        fn req_packed_command(&mut self, _cmd: &[u8]) -> RedisResult<Value> {
+            if self.dropped.load(Ordering::SeqCst) {
+                return Err(io::Error::from(io::ErrorKind::BrokenPipe).into());
+            }
+            Ok(Value::Int(self.id))
+        }
+
+        fn req_packed_commands(
+            &mut self,
+            _cmd: &[u8],
+            _offset: usize,
+            _count: usize,
+        ) -> RedisResult<Vec<Value>> {
+            Err((
+                redis::ErrorKind::ClientError,
+                "pipelines are not supported by the fake connection",
+            )
+                .into())
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+
+        fn check_connection(&mut self) -> bool {
+            !self.dropped.load(Ordering::SeqCst)
+        }
+
+        fn is_open(&self) -> bool {
+            !self.dropped.load(Ordering::SeqCst)
+        }
+    }
+
+    struct FakeRedis {
+        connections: AtomicU32,
+        reachable: AtomicBool,
+        // how long connecting blocks, e.g. until the connect timeout of an unreachable redis
+        connect_delay: Mutex<Duration>,
+    }
+
+    impl FakeRedis {
+        fn new() -> Arc<Self> {
+            Arc::new(Self {
+                connections: AtomicU32::new(0),
+                reachable: AtomicBool::new(true),
+                connect_delay: Mutex::new(Duration::ZERO),
+            })
+        }
+
+        fn connect(self: &Arc<Self>) -> SharedRedisConnection<FakeConnection> {
+            let redis = Arc::clone(self);
+            SharedRedisConnection::new(move || {
+                thread::sleep(*redis.connect_delay.lock().unwrap());
+                if !redis.reachable.load(Ordering::SeqCst) {
+                    return Err(io::Error::from(io::ErrorKind::ConnectionRefused).into());
+                }
+                Ok(FakeConnection {
+                    id: (redis.connections.fetch_add(1, Ordering::SeqCst) + 1).into(),
+                    dropped: AtomicBool::new(false),
+                })
+            })
+            .unwrap()
+        }
+    }
+
+    /// Breaks the currently established connection
+    fn drop_connection(connection: &SharedRedisConnection<FakeConnection>) {
+        let established = connection.connection.lock().unwrap();
+        established
+            .as_ref()
+            .unwrap()
+            .dropped
+            .store(true, Ordering::SeqCst);
+    }
+
+    fn get(connection: &SharedRedisConnection<FakeConnection>) -> RedisResult<i64> {
+        connection.with(|established| redis::cmd("GET").arg("key").query(established))
+    }
+
+    #[test]
+    fn dropped_connection_is_reestablished_on_next_operation() {
+        let redis = FakeRedis::new();
+        let connection = redis.connect();
+        assert_eq!(get(&connection).unwrap(), 1);
+
+        drop_connection(&connection);
+
+        assert_eq!(get(&connection).unwrap(), 2);
+        assert_eq!(redis.connections.load(Ordering::SeqCst), 2);
+        assert!(connection.is_up());
+    }
+
+    #[test]
+    fn unreachable_redis_is_reported_until_it_recovers() {
+        let redis = FakeRedis::new();
+        let connection = redis.connect();
+
+        redis.reachable.store(false, Ordering::SeqCst);
+        drop_connection(&connection);
+
+        assert!(get(&connection).is_err());
+        assert!(!connection.ping());
+        assert!(!connection.is_up());
+
+        redis.reachable.store(true, Ordering::SeqCst);
+        assert_eq!(get(&connection).unwrap(), 2);
+        assert!(connection.is_up());
+    }
+
+    #[test]
+    fn pipelines_fail_instead_of_panicking() {
+        let connection = FakeRedis::new().connect();
+
+        let result = connection.with(|established| {
+            redis::pipe()
+                .cmd("GET")
+                .arg("key")
+                .query::<Vec<i64>>(established)
+        });
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn reconnecting_does_not_stall_the_other_tasks() {
+        let redis = FakeRedis::new();
+        let connection = Arc::new(redis.connect());
+        *redis.connect_delay.lock().unwrap() = Duration::from_secs(2);
+        drop_connection(&connection);
+        let started = Instant::now();
+
+        let reconnecting = tokio::spawn({
+            let connection = Arc::clone(&connection);
+            async move { get(&connection) }
+        });
+        // scheduled on the only worker, which is busy reconnecting
+        let other = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            started.elapsed()
+        });
+
+        assert!(other.await.unwrap() < Duration::from_secs(1));
+        assert_eq!(reconnecting.await.unwrap().unwrap(), 2);
+    }
+}
@@ -0,0 +1,1331 @@
+//! Supervision of the relayer components, which are rebooted with a backoff whenever they stop
+
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use contracts_azero_client::{keypair_from_string, AccountId, ClientWithSigner};
+use ethers::signers::Signer;
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use log::{debug, error, info, warn};
+use thiserror::Error;
+use tokio::{
+    pin, select,
+    sync::{broadcast, mpsc, oneshot, watch},
+    task::{self, JoinError, JoinSet},
+    time::sleep,
+};
+
+use crate::{
+    admin::SubmissionGate,
+    audit::{AuditError, AuditSink, FileAuditSink, NoAuditSink},
+    check_guardian::{self, CheckGuardianError},
+    config::{AuditSinkKind, Config, ConfigError},
+    connections::{
+        self,
+        azero::{self, AzeroSigner, AzeroSignerClient, AzeroWsConnection},
+        eth::{
+            self, with_gas_escalator, EthConnection, EthConnectionError,
+            GasEscalatingEthConnection, LocalKeySource, SignedEthConnection,
+        },
+    },
+    contracts::{AzeroContractError, MostInstance},
+    dedup::RequestDeduplicator,
+    fees::{AzeroGasPriceOracle, OracleFees},
+    handlers::{
+        AlephZeroEventHandler, AlephZeroEventsHandler, AlephZeroEventsHandlerError,
+        AzeroEventHandler, AzeroMost, EthereumEventsHandler, EthereumEventsHandlerError,
+        ShadowAzeroEventHandler, ShadowMost, SignedMostInstance,
+    },
+    helpers::shutdown_requested,
+    listeners::{
+        AdvisoryEmergencies, AdvisoryListener, AdvisoryListenerError, AdvisoryReads,
+        AlephZeroCommitteeMembershipListener, AlephZeroCommitteeMembershipListenerError,
+        AlephZeroFinalizedHeadListener, AlephZeroFinalizedHeadListenerError,
+        AlephZeroHaltedListener, AlephZeroHaltedListenerError, AlephZeroListener,
+        AlephZeroListenerError, AzeroAdvisoryReads, AzeroHaltedRead, AzeroMostEvents,
+        CachedBaseFee, EthMostEvents, EthereumFinalizedHeadListener,
+        EthereumFinalizedHeadListenerError, EthereumListener, EthereumListenerError,
+        EthereumPausedListener, EthereumPausedListenerError, SupplyReconciliationListener,
+        SupplyReconciliationListenerError,
+    },
+    metadata::MetadataError,
+    metrics::Metrics,
+    offline_signing::{OfflineSigningError, SignedTxBroadcaster},
+    redis::{
+        read_block_number, AzeroWatermark, DeadLetterStore, RedisAuditSink,
+        RedisBlockCheckpointStore, RedisDeadLetterStore, RedisManager, RedisManagerError,
+        RedisRelayedRequestStore, RedisSubmittedTxStore, SharedRedisConnection, SubmittedTxStore,
+    },
+    watchdog::Watchdog,
+    CircuitBreakerEvent,
+};
+
+/// minimum amount of time the relayer should run healthy to reset the backoff duration to the default value
+const MINIMUM_TASK_LENGHT: Duration = Duration::from_millis(600000); // 10 minutes
+/// starting backoff value
+const DEFAULT_BACKOFF_DURATION: Duration = Duration::from_millis(2000); // 2 seconds
+
+/// Reason why the relayer, or one of its commands, could not run
+#[derive(Debug, Error)]
+#[error(transparent)]
+#[non_exhaustive]
+pub enum RelayerError {
+    #[error("AlephZero node connection error")]
+    AzeroConnection(#[from] connections::azero::Error),
+
+    #[error("AlephZero client error")]
+    AzeroClient(#[from] contracts_azero_client::ClientError),
+
+    #[error("Ethereum node connection error")]
+    EthereumConnection(#[from] connections::eth::EthConnectionError),
+
+    #[error("Task join error")]
+    Join(#[from] JoinError),
+
+    #[error("circuit breaker channel send error")]
+    CircuitBreakerSend(#[from] mpsc::error::SendError<CircuitBreakerEvent>),
+
+    #[error("ack receive error")]
+    AckReceive(#[from] oneshot::error::RecvError),
+
+    #[error("Advisory listener failure")]
+    AdvisoryListener(#[from] AdvisoryListenerError),
+
+    #[error("AlephZero Most listener failure")]
+    AlephZeroListener(#[from] AlephZeroListenerError),
+
+    #[error("AlephZero events handler failure")]
+    AlephZeroEventsHandler(#[from] AlephZeroEventsHandlerError),
+
+    #[error("Ethereum Most listener failure")]
+    EthereumListener(#[from] EthereumListenerError),
+
+    #[error("Ethereum events handler failure")]
+    EthereumEventsHandler(#[from] EthereumEventsHandlerError),
+
+    #[error("Redis manager failure")]
+    RedisManager(#[from] RedisManagerError),
+
+    #[error("Redis error")]
+    Redis(#[from] ::redis::RedisError),
+
+    #[error("AlephZero finalized head listener failure")]
+    AlephZeroFinalizedHeadListener(#[from] AlephZeroFinalizedHeadListenerError),
+
+    #[error("AlephZero Most halted listener failure")]
+    AlephZeroHaltedListener(#[from] AlephZeroHaltedListenerError),
+
+    #[error("Ethereum finalized head listener failure")]
+    EthereumFinalizedHeadListener(#[from] EthereumFinalizedHeadListenerError),
+
+    #[error("Ethereum's Most paused listener failure")]
+    EthereumPausedListener(#[from] EthereumPausedListenerError),
+
+    #[error("AlephZero contract error")]
+    AzeroContract(#[from] AzeroContractError),
+
+    #[error("Supply reconciliation listener failure")]
+    SupplyReconciliationListener(#[from] SupplyReconciliationListenerError),
+
+    #[error("AlephZero committee membership listener failure")]
+    AlephZeroCommitteeMembershipListener(#[from] AlephZeroCommitteeMembershipListenerError),
+
+    #[error("Offline signed tx broadcaster failure")]
+    OfflineSigning(#[from] OfflineSigningError),
+
+    #[error("Guardian check failure")]
+    CheckGuardian(#[from] CheckGuardianError),
+
+    #[error("Audit sink error")]
+    Audit(#[from] AuditError),
+
+    #[error("Contract metadata error")]
+    Metadata(#[from] MetadataError),
+
+    #[error("Invalid configuration")]
+    Config(#[from] ConfigError),
+
+    #[error("Block {0} is not finalized yet, the finalized head is {1}")]
+    NotFinalized(u32, u32),
+}
+
+/// Components which stop on their own once a shutdown is requested, after the in-flight requests are handled
+/// and their block numbers are cached
+const DRAINED_COMPONENTS: [&str; 5] = [
+    "EthereumListener",
+    "EthereumEventsHandler",
+    "AlephZeroListener",
+    "AlephZeroEventsHandler",
+    "RedisManager",
+];
+
+/// Core components of the relayer, with their names kept around for diagnosing crashes
+#[derive(Default)]
+pub struct Components {
+    tasks: JoinSet<Result<CircuitBreakerEvent, RelayerError>>,
+    names: HashMap<task::Id, &'static str>,
+}
+
+impl Components {
+    pub fn spawn<F>(&mut self, name: &'static str, component: F)
+    where
+        F: Future<Output = Result<CircuitBreakerEvent, RelayerError>> + Send + 'static,
+    {
+        let id = self.tasks.spawn(component).id();
+        self.names.insert(id, name);
+    }
+
+    async fn join_next(
+        &mut self,
+    ) -> Option<Result<Result<CircuitBreakerEvent, RelayerError>, JoinError>> {
+        let result = self.tasks.join_next_with_id().await?;
+        Some(result.map(|(id, result)| {
+            self.names.remove(&id);
+            result
+        }))
+    }
+
+    pub fn len(&self) -> usize {
+        self.tasks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tasks.is_empty()
+    }
+
+    /// Waits until all components exited gracefully or one of them failed, aborting the remaining ones in the latter case.
+    ///
+    /// Returns the reason why the components stopped.
+    async fn wait(&mut self) -> String {
+        while let Some(result) = self.join_next().await {
+            let failure = match result {
+                Err(why) => self.describe_failure(why),
+                Ok(Err(why)) => format!("One of the core components exited with an error {why:?}"),
+                Ok(Ok(event)) if self.is_empty() => {
+                    return format!(
+                        "All core components exited gracefully, the last one due to {event:?}"
+                    )
+                }
+                Ok(Ok(event)) => {
+                    debug!(
+                        "One of the core components exited gracefully due to : {event:?}, remaining: {}",
+                        self.len()
+                    );
+                    continue;
+                }
+            };
+
+            self.shutdown().await;
+            return failure;
+        }
+
+        "No core components were running".to_owned()
+    }
+
+    async fn shutdown(&mut self) {
+        self.tasks.shutdown().await;
+        self.names.clear();
+    }
+
+    /// Waits up to `grace_period` for the [`DRAINED_COMPONENTS`] to stop, then aborts the remaining ones.
+    ///
+    /// Returns how the shutdown went.
+    async fn drain(&mut self, grace_period: Duration) -> String {
+        let deadline = sleep(grace_period);
+        pin!(deadline);
+
+        while self
+            .names
+            .values()
+            .any(|name| DRAINED_COMPONENTS.contains(name))
+        {
+            select! {
+                Some(result) = self.join_next() => match result {
+                    Err(why) => warn!("{} while shutting down", self.describe_failure(why)),
+                    Ok(Err(why)) => warn!("One of the core components exited with an error {why:?} while shutting down"),
+                    Ok(Ok(event)) => debug!("One of the core components exited due to {event:?}, remaining: {}", self.len()),
+                },
+
+                _ = &mut deadline => {
+                    self.shutdown().await;
+                    return format!("Shut down without waiting for the in-flight requests for more than {grace_period:?}");
+                }
+            }
+        }
+
+        self.shutdown().await;
+        "Shut down after the in-flight requests were handled".to_owned()
+    }
+
+    /// Names the component which did not finish and includes the panic message, if it panicked
+    fn describe_failure(&mut self, why: JoinError) -> String {
+        let name = self.names.remove(&why.id()).unwrap_or("Unknown component");
+
+        if why.is_panic() {
+            format!("{name} panicked: {}", panic_message(why.into_panic()))
+        } else {
+            format!("{name} was cancelled")
+        }
+    }
+}
+
+/// Delay before rebooting the relayer once its components stopped
+struct Backoff {
+    delay: Duration,
+    max_delay: Duration,
+    max_restarts: Option<u32>,
+    consecutive_restarts: u32,
+}
+
+impl Backoff {
+    fn new(max_delay: Duration, max_restarts: Option<u32>) -> Self {
+        Self {
+            delay: DEFAULT_BACKOFF_DURATION,
+            max_delay,
+            max_restarts,
+            consecutive_restarts: 0,
+        }
+    }
+
+    /// Doubles the delay for every restart in a row which follows a run shorter than [`MINIMUM_TASK_LENGHT`].
+    ///
+    /// Returns `None` once there were `max_restarts` restarts in a row and the relayer should give up.
+    fn next(&mut self, run_time: Duration) -> Option<Duration> {
+        if run_time >= MINIMUM_TASK_LENGHT {
+            self.consecutive_restarts = 0;
+        }
+        if self
+            .max_restarts
+            .is_some_and(|max_restarts| self.consecutive_restarts >= max_restarts)
+        {
+            return None;
+        }
+
+        self.delay = match self.consecutive_restarts {
+            0 => DEFAULT_BACKOFF_DURATION,
+            _ => self.delay.saturating_mul(2),
+        }
+        .min(self.max_delay);
+        self.consecutive_restarts += 1;
+
+        Some(self.delay)
+    }
+}
+
+/// Starts the relayer and reboots it with a [`Backoff`] whenever its components stop, whether gracefully or not.
+///
+/// `start` gets whether it is the first run. Returns the reason for giving up once the restart limit is reached,
+/// or how the components were drained once a shutdown is requested on `shutdown`.
+async fn supervise<F, Fut>(
+    mut start: F,
+    mut backoff: Backoff,
+    mut shutdown: watch::Receiver<bool>,
+    grace_period: Duration,
+) -> Result<String, RelayerError>
+where
+    F: FnMut(bool) -> Fut,
+    Fut: Future<Output = Result<Components, RelayerError>>,
+{
+    let mut first_run = true;
+
+    loop {
+        let mut tasks = start(first_run).await?;
+        first_run = false;
+        let tick = Instant::now();
+
+        let reason = select! {
+            reason = tasks.wait() => reason,
+            _ = shutdown_requested(&mut shutdown) => return Ok(tasks.drain(grace_period).await),
+        };
+        let tock = tick.elapsed();
+        info!("Relayer exited after {tock:?}: {reason}");
+
+        match backoff.next(tock) {
+            Some(delay) => {
+                info!(
+                    "Waiting {delay:?} before rebooting, restart {} in a row.",
+                    backoff.consecutive_restarts
+                );
+                select! {
+                    _ = sleep(delay) => {},
+                    _ = shutdown_requested(&mut shutdown) => return Ok("Shut down while waiting to reboot".to_owned()),
+                }
+            }
+            None => {
+                return Ok(format!(
+                    "{reason}. Giving up after {} restarts in a row",
+                    backoff.consecutive_restarts
+                ))
+            }
+        }
+    }
+}
+
+type StartComponents =
+    Box<dyn FnMut(bool) -> BoxFuture<'static, Result<Components, RelayerError>> + Send>;
+
+/// Relayer whose components are rebooted with a backoff whenever they stop, set up with [`Relayer::builder`]
+pub struct Relayer {
+    config: Arc<Config>,
+    start: StartComponents,
+    shutdown: watch::Receiver<bool>,
+}
+
+impl Relayer {
+    pub fn builder(config: Arc<Config>) -> RelayerBuilder {
+        RelayerBuilder {
+            config,
+            components: None,
+            submission_gate: None,
+            redis_connection: None,
+            metrics: None,
+            shutdown: None,
+        }
+    }
+
+    /// Runs the components until the restart limit is reached or a shutdown is requested.
+    ///
+    /// Returns the reason for giving up, or how the components were drained.
+    pub async fn run(self) -> Result<String, RelayerError> {
+        let Relayer {
+            config,
+            start,
+            shutdown,
+        } = self;
+
+        supervise(
+            start,
+            Backoff::new(
+                Duration::from_secs(config.max_restart_backoff_sec),
+                config.max_consecutive_restarts,
+            ),
+            shutdown,
+            Duration::from_secs(config.shutdown_grace_period_sec),
+        )
+        .await
+    }
+}
+
+/// Sets up a [`Relayer`], whose components connect to the nodes unless others are given with
+/// [`RelayerBuilder::components`]
+pub struct RelayerBuilder {
+    config: Arc<Config>,
+    components: Option<StartComponents>,
+    submission_gate: Option<Arc<SubmissionGate>>,
+    redis_connection: Option<Arc<SharedRedisConnection>>,
+    metrics: Option<Arc<Metrics>>,
+    shutdown: Option<watch::Receiver<bool>>,
+}
+
+impl RelayerBuilder {
+    /// Runs the components `start` returns instead of the ones connected to the nodes, e.g. against mock connections.
+    ///
+    /// `start` gets whether it is the first run.
+    pub fn components<F, Fut>(mut self, mut start: F) -> Self
+    where
+        F: FnMut(bool) -> Fut + Send + 'static,
+        Fut: Future<Output = Result<Components, RelayerError>> + Send + 'static,
+    {
+        self.components = Some(Box::new(move |first_run| start(first_run).boxed()));
+        self
+    }
+
+    /// Gate of the first submissions after startup, shared with the admin server which confirms them
+    pub fn submission_gate(mut self, submission_gate: Arc<SubmissionGate>) -> Self {
+        self.submission_gate = Some(submission_gate);
+        self
+    }
+
+    pub fn redis_connection(mut self, redis_connection: Arc<SharedRedisConnection>) -> Self {
+        self.redis_connection = Some(redis_connection);
+        self
+    }
+
+    pub fn metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Once `true` is sent, the components are drained and the relayer stops. It is never shut down if not set
+    pub fn shutdown(mut self, shutdown: watch::Receiver<bool>) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Unless the components are given, connects to redis, which outlives their restarts. The nodes are connected to
+    /// by the components on every run
+    pub async fn build(self) -> Result<Relayer, RelayerError> {
+        let RelayerBuilder {
+            config,
+            components,
+            submission_gate,
+            redis_connection,
+            metrics,
+            shutdown,
+        } = self;
+        let shutdown = shutdown.unwrap_or_else(|| watch::channel(false).1);
+
+        let start = match components {
+            Some(start) => start,
+            None => {
+                // Gas escalator should be shared between all relayer runs - otherwise the gas escalating task will leak on every restart
+                let persistent_eth_connection =
+                    with_gas_escalator(eth::connect(&config).await).await;
+                // Manual confirmations concern the first submissions after startup, not after every reboot
+                let submission_gate = submission_gate
+                    .unwrap_or_else(|| Arc::new(SubmissionGate::new(config.manual_confirm_first)));
+                // Reconnects on its own, so it outlives the relayer restarts
+                let redis_connection = match redis_connection {
+                    Some(redis_connection) => redis_connection,
+                    None => Arc::new(crate::redis::connect(&config)?),
+                };
+                let metrics = metrics.unwrap_or_default();
+                // Outlives the relayer restarts, as an advisory emergency stops the relayer until it is lifted
+                let advisory_emergencies = Arc::new(AdvisoryEmergencies::default());
+                let config = Arc::clone(&config);
+                let shutdown = shutdown.clone();
+
+                Box::new(move |first_run| {
+                    run_relayer(
+                        first_run,
+                        Arc::clone(&config),
+                        persistent_eth_connection.clone(),
+                        Arc::clone(&submission_gate),
+                        Arc::clone(&redis_connection),
+                        Arc::clone(&metrics),
+                        Arc::clone(&advisory_emergencies),
+                        shutdown.clone(),
+                    )
+                    .boxed()
+                })
+            }
+        };
+
+        Ok(Relayer {
+            config,
+            start,
+            shutdown,
+        })
+    }
+}
+
+fn panic_message(payload: Box<dyn Any + Send>) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "non-string panic payload".to_string()
+    }
+}
+
+/// Spawns a block listener, supervised by the [`Watchdog`] if `listener_stall_timeout_sec` is set.
+///
+/// `progress` should receive the next block range the listener is going to process, `awaiting_handler`
+/// whether the listener is held back by the events handler.
+fn spawn_listener<F, Fut>(
+    tasks: &mut Components,
+    config: &Config,
+    name: &'static str,
+    progress: broadcast::Receiver<u32>,
+    awaiting_handler: watch::Receiver<bool>,
+    metrics: Arc<Metrics>,
+    mut start: F,
+) where
+    F: FnMut(bool) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<CircuitBreakerEvent, RelayerError>> + Send + 'static,
+{
+    match config.listener_stall_timeout_sec {
+        Some(stall_timeout) => tasks.spawn(
+            name,
+            Watchdog::supervise(
+                name,
+                progress,
+                awaiting_handler,
+                Duration::from_secs(stall_timeout),
+                metrics,
+                start,
+            ),
+        ),
+        None => tasks.spawn(name, start(false)),
+    }
+}
+
+pub(crate) async fn create_azero_connections(
+    config: &Config,
+) -> Result<(Arc<AzeroWsConnection>, Arc<ClientWithSigner<AzeroSigner>>), RelayerError> {
+    let azero_connection = azero::init(&config.azero_node_wss_url).await;
+    let signer = if let Some(cid) = config.signer_cid {
+        info!("[AlephZero] Creating signed connection using a Signer client");
+        let client =
+            AzeroSignerClient::new(cid, &config.signer_ports, config.signer_timeouts()).await?;
+        info!("[AlephZero] Using the signer on port {}", client.port());
+        AzeroSigner::Signer(client)
+    } else if config.dev {
+        let azero_seed = "//".to_owned() + &config.dev_account_index.to_string();
+        let keypair = keypair_from_string(&azero_seed);
+
+        info!(
+            "Creating signed connection using a development key {}",
+            keypair.account_id()
+        );
+
+        AzeroSigner::Dev(Box::new(keypair))
+    } else {
+        unreachable!("a signing mode is required by Config::validate");
+    };
+    let azero_signed_connection = azero_connection
+        .with_signer(signer)
+        .await?
+        .with_mortality(config.azero_extrinsic_mortality_blocks);
+
+    Ok((
+        Arc::new(azero_connection),
+        Arc::new(azero_signed_connection),
+    ))
+}
+
+pub(crate) async fn create_eth_connections(
+    config: &Config,
+    persistent_eth_connection: GasEscalatingEthConnection,
+) -> Result<(Arc<EthConnection>, Arc<SignedEthConnection>), EthConnectionError> {
+    let eth_signed_connection = if let Some(cid) = config.signer_cid {
+        info!("Creating signed connection using a Signer client");
+        eth::with_signer(
+            persistent_eth_connection,
+            cid,
+            &config.signer_ports,
+            config.signer_timeouts(),
+        )
+        .await?
+    } else if config.dev {
+        let source = LocalKeySource::from_config(config);
+        let wallet = source.wallet(config.dev_account_index, |var| std::env::var(var))?;
+
+        if source == LocalKeySource::DevMnemonic {
+            let private_key = wallet
+                .signer()
+                .to_bytes()
+                .iter()
+                .map(|&i| format!("{:X}", i))
+                .collect::<Vec<String>>()
+                .join("");
+
+            info!(
+                "Creating signed connection using a development key {} [{private_key}]",
+                &wallet.address()
+            );
+        } else {
+            info!(
+                "Creating signed connection using the key {} from {source:?}",
+                &wallet.address()
+            );
+        }
+        eth::with_local_wallet(persistent_eth_connection, wallet).await?
+    } else {
+        unreachable!("a signing mode is required by Config::validate");
+    };
+
+    Ok((
+        Arc::new(eth::connect(config).await),
+        Arc::new(eth_signed_connection),
+    ))
+}
+
+async fn set_payout_account(
+    config: &Config,
+    azero_signed_connection: &ClientWithSigner<AzeroSigner>,
+    payout_address: &str,
+) -> Result<(), RelayerError> {
+    let most_azero = MostInstance::new(
+        &config.azero_contract_address,
+        &config.azero_contract_metadata,
+        config.azero_ref_time_limit,
+        config.azero_proof_size_limit,
+    )?;
+
+    let current_committee_id = most_azero
+        .current_committee_id(azero_signed_connection.client())
+        .await?;
+    most_azero
+        .set_payout_account(
+            azero_signed_connection,
+            current_committee_id,
+            AccountId::from_str(payout_address)
+                .map_err(|why| AzeroContractError::NotAccountId(why.to_string()))?,
+        )
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) fn audit_sink(
+    config: &Config,
+    redis_connection: &Arc<SharedRedisConnection>,
+) -> Result<Arc<dyn AuditSink>, RelayerError> {
+    Ok(match config.audit_sink {
+        Some(AuditSinkKind::File) => Arc::new(FileAuditSink::open(&config.audit_log_path)?),
+        Some(AuditSinkKind::Redis) => {
+            Arc::new(RedisAuditSink::new(config, Arc::clone(redis_connection)))
+        }
+        None => Arc::new(NoAuditSink),
+    })
+}
+
+/// Fees of the Ethereum transactions derived from the gas price oracle, `None` if they are left to the Ethereum node
+pub(crate) fn oracle_fees(
+    config: &Config,
+    azero_connection: &Arc<AzeroWsConnection>,
+) -> Result<Option<Arc<OracleFees>>, RelayerError> {
+    let Some(strategy) = config.oracle_fee_strategy() else {
+        return Ok(None);
+    };
+
+    info!("Ethereum fees derived from the gas price oracle: {strategy:?}");
+    let most = MostInstance::new(
+        &config.azero_contract_address,
+        &config.azero_contract_metadata,
+        config.azero_ref_time_limit,
+        config.azero_proof_size_limit,
+    )?;
+    let oracle = AzeroGasPriceOracle::new(
+        Arc::clone(azero_connection),
+        most,
+        &config.gas_price_oracle_metadata,
+    );
+
+    Ok(Some(Arc::new(OracleFees::new(strategy, Arc::new(oracle)))))
+}
+
+/// Stands in for the components while `active_advisories` are in emergency, so that the supervisor reboots the
+/// relayer with a backoff instead of starting it. Returns whether the relayer is held.
+pub fn hold_for_advisories(components: &mut Components, active_advisories: Vec<AccountId>) -> bool {
+    if active_advisories.is_empty() {
+        return false;
+    }
+
+    info!("Active advisories detected: {active_advisories:?} - Relayer will not start.");
+    components.spawn("AdvisoryCheck", async {
+        Ok(CircuitBreakerEvent::AdvisoryEmergency(active_advisories))
+    });
+    true
+}
+
+async fn run_relayer(
+    first_run: bool,
+    config: Arc<Config>,
+    persistent_eth_connection: GasEscalatingEthConnection,
+    submission_gate: Arc<SubmissionGate>,
+    redis_connection: Arc<SharedRedisConnection>,
+    metrics: Arc<Metrics>,
+    advisory_emergencies: Arc<AdvisoryEmergencies>,
+    shutdown: watch::Receiver<bool>,
+) -> Result<Components, RelayerError> {
+    let mut components = Components::default();
+    let tasks = &mut components;
+
+    // create connections
+    let (azero_connection, azero_signed_connection) = create_azero_connections(&config).await?;
+    info!("Established connection to Aleph Zero node");
+
+    let (eth_connection, eth_signed_connection) =
+        create_eth_connections(&config, persistent_eth_connection).await?;
+    info!("Established connection to the Ethereum node");
+
+    let shadow_of = match &config.shadow_of {
+        Some(guardian) => {
+            let guardian = AccountId::from_str(guardian)
+                .map_err(|why| AzeroContractError::NotAccountId(why.to_string()))?;
+            info!("Running in the shadow mode of guardian {guardian}, nothing is going to be submitted");
+            Some(guardian)
+        }
+        None => None,
+    };
+
+    if first_run {
+        let checks = [
+            check_guardian::check_azero(&config, &azero_signed_connection).await?,
+            check_guardian::check_eth(&config, Arc::clone(&eth_signed_connection)).await?,
+        ];
+        for check in &checks {
+            check.ensure_bridgeable()?;
+            // not refused, as the membership listener keeps the relayer from submitting until the account joins the
+            // committee, and the shadow relayer runs under a key from outside of it anyway
+            if let (Err(why), None) = (check.ensure_member(), &shadow_of) {
+                error!(
+                    "{why}: every receive_request on {} reverts with NotInCommittee until it joins, check the signing key",
+                    check.chain
+                );
+            }
+        }
+    }
+
+    if let (Some(payout_address), None) = (&config.payout_address, &shadow_of) {
+        set_payout_account(&config, &azero_signed_connection, payout_address).await?;
+    }
+
+    let submitted_txs: Arc<dyn SubmittedTxStore> = Arc::new(RedisSubmittedTxStore::new(
+        &config,
+        Arc::clone(&redis_connection),
+    ));
+    let dead_letters: Arc<dyn DeadLetterStore> = Arc::new(RedisDeadLetterStore::new(
+        &config,
+        Arc::clone(&redis_connection),
+    ));
+    let audit_sink = audit_sink(&config, &redis_connection)?;
+    // shared by the handlers of both chains
+    let dedup = Arc::new(RequestDeduplicator::new(
+        Duration::from_secs(config.request_dedup_window_sec),
+        Arc::clone(&metrics),
+    ));
+
+    // Create channels
+    let (eth_events_sender, eth_events_receiver) = mpsc::channel::<EthMostEvents>(1);
+    let (eth_block_number_sender, _) = broadcast::channel::<u32>(1);
+    let (eth_finalized_head_sender, eth_finalized_head_receiver) = watch::channel::<u32>(0);
+
+    let (azero_events_sender, azero_events_receiver) = mpsc::channel::<AzeroMostEvents>(32);
+    let (azero_block_number_sender, azero_block_number_receiver) = broadcast::channel::<u32>(1);
+    let (azero_block_seal_sender, azero_block_seal_receiver) = mpsc::channel::<u32>(1);
+    let (azero_finalized_head_sender, azero_finalized_head_receiver) = watch::channel::<u32>(0);
+
+    let (circuit_breaker_sender, _circuit_breaker_receiver) =
+        broadcast::channel::<CircuitBreakerEvent>(1);
+
+    let advisories: Arc<dyn AdvisoryReads> = Arc::new(AzeroAdvisoryReads::new(
+        AdvisoryListener::parse_advisory_addresses(config.clone()),
+        Arc::clone(&azero_connection),
+    ));
+
+    // Check advisory status before starting the relayer
+    let active_advisories =
+        AdvisoryListener::query_active_advisories(&config, &*advisories).await?;
+    advisory_emergencies.update(&active_advisories);
+
+    // If there are active advisories, we should avoid starting the relayer.
+    // Starting all the components might lead to a race condition in which event handlers
+    // might start processing before advisory listener activates the circuit breaker.
+    if hold_for_advisories(tasks, active_advisories) {
+        return Ok(components);
+    }
+
+    // Receivers need to be prepared beforehand in order to receive all the data from other components
+    let advisory_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let aleph_halted_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let aleph_committee_membership_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let aleph_finalized_head_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let eth_paused_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let supply_reconciliation_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let eth_finalized_head_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let signed_tx_broadcaster_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let redis_manager_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let eth_listener_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let eth_events_handler_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let aleph_listener_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    let aleph_events_handler_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+
+    let redis_manager_eth_block_number_receiver = eth_block_number_sender.subscribe();
+    let eth_listener_eth_block_number_receiver = eth_block_number_sender.subscribe();
+    let eth_watchdog_block_number_receiver = eth_block_number_sender.subscribe();
+    let aleph_watchdog_block_number_receiver = azero_block_number_sender.subscribe();
+    let (eth_awaiting_handler_sender, eth_awaiting_handler_receiver) = watch::channel(false);
+    let (aleph_awaiting_handler_sender, aleph_awaiting_handler_receiver) = watch::channel(false);
+
+    tasks.spawn(
+        "AdvisoryListener",
+        AdvisoryListener::run(
+            Arc::clone(&config),
+            advisories,
+            advisory_emergencies,
+            circuit_breaker_sender.clone(),
+            advisory_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    tasks.spawn(
+        "AlephZeroHaltedListener",
+        AlephZeroHaltedListener::run(
+            Arc::clone(&config),
+            Arc::new(AzeroHaltedRead::new(
+                &config,
+                Arc::clone(&azero_connection),
+            )?),
+            circuit_breaker_sender.clone(),
+            aleph_halted_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    tasks.spawn(
+        "AlephZeroCommitteeMembershipListener",
+        AlephZeroCommitteeMembershipListener::run(
+            Arc::clone(&config),
+            Arc::clone(&azero_signed_connection),
+            Arc::clone(&metrics),
+            circuit_breaker_sender.clone(),
+            aleph_committee_membership_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    tasks.spawn(
+        "AlephZeroFinalizedHeadListener",
+        AlephZeroFinalizedHeadListener::run(
+            Arc::clone(&config),
+            Arc::clone(&azero_connection),
+            azero_finalized_head_sender,
+            circuit_breaker_sender.clone(),
+            aleph_finalized_head_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    tasks.spawn(
+        "EthereumPausedListener",
+        EthereumPausedListener::run(
+            Arc::clone(&config),
+            Arc::clone(&eth_connection),
+            circuit_breaker_sender.clone(),
+            eth_paused_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    tasks.spawn(
+        "EthereumFinalizedHeadListener",
+        EthereumFinalizedHeadListener::run(
+            Arc::clone(&config),
+            Arc::clone(&eth_connection),
+            eth_finalized_head_sender,
+            eth_finalized_head_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    if let (Some(offline_signing_dir), None) = (&config.offline_signing_dir, &shadow_of) {
+        tasks.spawn(
+            "SignedTxBroadcaster",
+            SignedTxBroadcaster::run(
+                offline_signing_dir.clone(),
+                Duration::from_secs(config.offline_signing_poll_interval_sec),
+                Arc::clone(&eth_connection),
+                signed_tx_broadcaster_circuit_breaker_receiver,
+            )
+            .map_err(RelayerError::from),
+        );
+    }
+
+    tasks.spawn(
+        "SupplyReconciliationListener",
+        SupplyReconciliationListener::run(
+            Arc::clone(&config),
+            Arc::clone(&azero_connection),
+            Arc::clone(&eth_connection),
+            supply_reconciliation_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    tasks.spawn(
+        "RedisManager",
+        RedisManager::run(
+            first_run,
+            Arc::clone(&config),
+            Arc::clone(&redis_connection),
+            eth_block_number_sender.clone(),
+            redis_manager_eth_block_number_receiver,
+            azero_block_number_sender.clone(),
+            azero_block_seal_receiver,
+            AzeroWatermark::new(
+                Arc::new(RedisBlockCheckpointStore::new(
+                    &config,
+                    Arc::clone(&redis_connection),
+                )),
+                azero_connection.clone(),
+            ),
+            shutdown.clone(),
+            redis_manager_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    let mut eth_listener_receivers = Some((
+        eth_listener_eth_block_number_receiver,
+        eth_listener_circuit_breaker_receiver,
+    ));
+    spawn_listener(
+        tasks,
+        &config,
+        "EthereumListener",
+        eth_watchdog_block_number_receiver,
+        eth_awaiting_handler_receiver,
+        Arc::clone(&metrics),
+        {
+            let config = Arc::clone(&config);
+            let eth_connection = Arc::clone(&eth_connection);
+            let eth_events_sender = eth_events_sender.clone();
+            let eth_block_number_sender = eth_block_number_sender.clone();
+            let eth_finalized_head_receiver = eth_finalized_head_receiver.clone();
+            let circuit_breaker_sender = circuit_breaker_sender.clone();
+            let redis_connection = Arc::clone(&redis_connection);
+            let shutdown = shutdown.clone();
+
+            move |restarted| {
+                let (block_number_receiver, circuit_breaker_receiver) =
+                    eth_listener_receivers.take().unwrap_or_else(|| {
+                        (
+                            eth_block_number_sender.subscribe(),
+                            circuit_breaker_sender.subscribe(),
+                        )
+                    });
+
+                if restarted {
+                    let _ = eth_block_number_sender.send(read_block_number(
+                        config.name.clone(),
+                        config.redis_eth_block_key.clone(),
+                        &redis_connection,
+                        *config.default_sync_from_block_eth,
+                    ));
+                }
+
+                EthereumListener::run(
+                    Arc::clone(&config),
+                    Arc::clone(&eth_connection),
+                    eth_events_sender.clone(),
+                    eth_block_number_sender.clone(),
+                    block_number_receiver,
+                    eth_awaiting_handler_sender.clone(),
+                    eth_finalized_head_receiver.clone(),
+                    shutdown.clone(),
+                    circuit_breaker_receiver,
+                )
+                .map_err(RelayerError::from)
+            }
+        },
+    );
+
+    let most = SignedMostInstance::new(&config, Arc::clone(&azero_signed_connection))?;
+    let azero_most: Arc<dyn AzeroMost> = match &shadow_of {
+        Some(guardian) => Arc::new(ShadowMost::new(
+            Arc::new(most.observing(guardian.clone())),
+            Duration::from_secs(config.shadow_grace_period_sec),
+        )),
+        None => Arc::new(most),
+    };
+    tasks.spawn(
+        "EthereumEventsHandler",
+        EthereumEventsHandler::run(
+            Arc::clone(&config),
+            eth_events_receiver,
+            Arc::clone(&azero_most),
+            Arc::clone(&dead_letters),
+            Arc::new(RedisRelayedRequestStore::new(
+                &config,
+                Arc::clone(&redis_connection),
+                "eth_to_azero",
+            )),
+            Arc::clone(&dedup),
+            Arc::clone(&submission_gate),
+            Arc::clone(&metrics),
+            circuit_breaker_sender.clone(),
+            eth_events_handler_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    // the handler drops it once the contract parameters change
+    let azero_base_fee = Arc::new(CachedBaseFee::new(Duration::from_secs(
+        config.azero_base_fee_cache_ttl_sec,
+    )));
+    let mut aleph_listener_receivers = Some((
+        azero_block_number_receiver,
+        aleph_listener_circuit_breaker_receiver,
+    ));
+    spawn_listener(
+        tasks,
+        &config,
+        "AlephZeroListener",
+        aleph_watchdog_block_number_receiver,
+        aleph_awaiting_handler_receiver,
+        Arc::clone(&metrics),
+        {
+            let config = Arc::clone(&config);
+            let azero_connection = Arc::clone(&azero_connection);
+            let azero_block_number_sender = azero_block_number_sender.clone();
+            let circuit_breaker_sender = circuit_breaker_sender.clone();
+            let azero_base_fee = Arc::clone(&azero_base_fee);
+
+            move |restarted| {
+                let (block_number_receiver, circuit_breaker_receiver) =
+                    aleph_listener_receivers.take().unwrap_or_else(|| {
+                        (
+                            azero_block_number_sender.subscribe(),
+                            circuit_breaker_sender.subscribe(),
+                        )
+                    });
+
+                if restarted {
+                    let _ = azero_block_number_sender.send(read_block_number(
+                        config.name.clone(),
+                        config.redis_azero_block_key.clone(),
+                        &redis_connection,
+                        *config.default_sync_from_block_azero,
+                    ));
+                }
+
+                AlephZeroListener::run(
+                    Arc::clone(&config),
+                    Arc::clone(&azero_connection),
+                    azero_events_sender.clone(),
+                    azero_block_number_sender.clone(),
+                    block_number_receiver,
+                    aleph_awaiting_handler_sender.clone(),
+                    azero_block_seal_sender.clone(),
+                    azero_finalized_head_receiver.clone(),
+                    shutdown.clone(),
+                    circuit_breaker_receiver,
+                    Arc::clone(&azero_base_fee),
+                )
+                .map_err(RelayerError::from)
+            }
+        },
+    );
+
+    let oracle_fees = oracle_fees(&config, &azero_connection)?;
+
+    let azero_event_handler: Arc<dyn AzeroEventHandler> = match shadow_of {
+        Some(_) => Arc::new(ShadowAzeroEventHandler),
+        None => Arc::new(AlephZeroEventHandler::new(
+            Arc::clone(&config),
+            azero_most,
+            Arc::clone(&eth_signed_connection),
+            eth_finalized_head_receiver,
+            submitted_txs,
+            dead_letters,
+            audit_sink,
+            dedup,
+            Arc::clone(&submission_gate),
+            oracle_fees,
+            azero_base_fee,
+            metrics,
+        )),
+    };
+    tasks.spawn(
+        "AlephZeroEventsHandler",
+        AlephZeroEventsHandler::run(
+            Arc::clone(&config),
+            azero_event_handler,
+            azero_events_receiver,
+            circuit_breaker_sender.clone(),
+            aleph_events_handler_circuit_breaker_receiver,
+        )
+        .map_err(RelayerError::from),
+    );
+
+    Ok(components)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn panicking_component_is_named_in_the_diagnostic() {
+        let mut components = Components::default();
+        components.spawn("AlephZeroListener", async {
+            panic!("unexpected event {}", 42);
+        });
+
+        let why = components.join_next().await.unwrap().unwrap_err();
+
+        assert_eq!(
+            components.describe_failure(why),
+            "AlephZeroListener panicked: unexpected event 42"
+        );
+        assert!(components.is_empty());
+    }
+
+    #[tokio::test]
+    async fn finished_components_are_forgotten() {
+        let mut components = Components::default();
+        components.spawn("AdvisoryCheck", async {
+            Ok(CircuitBreakerEvent::BridgeHaltAlephZero)
+        });
+
+        assert!(matches!(
+            components.join_next().await,
+            Some(Ok(Ok(CircuitBreakerEvent::BridgeHaltAlephZero)))
+        ));
+        assert!(components.names.is_empty());
+    }
+
+    #[test]
+    fn backoff_doubles_on_consecutive_restarts_up_to_the_maximum() {
+        let short_run = Duration::from_secs(1);
+        let mut backoff = Backoff::new(Duration::from_secs(10), None);
+
+        let delays: Vec<_> = (0..5).map(|_| backoff.next(short_run).unwrap()).collect();
+
+        assert_eq!(delays, [2, 4, 8, 10, 10].map(Duration::from_secs),);
+        assert_eq!(backoff.consecutive_restarts, 5);
+    }
+
+    #[test]
+    fn backoff_is_reset_after_a_long_run() {
+        let mut backoff = Backoff::new(Duration::from_secs(600), None);
+        backoff.next(Duration::from_secs(1));
+        backoff.next(Duration::from_secs(1));
+
+        assert_eq!(
+            backoff.next(MINIMUM_TASK_LENGHT),
+            Some(DEFAULT_BACKOFF_DURATION)
+        );
+        assert_eq!(backoff.consecutive_restarts, 1);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn crashing_component_is_restarted_up_to_the_limit() {
+        let mut starts = 0;
+        let start = |_: bool| {
+            starts += 1;
+            async {
+                let mut components = Components::default();
+                components.spawn("AlephZeroListener", async { panic!("connection lost") });
+                components.spawn("EthereumListener", std::future::pending());
+                Ok(components)
+            }
+        };
+
+        let (_, shutdown) = watch::channel(false);
+
+        let reason = supervise(
+            start,
+            Backoff::new(Duration::from_secs(10), Some(3)),
+            shutdown,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(starts, 4);
+        assert_eq!(
+            reason,
+            "AlephZeroListener panicked: connection lost. Giving up after 3 restarts in a row"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn relayer_is_not_started_while_an_advisory_is_active() {
+        let advisory = AccountId::from([7; 32]);
+        let mut starts = 0;
+        let start = |_: bool| {
+            starts += 1;
+            let advisory = advisory.clone();
+            async move {
+                let mut components = Components::default();
+                if !hold_for_advisories(&mut components, vec![advisory]) {
+                    components.spawn("AlephZeroListener", std::future::pending());
+                }
+                Ok(components)
+            }
+        };
+
+        let (_, shutdown) = watch::channel(false);
+
+        let reason = supervise(
+            start,
+            Backoff::new(Duration::from_secs(10), Some(2)),
+            shutdown,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(starts, 3);
+        assert!(
+            reason.starts_with(
+                "All core components exited gracefully, the last one due to AdvisoryEmergency"
+            ),
+            "{reason}"
+        );
+    }
+
+    #[test]
+    fn relayer_is_started_without_active_advisories() {
+        let mut components = Components::default();
+
+        assert!(!hold_for_advisories(&mut components, Vec::new()));
+        assert!(components.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_waits_for_the_drained_components() {
+        let mut components = Components::default();
+        components.spawn("AlephZeroEventsHandler", async {
+            sleep(Duration::from_secs(30)).await;
+            Ok(CircuitBreakerEvent::Shutdown)
+        });
+        components.spawn("AlephZeroFinalizedHeadListener", std::future::pending());
+        let started = Instant::now();
+
+        let reason = components.drain(Duration::from_secs(60)).await;
+
+        assert_eq!(
+            reason,
+            "Shut down after the in-flight requests were handled"
+        );
+        assert!(started.elapsed() >= Duration::from_secs(30));
+        assert!(components.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shutdown_gives_up_on_the_drained_components_after_the_grace_period() {
+        let mut components = Components::default();
+        components.spawn("EthereumEventsHandler", std::future::pending());
+
+        let reason = components.drain(Duration::from_secs(60)).await;
+
+        assert_eq!(
+            reason,
+            "Shut down without waiting for the in-flight requests for more than 60s"
+        );
+        assert!(components.is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn requested_shutdown_is_not_followed_by_a_reboot() {
+        let (shutdown_sender, shutdown) = watch::channel(false);
+        let mut starts = 0;
+        let start = |_: bool| {
+            starts += 1;
+            let shutdown_sender = shutdown_sender.clone();
+            async move {
+                let mut components = Components::default();
+                components.spawn("EthereumListener", async move {
+                    shutdown_sender.send_replace(true);
+                    Ok(CircuitBreakerEvent::Shutdown)
+                });
+                Ok(components)
+            }
+        };
+
+        let reason = supervise(
+            start,
+            Backoff::new(Duration::from_secs(10), None),
+            shutdown,
+            Duration::from_secs(60),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(starts, 1);
+        assert!(reason.starts_with("Shut down"), "{reason}");
+    }
+}
@@ -0,0 +1,189 @@
+use std::{future::Future, panic::resume_unwind, sync::Arc};
+
+use log::{info, warn};
+use tokio::{
+    select,
+    sync::{
+        broadcast::{self, error::RecvError},
+        watch,
+    },
+    task::JoinSet,
+    time::{sleep_until, Duration, Instant},
+};
+
+use crate::metrics::Metrics;
+
+/// Restarts a listener which stopped making progress without exiting, e.g. because it hangs on a request.
+pub struct Watchdog;
+
+impl Watchdog {
+    /// Runs the task created by `start` until it exits, restarting it whenever nothing is received on `progress` for `stall_timeout`.
+    ///
+    /// `start` is told whether the task is restarted, in which case it should resume from the persisted state.
+    /// Only the stalled task is restarted, the other components keep running. No progress is expected while
+    /// `awaiting_handler` is set, as the listener is then held back by a busy handler rather than stalled.
+    pub async fn supervise<F, Fut, T, E>(
+        name: &'static str,
+        mut progress: broadcast::Receiver<u32>,
+        mut awaiting_handler: watch::Receiver<bool>,
+        stall_timeout: Duration,
+        metrics: Arc<Metrics>,
+        mut start: F,
+    ) -> Result<T, E>
+    where
+        F: FnMut(bool) -> Fut,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        T: Send + 'static,
+        E: Send + 'static,
+    {
+        // dropping the set aborts the task, so it does not outlive the watchdog
+        let mut task = JoinSet::new();
+        task.spawn(start(false));
+        let mut deadline = Instant::now() + stall_timeout;
+
+        loop {
+            select! {
+                Some(result) = task.join_next() => match result {
+                    Ok(result) => return result,
+                    // the stalled task which has just been replaced
+                    Err(why) if why.is_cancelled() => {},
+                    Err(why) => resume_unwind(why.into_panic()),
+                },
+
+                Ok(_) | Err(RecvError::Lagged(_)) = progress.recv() => {
+                    deadline = Instant::now() + stall_timeout;
+                },
+
+                // the wait for the handler does not count towards the stall
+                Ok(()) = awaiting_handler.changed() => {
+                    deadline = Instant::now() + stall_timeout;
+                },
+
+                _ = sleep_until(deadline), if !*awaiting_handler.borrow() => {
+                    warn!("{name} made no progress for {stall_timeout:?}, restarting it");
+                    task.abort_all();
+                    metrics.record_listener_restart(name);
+
+                    task.spawn(start(true));
+                    deadline = Instant::now() + stall_timeout;
+                    info!("{name} restarted");
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        convert::Infallible,
+        future::pending,
+        sync::atomic::{AtomicU32, Ordering},
+    };
+
+    use tokio::time::sleep;
+
+    use super::*;
+
+    const STALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Reports progress every second for `progress_for` seconds, then either hangs or exits with the number of its start
+    fn mock_listener(
+        starts: &Arc<AtomicU32>,
+        progress_sender: &broadcast::Sender<u32>,
+        progress_for: u32,
+        hang: bool,
+    ) -> impl Future<Output = Result<u32, Infallible>> {
+        let start = starts.fetch_add(1, Ordering::SeqCst) + 1;
+        let progress_sender = progress_sender.clone();
+
+        async move {
+            for block in 0..progress_for {
+                sleep(Duration::from_secs(1)).await;
+                let _ = progress_sender.send(block);
+            }
+            if hang {
+                pending::<()>().await;
+            }
+            Ok(start)
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn stalled_listener_is_restarted() {
+        let starts = Arc::new(AtomicU32::new(0));
+        let (progress_sender, progress_receiver) = broadcast::channel(1);
+        let metrics = Arc::new(Metrics::default());
+
+        let result = Watchdog::supervise(
+            "MockListener",
+            progress_receiver,
+            watch::channel(false).1,
+            STALL_TIMEOUT,
+            Arc::clone(&metrics),
+            |restarted| mock_listener(&starts, &progress_sender, 20, !restarted),
+        )
+        .await;
+
+        assert_eq!(result, Ok(2));
+        assert_eq!(
+            metrics.render(true),
+            "redis_up 1\nlistener_restarts_total{listener=\"MockListener\"} 1\n"
+        );
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn progressing_listener_is_left_alone() {
+        let starts = Arc::new(AtomicU32::new(0));
+        let (progress_sender, progress_receiver) = broadcast::channel(1);
+        let metrics = Arc::new(Metrics::default());
+
+        let result = Watchdog::supervise(
+            "MockListener",
+            progress_receiver,
+            watch::channel(false).1,
+            STALL_TIMEOUT,
+            Arc::clone(&metrics),
+            |_| mock_listener(&starts, &progress_sender, 30, false),
+        )
+        .await;
+
+        assert_eq!(result, Ok(1));
+        assert_eq!(metrics.render(true), "redis_up 1\n");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn listener_awaiting_the_handler_is_left_alone() {
+        let (progress_sender, progress_receiver) = broadcast::channel(1);
+        let (awaiting_handler_sender, awaiting_handler) = watch::channel(false);
+        let metrics = Arc::new(Metrics::default());
+        let starts = AtomicU32::new(0);
+
+        let result = Watchdog::supervise(
+            "MockListener",
+            progress_receiver,
+            awaiting_handler,
+            STALL_TIMEOUT,
+            Arc::clone(&metrics),
+            |_| {
+                let start = starts.fetch_add(1, Ordering::SeqCst) + 1;
+                let progress_sender = progress_sender.clone();
+                let awaiting_handler_sender = awaiting_handler_sender.clone();
+                async move {
+                    // a batch the handler takes longer than the stall timeout with
+                    awaiting_handler_sender.send_replace(true);
+                    sleep(3 * STALL_TIMEOUT).await;
+                    awaiting_handler_sender.send_replace(false);
+
+                    sleep(Duration::from_secs(1)).await;
+                    let _ = progress_sender.send(1);
+                    Ok::<_, Infallible>(start)
+                }
+            },
+        )
+        .await;
+
+        assert_eq!(result, Ok(1));
+        assert_eq!(metrics.render(true), "redis_up 1\n");
+    }
+}
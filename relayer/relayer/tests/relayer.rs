@@ -0,0 +1,241 @@
+//! Runs the relayer through its library API against scripted contracts instead of the node connections
+
+use std::{
+    collections::HashMap,
+    future::ready,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use clap::Parser;
+use contracts_azero_client::{AccountId, ContractEvent};
+use futures::{future::BoxFuture, FutureExt, TryFutureExt};
+use relayer::{
+    config::Config,
+    contracts::AzeroContractError,
+    handlers::{AlephZeroEventHandlerError, AlephZeroEventsHandler, AzeroEventHandler},
+    helpers::next_circuit_breaker_event,
+    listeners::{
+        AdvisoryEmergencies, AdvisoryListener, AdvisoryReads, AlephZeroHaltedListener,
+        AzeroMostEvents, HaltedRead,
+    },
+    supervisor::{hold_for_advisories, Components, Relayer, RelayerError},
+    CircuitBreakerEvent,
+};
+use tokio::{
+    select,
+    sync::{broadcast, mpsc, oneshot, watch},
+    time::sleep,
+};
+
+const ADVISORY: [u8; 32] = [7; 32];
+const BLOCK_TIME: Duration = Duration::from_secs(1);
+const MAX_REBOOT_DELAY: Duration = Duration::from_secs(10);
+
+/// Contract state behind the advisory and halted listeners
+#[derive(Default)]
+struct ScriptedBridge {
+    advisory_emergency: AtomicBool,
+    halted: AtomicBool,
+}
+
+impl AdvisoryReads for ScriptedBridge {
+    fn is_emergency(&self) -> Vec<(AccountId, BoxFuture<'_, Result<bool, AzeroContractError>>)> {
+        let is_emergency = self.advisory_emergency.load(Ordering::SeqCst);
+        vec![(AccountId::from(ADVISORY), ready(Ok(is_emergency)).boxed())]
+    }
+}
+
+#[async_trait::async_trait]
+impl HaltedRead for ScriptedBridge {
+    async fn is_halted(&self) -> Result<bool, AzeroContractError> {
+        Ok(self.halted.load(Ordering::SeqCst))
+    }
+}
+
+/// Counts the events it is given as submissions
+#[derive(Default)]
+struct CountingHandler(AtomicU32);
+
+#[async_trait::async_trait]
+impl AzeroEventHandler for CountingHandler {
+    async fn handle(&self, _event: ContractEvent) -> Result<(), AlephZeroEventHandlerError> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+/// The components which the circuit breaker concerns, with a listener stand-in sending a batch of one event every
+/// block. The circuit breaker of every run which is not held for advisories is handed over on `runs`.
+async fn start_scripted_relayer(
+    config: Arc<Config>,
+    bridge: Arc<ScriptedBridge>,
+    handler: Arc<CountingHandler>,
+    emergencies: Arc<AdvisoryEmergencies>,
+    runs: mpsc::UnboundedSender<broadcast::Receiver<CircuitBreakerEvent>>,
+) -> Result<Components, RelayerError> {
+    let mut components = Components::default();
+
+    let active_advisories = AdvisoryListener::query_active_advisories(&config, &*bridge).await?;
+    emergencies.update(&active_advisories);
+    if hold_for_advisories(&mut components, active_advisories) {
+        return Ok(components);
+    }
+
+    let (circuit_breaker_sender, _) = broadcast::channel(1);
+    runs.send(circuit_breaker_sender.subscribe()).unwrap();
+
+    components.spawn(
+        "AdvisoryListener",
+        AdvisoryListener::run(
+            Arc::clone(&config),
+            Arc::clone(&bridge),
+            emergencies,
+            circuit_breaker_sender.clone(),
+            circuit_breaker_sender.subscribe(),
+        )
+        .map_err(RelayerError::from),
+    );
+    components.spawn(
+        "AlephZeroHaltedListener",
+        AlephZeroHaltedListener::run(
+            Arc::clone(&config),
+            bridge,
+            circuit_breaker_sender.clone(),
+            circuit_breaker_sender.subscribe(),
+        )
+        .map_err(RelayerError::from),
+    );
+
+    let (events_sender, events_receiver) = mpsc::channel(1);
+    let mut listener_circuit_breaker_receiver = circuit_breaker_sender.subscribe();
+    components.spawn("AlephZeroListener", async move {
+        let mut block = 0;
+        loop {
+            block += 1;
+            let (ack, acked) = oneshot::channel();
+            let batch = AzeroMostEvents {
+                events: vec![ContractEvent {
+                    contract: AccountId::from([0; 32]),
+                    name: Some("CrosschainTransferRequest".to_owned()),
+                    data: HashMap::new(),
+                    block_number: Some(block),
+                }],
+                from_block: block,
+                to_block: block,
+                ack,
+            };
+
+            select! {
+                cb_event = next_circuit_breaker_event(&mut listener_circuit_breaker_receiver) => {
+                    return Ok(cb_event.expect("circuit breaker"));
+                },
+
+                _ = async {
+                    sleep(BLOCK_TIME).await;
+                    events_sender.send(batch).await.expect("events handler");
+                    acked.await
+                } => {}
+            }
+        }
+    });
+    components.spawn(
+        "AlephZeroEventsHandler",
+        AlephZeroEventsHandler::run(
+            config,
+            handler,
+            events_receiver,
+            circuit_breaker_sender.clone(),
+            circuit_breaker_sender.subscribe(),
+        )
+        .map_err(RelayerError::from),
+    );
+
+    Ok(components)
+}
+
+async fn submissions_during(handler: &CountingHandler, duration: Duration) -> u32 {
+    let before = handler.0.load(Ordering::SeqCst);
+    sleep(duration).await;
+    handler.0.load(Ordering::SeqCst) - before
+}
+
+#[tokio::test(start_paused = true)]
+async fn circuit_breaker_pauses_and_resumes_submissions() {
+    let config = Arc::new(Config::parse_from([
+        "relayer",
+        "--name",
+        "test",
+        "--azero-contract-address",
+        "5C4hrfjw9DjXZTzV3MwzrrAr9P1MJhSrvWGWqi1eSuyUpnhM",
+        "--eth-contract-address",
+        "0x0000000000000000000000000000000000000000",
+        "--max-restart-backoff-sec",
+        "10",
+    ]));
+    let bridge = Arc::new(ScriptedBridge::default());
+    let handler = Arc::new(CountingHandler::default());
+    let emergencies = Arc::new(AdvisoryEmergencies::default());
+    let (runs_sender, mut runs) = mpsc::unbounded_channel();
+    let (starts_sender, mut starts) = watch::channel(0);
+    let (_shutdown_sender, shutdown) = watch::channel(false);
+
+    let relayer_bridge = Arc::clone(&bridge);
+    let relayer_handler = Arc::clone(&handler);
+    let relayer = Relayer::builder(Arc::clone(&config))
+        .components(move |_| {
+            starts_sender.send_modify(|starts| *starts += 1);
+            start_scripted_relayer(
+                Arc::clone(&config),
+                Arc::clone(&relayer_bridge),
+                Arc::clone(&relayer_handler),
+                Arc::clone(&emergencies),
+                runs_sender.clone(),
+            )
+        })
+        .shutdown(shutdown)
+        .build()
+        .await
+        .unwrap();
+    tokio::spawn(relayer.run());
+
+    // normal operation
+    let mut circuit_breaker = runs.recv().await.unwrap();
+    assert!(submissions_during(&handler, BLOCK_TIME * 10).await > 0);
+    assert_eq!(*starts.borrow(), 1);
+
+    // advisory emergency - the components exit and the relayer is held at every reboot
+    bridge.advisory_emergency.store(true, Ordering::SeqCst);
+    assert!(matches!(
+        circuit_breaker.recv().await,
+        Ok(CircuitBreakerEvent::AdvisoryEmergency(active)) if active == [AccountId::from(ADVISORY)]
+    ));
+    starts.wait_for(|starts| *starts == 3).await.unwrap();
+    assert_eq!(submissions_during(&handler, MAX_REBOOT_DELAY * 2).await, 0);
+    assert!(runs.try_recv().is_err());
+
+    // advisory cleared
+    bridge.advisory_emergency.store(false, Ordering::SeqCst);
+    let mut circuit_breaker = runs.recv().await.unwrap();
+    assert!(submissions_during(&handler, BLOCK_TIME * 10).await > 0);
+
+    // halt - the relayer keeps rebooting, but the halted listener stops it before anything is submitted
+    bridge.halted.store(true, Ordering::SeqCst);
+    assert!(matches!(
+        circuit_breaker.recv().await,
+        Ok(CircuitBreakerEvent::BridgeHaltAlephZero)
+    ));
+    let mut circuit_breaker = runs.recv().await.unwrap();
+    assert!(matches!(
+        circuit_breaker.recv().await,
+        Ok(CircuitBreakerEvent::BridgeHaltAlephZero)
+    ));
+    assert_eq!(submissions_during(&handler, MAX_REBOOT_DELAY * 2).await, 0);
+
+    // unhalt
+    bridge.halted.store(false, Ordering::SeqCst);
+    assert!(submissions_during(&handler, MAX_REBOOT_DELAY + BLOCK_TIME * 10).await > 0);
+}
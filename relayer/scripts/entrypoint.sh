@@ -38,7 +38,7 @@ ETH_GAS_LIMIT=${ETH_GAS_LIMIT:-""}
 # --- Signer's CID
 SIGNER_CID=${SIGNER_CID:-""}
 
-# --- Signer's port
+# --- Signer's port, a list (1234,1300) or a range (1234-1240) of ports to probe
 SIGNER_PORT=${SIGNER_PORT:-"1234"}
 
 # --- RELAYER ID from MY_POD_NAME coming from statefulset's pod, such as
@@ -107,6 +107,14 @@ if [[ -n "${DEV_MODE}" ]]; then
   ARGS+=(--dev)
 fi
 
+if [[ -n "${ETH_PRIVATE_KEY_ENV}" ]]; then
+  ARGS+=(--eth-private-key-env=${ETH_PRIVATE_KEY_ENV})
+fi
+
+if [[ -n "${ETH_MNEMONIC_ENV}" ]]; then
+  ARGS+=(--eth-mnemonic-env=${ETH_MNEMONIC_ENV})
+fi
+
 if [[ -n "${OVERRIDE_AZERO_CACHE}" ]]; then
   ARGS+=(--override-azero-cache)
 fi
@@ -127,10 +135,126 @@ if [[ -n "${SIGNER_CID}" ]]; then
   ARGS+=(--signer-cid=${SIGNER_CID})
 fi
 
+if [[ -n "${ETH_VERIFY_RECEIPT_LOGS}" ]]; then
+  ARGS+=(--eth-verify-receipt-logs)
+fi
+
+if [[ -n "${ETH_SKIP_DRY_RUN}" ]]; then
+  ARGS+=(--eth-skip-dry-run)
+fi
+
+if [[ -n "${ETH_SINGLE_SUBMISSION}" ]]; then
+  ARGS+=(--eth-single-submission)
+fi
+
+if [[ -n "${ETH_FINALITY_TRACKERS}" ]]; then
+  ARGS+=(--eth-finality-trackers=${ETH_FINALITY_TRACKERS})
+fi
+
+if [[ -n "${SIGNER_CONNECT_TIMEOUT_SEC}" ]]; then
+  ARGS+=(--signer-connect-timeout-sec=${SIGNER_CONNECT_TIMEOUT_SEC})
+fi
+
+if [[ -n "${SIGNER_REQUEST_TIMEOUT_SEC}" ]]; then
+  ARGS+=(--signer-request-timeout-sec=${SIGNER_REQUEST_TIMEOUT_SEC})
+fi
+
+if [[ -n "${MAX_RESTART_BACKOFF_SEC}" ]]; then
+  ARGS+=(--max-restart-backoff-sec=${MAX_RESTART_BACKOFF_SEC})
+fi
+
+if [[ -n "${MAX_CONSECUTIVE_RESTARTS}" ]]; then
+  ARGS+=(--max-consecutive-restarts=${MAX_CONSECUTIVE_RESTARTS})
+fi
+
+if [[ -n "${SHUTDOWN_GRACE_PERIOD_SEC}" ]]; then
+  ARGS+=(--shutdown-grace-period-sec=${SHUTDOWN_GRACE_PERIOD_SEC})
+fi
+
+if [[ -n "${FINALIZED_HEAD_MAX_BACKOFF_SEC}" ]]; then
+  ARGS+=(--finalized-head-max-backoff-sec=${FINALIZED_HEAD_MAX_BACKOFF_SEC})
+fi
+
+if [[ -n "${FINALIZED_HEAD_MAX_RETRIES}" ]]; then
+  ARGS+=(--finalized-head-max-retries=${FINALIZED_HEAD_MAX_RETRIES})
+fi
+
+if [[ -n "${SHADOW_OF}" ]]; then
+  ARGS+=(--shadow-of=${SHADOW_OF})
+fi
+
+if [[ -n "${SHADOW_GRACE_PERIOD_SEC}" ]]; then
+  ARGS+=(--shadow-grace-period-sec=${SHADOW_GRACE_PERIOD_SEC})
+fi
+
+if [[ -n "${ADVISORY_POLL_INTERVAL_SEC}" ]]; then
+  ARGS+=(--advisory-poll-interval-sec=${ADVISORY_POLL_INTERVAL_SEC})
+fi
+
+if [[ -n "${ADVISORY_QUERY_TIMEOUT_SEC}" ]]; then
+  ARGS+=(--advisory-query-timeout-sec=${ADVISORY_QUERY_TIMEOUT_SEC})
+fi
+
+if [[ -n "${ADVISORY_MAX_BACKOFF_SEC}" ]]; then
+  ARGS+=(--advisory-max-backoff-sec=${ADVISORY_MAX_BACKOFF_SEC})
+fi
+
+if [[ -n "${ADVISORY_MAX_RETRIES}" ]]; then
+  ARGS+=(--advisory-max-retries=${ADVISORY_MAX_RETRIES})
+fi
+
 if [[ -n "${SYNC_STEP}" ]]; then
   ARGS+=(--sync-step=${SYNC_STEP})
 fi
 
+if [[ -n "${AZERO_BLOCK_PROD_TIME_SEC}" ]]; then
+  ARGS+=(--azero-block-prod-time-sec=${AZERO_BLOCK_PROD_TIME_SEC})
+fi
+
+if [[ -n "${ETH_BLOCK_PROD_TIME_SEC}" ]]; then
+  ARGS+=(--eth-block-prod-time-sec=${ETH_BLOCK_PROD_TIME_SEC})
+fi
+
+if [[ -n "${ETH_FINALITY}" ]]; then
+  ARGS+=(--eth-finality=${ETH_FINALITY})
+fi
+
+if [[ -n "${AZERO_MAX_REQUESTS_PER_BLOCK}" ]]; then
+  ARGS+=(--azero-max-requests-per-block=${AZERO_MAX_REQUESTS_PER_BLOCK})
+fi
+
+if [[ -n "${ETH_MAX_CONCURRENT_SUBMISSIONS}" ]]; then
+  ARGS+=(--eth-max-concurrent-submissions=${ETH_MAX_CONCURRENT_SUBMISSIONS})
+fi
+
+if [[ -n "${AZERO_HALT_RECHECK_INTERVAL_SEC}" ]]; then
+  ARGS+=(--azero-halt-recheck-interval-sec=${AZERO_HALT_RECHECK_INTERVAL_SEC})
+fi
+
+if [[ -n "${AZERO_CATCH_UP_WINDOW}" ]]; then
+  ARGS+=(--azero-catch-up-window=${AZERO_CATCH_UP_WINDOW})
+fi
+
+if [[ -n "${AZERO_MIN_CONFIRMATIONS}" ]]; then
+  ARGS+=(--azero-min-confirmations=${AZERO_MIN_CONFIRMATIONS})
+fi
+
+if [[ -n "${AZERO_GUARD_READ_TTL_SEC}" ]]; then
+  ARGS+=(--azero-guard-read-ttl-sec=${AZERO_GUARD_READ_TTL_SEC})
+fi
+
+if [[ -n "${AZERO_BASE_FEE_CACHE_TTL_SEC}" ]]; then
+  ARGS+=(--azero-base-fee-cache-ttl-sec=${AZERO_BASE_FEE_CACHE_TTL_SEC})
+fi
+
+if [[ -n "${AUDIT_SINK}" ]]; then
+  ARGS+=(--audit-sink=${AUDIT_SINK})
+fi
+
+if [[ -n "${AUDIT_LOG_PATH}" ]]; then
+  ARGS+=(--audit-log-path=${AUDIT_LOG_PATH})
+fi
+
 if [[ -n "${ETH_POLL_INTERVAL}" ]]; then
   ARGS+=(--eth-poll-interval=${ETH_POLL_INTERVAL})
 fi
@@ -144,10 +268,72 @@ if [[ "${ETH_GAS_LIMIT}" =~ ^[0-9]+$ ]]; then
   ARGS+=(--eth-gas-limit=${ETH_GAS_LIMIT})
 fi
 
+if [[ -n "${ETH_FEE_STRATEGY}" ]]; then
+  ARGS+=(--eth-fee-strategy=${ETH_FEE_STRATEGY})
+fi
+
+if [[ -n "${ETH_FEE_WEI_PER_PICO_AZERO}" ]]; then
+  ARGS+=(--eth-fee-wei-per-pico-azero=${ETH_FEE_WEI_PER_PICO_AZERO})
+fi
+
+if [[ -n "${ETH_FEE_ORACLE_PERCENT}" ]]; then
+  ARGS+=(--eth-fee-oracle-percent=${ETH_FEE_ORACLE_PERCENT})
+fi
+
+if [[ -n "${ETH_MIN_MAX_FEE_PER_GAS_GWEI}" ]]; then
+  ARGS+=(--eth-min-max-fee-per-gas-gwei=${ETH_MIN_MAX_FEE_PER_GAS_GWEI})
+fi
+
+if [[ -n "${ETH_MAX_MAX_FEE_PER_GAS_GWEI}" ]]; then
+  ARGS+=(--eth-max-max-fee-per-gas-gwei=${ETH_MAX_MAX_FEE_PER_GAS_GWEI})
+fi
+
+if [[ -n "${ETH_MAX_PRIORITY_FEE_PER_GAS_GWEI}" ]]; then
+  ARGS+=(--eth-max-priority-fee-per-gas-gwei=${ETH_MAX_PRIORITY_FEE_PER_GAS_GWEI})
+fi
+
+if [[ -n "${ETH_MAX_GAS_PRICE_GWEI}" ]]; then
+  ARGS+=(--eth-max-gas-price-gwei=${ETH_MAX_GAS_PRICE_GWEI})
+fi
+
+if [[ -n "${ETH_GAS_PRICE_MAX_BACKOFF_SEC}" ]]; then
+  ARGS+=(--eth-gas-price-max-backoff-sec=${ETH_GAS_PRICE_MAX_BACKOFF_SEC})
+fi
+
+if [[ -n "${ETH_FORWARDING_RETRIES}" ]]; then
+  ARGS+=(--eth-forwarding-retries=${ETH_FORWARDING_RETRIES})
+fi
+
+if [[ -n "${ETH_FORWARDING_MAX_BACKOFF_SEC}" ]]; then
+  ARGS+=(--eth-forwarding-max-backoff-sec=${ETH_FORWARDING_MAX_BACKOFF_SEC})
+fi
+
+if [[ -n "${GAS_PRICE_ORACLE_METADATA}" ]]; then
+  ARGS+=(--gas-price-oracle-metadata=${GAS_PRICE_ORACLE_METADATA})
+fi
+
 if [[ -n "${PAYOUT_ADDRESS}" ]]; then
   ARGS+=(--payout-address=${PAYOUT_ADDRESS})
 fi
 
+if [[ -n "${REDIS_TLS}" ]]; then
+  ARGS+=(--redis-tls)
+fi
+
+if [[ -n "${REDIS_CA_PATH}" ]]; then
+  ARGS+=(--redis-ca-path=${REDIS_CA_PATH})
+fi
+
+if [[ -n "${REDIS_USERNAME}" ]]; then
+  ARGS+=(--redis-username=${REDIS_USERNAME})
+fi
+
+# REDIS_PASSWORD and ADMIN_TOKEN are read by the relayer from the environment, so that they do not show up in the process list
+
+if [[ -n "${REQUEST_DEDUP_WINDOW_SEC}" ]]; then
+  ARGS+=(--request-dedup-window-sec=${REQUEST_DEDUP_WINDOW_SEC})
+fi
+
 if [[ "${REDIS_AZERO_BLOCK_KEY}" =~ ^[a-z0-9_]+$ ]]; then
   echo "Setting --redis-azero-block-key to ${REDIS_AZERO_BLOCK_KEY}"
   ARGS+=(--redis-azero-block-key=${REDIS_AZERO_BLOCK_KEY})
@@ -159,4 +345,5 @@ if [[ "${REDIS_ETH_BLOCK_KEY}" =~ ^[a-z0-9_]+$ ]]; then
 fi
 
 # --- RUN
-xargs most-relayer "${ARGS[@]}"
+# exec, so that SIGTERM reaches the relayer and it can shut down gracefully
+exec most-relayer "${ARGS[@]}"
@@ -4,7 +4,7 @@ use ethers::{
     types::Address,
 };
 use log::info;
-use signer_client::{Client, Command, Response};
+use signer_client::{Client, Command, Response, PROTOCOL_VERSION};
 use subxt::ext::{
     sp_core::{crypto::SecretStringError, sr25519::Pair as KeyPair, Pair},
     sp_runtime::AccountId32,
@@ -129,6 +129,15 @@ async fn do_handle_client(
         info!("Received command: {:?}", command);
 
         match command {
+            Command::Hello { client_version } => {
+                info!("Client speaks protocol version {client_version:#x}");
+                client
+                    .send(&Response::Hello {
+                        server_version: PROTOCOL_VERSION,
+                    })
+                    .await?;
+            }
+
             Command::Ping => {
                 client.send(&Response::Pong).await?;
             }
@@ -149,6 +158,19 @@ async fn do_handle_client(
                     .await?;
             }
 
+            Command::SignAzeroBatch { payloads } => {
+                let results = payloads
+                    .into_iter()
+                    .map(|payload| {
+                        let signature = azero_key.sign(&payload);
+                        let signature = subxt::ext::sp_runtime::MultiSignature::Sr25519(signature);
+                        (payload, signature)
+                    })
+                    .collect();
+
+                client.send(&Response::SignedAzeroBatch { results }).await?;
+            }
+
             Command::EthAddress => {
                 let address = eth_wallet.address();
                 client.send(&Response::EthAddress { address }).await?;
@@ -241,6 +263,21 @@ mod test {
         assert!(signature.verify(&payload[..], &client.azero_account_id().await.unwrap()));
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_sign_azero_batch() {
+        let mut client = connect().await;
+        let payloads: Vec<&[u8]> = vec![b"Hello, world!", b"", b"Hello again!"];
+
+        let signatures = client.sign_azero_batch(&payloads).await.unwrap();
+
+        let account_id = client.azero_account_id().await.unwrap();
+        assert!(signatures.len() == payloads.len());
+        for (signature, payload) in signatures.iter().zip(&payloads) {
+            assert!(signature.verify(*payload, &account_id));
+        }
+    }
+
     #[tokio::test]
     #[serial]
     async fn test_eth_address() {
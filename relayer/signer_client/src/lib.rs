@@ -1,8 +1,16 @@
+use std::{future::Future, io, time::Duration};
+
 use futures::{SinkExt as _, StreamExt as _};
 use serde::{Deserialize, Serialize};
 use serde_json::Deserializer;
 use subxt::ext::{sp_core::crypto::AccountId32, sp_runtime::MultiSignature};
-use tokio_util::codec::{FramedRead, FramedWrite, LengthDelimitedCodec};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    time::timeout,
+};
+use tokio_util::codec::{
+    length_delimited::LengthDelimitedCodecError, FramedRead, FramedWrite, LengthDelimitedCodec,
+};
 use tokio_vsock::{OwnedReadHalf, OwnedWriteHalf, VsockStream};
 use vsock::VsockAddr;
 
@@ -14,25 +22,73 @@ type EthChainId = ethers::types::U64;
 
 const ETH_MAINNET_CHAIN_ID: EthChainId = EthChainId::one();
 
+/// Largest message accepted by default, far above the size of any command or response
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Version of the protocol spoken by this crate, with the major version in the upper 16 bits.
+///
+/// Clients and signers with different major versions can not talk to each other.
+pub const PROTOCOL_VERSION: u32 = 0x0001_0000;
+
+fn major_version(version: u32) -> u32 {
+    version >> 16
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("IO error: {0}")]
-    IO(#[from] std::io::Error),
+    IO(std::io::Error),
+    #[error("Signer did not answer in time")]
+    Timeout,
+    #[error("Message exceeds the maximum size")]
+    MessageTooLarge,
     #[error("Serde error: {0}")]
     Serde(#[from] serde_json::Error),
     #[error("Invalid response from server")]
     InvalidResponse { expected: String, got: Response },
     #[error("Connection closed")]
     Closed,
+    #[error("Signer speaks protocol version {server:#x}, incompatible with version {client:#x} of the client")]
+    VersionMismatch { client: u32, server: u32 },
+}
+
+impl From<io::Error> for Error {
+    fn from(why: io::Error) -> Self {
+        match why.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => Error::Timeout,
+            // the only error of the codec is a frame length above the maximum
+            _ if why
+                .get_ref()
+                .is_some_and(|inner| inner.is::<LengthDelimitedCodecError>()) =>
+            {
+                Error::MessageTooLarge
+            }
+            _ => Error::IO(why),
+        }
+    }
+}
+
+/// Limits on how long the client waits for the signer, `None` waits forever
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    pub connect: Option<Duration>,
+    pub read: Option<Duration>,
+    pub write: Option<Duration>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Command {
+    Hello {
+        client_version: u32,
+    },
     Ping,
     AccountIdAzero,
     SignAzero {
         payload: Vec<u8>,
     },
+    SignAzeroBatch {
+        payloads: Vec<Vec<u8>>,
+    },
     EthAddress,
     SignEthHash {
         hash: EthH256,
@@ -45,6 +101,9 @@ pub enum Command {
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub enum Response {
+    Hello {
+        server_version: u32,
+    },
     Pong,
     AccountIdAzero {
         account_id: AccountId32,
@@ -53,6 +112,9 @@ pub enum Response {
         payload: Vec<u8>,
         signature: MultiSignature,
     },
+    SignedAzeroBatch {
+        results: Vec<(Vec<u8>, MultiSignature)>,
+    },
     EthAddress {
         address: EthAddress,
     },
@@ -67,39 +129,149 @@ pub enum Response {
     },
 }
 
-pub struct Client {
-    read: FramedRead<OwnedReadHalf, LengthDelimitedCodec>,
-    write: FramedWrite<OwnedWriteHalf, LengthDelimitedCodec>,
+/// Every message is a JSON document preceded by its length as a big-endian `u32`, so that pipelined messages
+/// are read back whole however the stream splits them.
+///
+/// A declared length above `max_message_size` is rejected before anything is buffered.
+fn codec(max_message_size: usize) -> LengthDelimitedCodec {
+    LengthDelimitedCodec::builder()
+        .length_field_length(4)
+        .max_frame_length(max_message_size)
+        .big_endian()
+        .new_codec()
+}
+
+fn encode<T: Serialize>(msg: &T) -> Result<Vec<u8>, Error> {
+    Ok(serde_json::to_vec(msg)?)
+}
+
+fn decode<'de, T: Deserialize<'de>>(frame: &[u8]) -> Result<T, Error> {
+    let mut de = Deserializer::from_reader(frame);
+    Ok(T::deserialize(&mut de)?)
+}
+
+async fn within<T, F>(limit: Option<Duration>, operation: F) -> Result<T, Error>
+where
+    F: Future<Output = Result<T, Error>>,
+{
+    match limit {
+        Some(limit) => timeout(limit, operation)
+            .await
+            .map_err(|_| Error::Timeout)?,
+        None => operation.await,
+    }
+}
+
+/// Connection to the signer, over VSock unless the halves of another stream are given in tests
+pub struct Client<R = OwnedReadHalf, W = OwnedWriteHalf> {
+    read: FramedRead<R, LengthDelimitedCodec>,
+    write: FramedWrite<W, LengthDelimitedCodec>,
+    timeouts: Timeouts,
 }
 
 impl From<VsockStream> for Client {
     fn from(connection: VsockStream) -> Self {
         let (read, write) = connection.into_split();
-        let write = FramedWrite::new(write, LengthDelimitedCodec::new());
-        let read = FramedRead::new(read, LengthDelimitedCodec::new());
-
-        Self { write, read }
+        Self::framed(read, write, Timeouts::default())
     }
 }
 
 impl Client {
     pub async fn new(cid: u32, port: u32) -> Result<Self, Error> {
-        let connection = VsockStream::connect(VsockAddr::new(cid, port)).await?;
-        Ok(connection.into())
+        Self::with_timeouts(cid, port, Timeouts::default()).await
+    }
+
+    /// Fails with [`Error::Timeout`] when the signer does not accept the connection, take a message or answer in time,
+    /// and with [`Error::VersionMismatch`] when it speaks an incompatible version of the protocol
+    pub async fn with_timeouts(cid: u32, port: u32, timeouts: Timeouts) -> Result<Self, Error> {
+        let connection = within(timeouts.connect, async {
+            Ok::<_, Error>(VsockStream::connect(VsockAddr::new(cid, port)).await?)
+        })
+        .await?;
+        let (read, write) = connection.into_split();
+
+        let mut client = Self::framed(read, write, timeouts);
+        client.hello().await?;
+        Ok(client)
+    }
+}
+
+impl<R: AsyncRead + Unpin, W: AsyncWrite + Unpin> Client<R, W> {
+    fn framed(read: R, write: W, timeouts: Timeouts) -> Self {
+        Self {
+            read: FramedRead::new(read, codec(DEFAULT_MAX_MESSAGE_SIZE)),
+            write: FramedWrite::new(write, codec(DEFAULT_MAX_MESSAGE_SIZE)),
+            timeouts,
+        }
+    }
+
+    /// Messages longer than `max_message_size` fail with [`Error::MessageTooLarge`], both ways
+    pub fn set_max_message_size(&mut self, max_message_size: usize) {
+        self.read
+            .decoder_mut()
+            .set_max_frame_length(max_message_size);
+        self.write
+            .encoder_mut()
+            .set_max_frame_length(max_message_size);
     }
 
     pub async fn send<T: Serialize>(&mut self, msg: &T) -> Result<(), Error> {
-        let msg = serde_json::to_vec(msg)?;
-        self.write.send(msg.into()).await?;
-        Ok(())
+        let frame = encode(msg)?;
+        within(self.timeouts.write, async {
+            Ok::<_, Error>(self.write.send(frame.into()).await?)
+        })
+        .await
     }
 
     pub async fn recv<'de, T: Deserialize<'de>>(&mut self) -> Result<T, Error> {
-        let msg = &self.read.next().await.ok_or(Error::Closed)??;
-        let mut de = Deserializer::from_reader(msg.as_ref());
-        let res = T::deserialize(&mut de)?;
+        let frame = within(self.timeouts.read, async {
+            Ok::<_, Error>(self.read.next().await.ok_or(Error::Closed)??)
+        })
+        .await?;
+        decode(&frame)
+    }
+
+    /// Exchanges the protocol versions with the signer, returning the version of the signer if it is compatible
+    pub async fn hello(&mut self) -> Result<u32, Error> {
+        self.send(&Command::Hello {
+            client_version: PROTOCOL_VERSION,
+        })
+        .await?;
+
+        match self.recv().await? {
+            Response::Hello { server_version }
+                if major_version(server_version) == major_version(PROTOCOL_VERSION) =>
+            {
+                Ok(server_version)
+            }
+            Response::Hello { server_version } => Err(Error::VersionMismatch {
+                client: PROTOCOL_VERSION,
+                server: server_version,
+            }),
+            other => Err(Error::InvalidResponse {
+                expected: "Hello".to_string(),
+                got: other,
+            }),
+        }
+    }
+
+    /// Checks that the signer answers on this connection
+    pub async fn ping(&mut self) -> Result<(), Error> {
+        self.send(&Command::Ping).await?;
+
+        match self.recv().await? {
+            Response::Pong => Ok(()),
+            other => Err(Error::InvalidResponse {
+                expected: "Pong".to_string(),
+                got: other,
+            }),
+        }
+    }
 
-        Ok(res)
+    /// Checks that the signer answers and returns the AlephZero account it signs with
+    pub async fn health_check(&mut self) -> Result<AccountId32, Error> {
+        self.ping().await?;
+        self.azero_account_id().await
     }
 
     pub async fn azero_account_id(&mut self) -> Result<AccountId32, Error> {
@@ -132,6 +304,19 @@ impl Client {
         }
     }
 
+    /// Signs all of `payloads` in one round trip, returning the signatures in the same order
+    pub async fn sign_azero_batch(
+        &mut self,
+        payloads: &[&[u8]],
+    ) -> Result<Vec<MultiSignature>, Error> {
+        self.send(&Command::SignAzeroBatch {
+            payloads: payloads.iter().map(|payload| payload.to_vec()).collect(),
+        })
+        .await?;
+
+        batch_signatures(payloads, self.recv().await?)
+    }
+
     pub async fn eth_address(&mut self) -> Result<EthAddress, Error> {
         self.send(&Command::EthAddress).await?;
 
@@ -192,3 +377,250 @@ impl Client {
         })
     }
 }
+
+/// The whole batch is rejected if any returned payload differs from the requested one at the same position
+fn batch_signatures(payloads: &[&[u8]], response: Response) -> Result<Vec<MultiSignature>, Error> {
+    if let Response::SignedAzeroBatch { results } = &response {
+        let matches = results.len() == payloads.len()
+            && results
+                .iter()
+                .zip(payloads)
+                .all(|((return_payload, _), payload)| return_payload == payload);
+
+        if matches {
+            return Ok(results
+                .iter()
+                .map(|(_, signature)| signature.clone())
+                .collect());
+        }
+    }
+
+    Err(Error::InvalidResponse {
+        expected: format!("SignedAzeroBatch(payloads: {:?})", payloads),
+        got: response,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use subxt::ext::sp_core::sr25519::Signature;
+    use tokio::io::{duplex, split, AsyncWriteExt};
+
+    use super::*;
+
+    fn signature(byte: u8) -> MultiSignature {
+        MultiSignature::Sr25519(Signature::from_raw([byte; 64]))
+    }
+
+    #[tokio::test]
+    async fn pipelined_commands_are_decoded_separately() {
+        let commands = vec![
+            Command::Ping,
+            Command::SignAzero {
+                payload: b"Hello, world!".to_vec(),
+            },
+        ];
+        // a tiny buffer splits every frame over many partial reads and writes
+        let (writer, reader) = duplex(3);
+        let mut write = FramedWrite::new(writer, codec(DEFAULT_MAX_MESSAGE_SIZE));
+        let mut read = FramedRead::new(reader, codec(DEFAULT_MAX_MESSAGE_SIZE));
+
+        let sent = commands.clone();
+        let sending = tokio::spawn(async move {
+            for command in &sent {
+                write.send(encode(command).unwrap().into()).await.unwrap();
+            }
+        });
+
+        let mut received = Vec::new();
+        while let Some(frame) = read.next().await {
+            received.push(decode::<Command>(&frame.unwrap()).unwrap());
+        }
+        sending.await.unwrap();
+
+        assert_eq!(received, commands);
+    }
+
+    #[tokio::test]
+    async fn frames_are_prefixed_with_the_length() {
+        let body = encode(&Command::Ping).unwrap();
+        let mut write = FramedWrite::new(Vec::new(), codec(DEFAULT_MAX_MESSAGE_SIZE));
+        write.send(body.clone().into()).await.unwrap();
+        let frame = write.into_inner();
+
+        assert_eq!(frame[..4], (body.len() as u32).to_be_bytes());
+        assert_eq!(frame[4..], body);
+    }
+
+    #[test]
+    fn batch_signatures_are_returned_in_order() {
+        let response = Response::SignedAzeroBatch {
+            results: vec![
+                (b"first".to_vec(), signature(1)),
+                (b"second".to_vec(), signature(2)),
+            ],
+        };
+
+        let signatures = batch_signatures(&[b"first", b"second"], response).unwrap();
+
+        assert_eq!(signatures, vec![signature(1), signature(2)]);
+    }
+
+    #[test]
+    fn any_mismatched_payload_rejects_the_whole_batch() {
+        let payloads: &[&[u8]] = &[b"first", b"second"];
+        let reordered = Response::SignedAzeroBatch {
+            results: vec![
+                (b"second".to_vec(), signature(2)),
+                (b"first".to_vec(), signature(1)),
+            ],
+        };
+        let missing = Response::SignedAzeroBatch {
+            results: vec![(b"first".to_vec(), signature(1))],
+        };
+
+        for response in [reordered, missing, Response::Pong] {
+            assert!(matches!(
+                batch_signatures(payloads, response),
+                Err(Error::InvalidResponse { .. })
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn silent_signer_times_out() {
+        // the other end is kept open but never answers
+        let (_signer, reader) = duplex(64);
+        let mut read = FramedRead::new(reader, codec(DEFAULT_MAX_MESSAGE_SIZE));
+
+        let received = within(Some(Duration::from_millis(50)), async {
+            Ok::<_, Error>(read.next().await.ok_or(Error::Closed)??)
+        })
+        .await;
+
+        assert!(matches!(received, Err(Error::Timeout)));
+    }
+
+    #[test]
+    fn timed_out_io_is_a_timeout() {
+        assert!(matches!(
+            Error::from(io::Error::from(io::ErrorKind::TimedOut)),
+            Error::Timeout
+        ));
+        assert!(matches!(
+            Error::from(io::Error::from(io::ErrorKind::ConnectionRefused)),
+            Error::IO(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn health_check_rejects_unexpected_responses() {
+        let (client_stream, signer_stream) = duplex(1024);
+        let (read, write) = split(client_stream);
+        let mut client = Client::framed(read, write, Timeouts::default());
+
+        // answers every command with a response to a different one
+        let signer = tokio::spawn(async move {
+            let (read, write) = split(signer_stream);
+            let mut signer = Client::framed(read, write, Timeouts::default());
+            while signer.recv::<Command>().await.is_ok() {
+                let response = Response::EthAddress {
+                    address: EthAddress::zero(),
+                };
+                if signer.send(&response).await.is_err() {
+                    break;
+                }
+            }
+        });
+
+        assert!(matches!(
+            client.health_check().await,
+            Err(Error::InvalidResponse {
+                got: Response::EthAddress { .. },
+                ..
+            })
+        ));
+        drop(client);
+        signer.await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn oversized_message_is_rejected_before_it_is_read() {
+        let (mut signer, client_stream) = duplex(64);
+        let (read, write) = split(client_stream);
+        let mut client = Client::framed(read, write, Timeouts::default());
+        client.set_max_message_size(1024);
+
+        // declares 4 GiB, of which nothing follows
+        signer.write_all(&u32::MAX.to_be_bytes()).await.unwrap();
+
+        assert!(matches!(
+            client.recv::<Response>().await,
+            Err(Error::MessageTooLarge)
+        ));
+    }
+
+    #[tokio::test]
+    async fn message_at_the_maximum_size_is_accepted() {
+        let body = encode(&Command::Ping).unwrap();
+        let (signer_stream, client_stream) = duplex(64);
+        let (read, write) = split(client_stream);
+        let mut client = Client::framed(read, write, Timeouts::default());
+        client.set_max_message_size(body.len());
+
+        let mut signer = FramedWrite::new(signer_stream, codec(body.len()));
+        signer.send(body.into()).await.unwrap();
+
+        assert_eq!(client.recv::<Command>().await.unwrap(), Command::Ping);
+        assert!(matches!(
+            client
+                .send(&Command::SignAzero {
+                    payload: vec![0; 1024]
+                })
+                .await,
+            Err(Error::MessageTooLarge)
+        ));
+    }
+
+    /// Client connected to a fake signer which answers every hello with `server_version`
+    fn client_of_signer(
+        server_version: u32,
+    ) -> Client<impl AsyncRead + Unpin, impl AsyncWrite + Unpin> {
+        let (client_stream, signer_stream) = duplex(1024);
+        let (read, write) = split(client_stream);
+
+        tokio::spawn(async move {
+            let (read, write) = split(signer_stream);
+            let mut signer = Client::framed(read, write, Timeouts::default());
+            while let Ok(Command::Hello { .. }) = signer.recv::<Command>().await {
+                if signer
+                    .send(&Response::Hello { server_version })
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+
+        Client::framed(read, write, Timeouts::default())
+    }
+
+    #[tokio::test]
+    async fn signer_with_another_minor_version_is_accepted() {
+        let mut client = client_of_signer(PROTOCOL_VERSION + 1);
+
+        assert_eq!(client.hello().await.unwrap(), PROTOCOL_VERSION + 1);
+    }
+
+    #[tokio::test]
+    async fn signer_with_another_major_version_is_rejected() {
+        let server_version = PROTOCOL_VERSION + 0x0001_0000;
+        let mut client = client_of_signer(server_version);
+
+        assert!(matches!(
+            client.hello().await,
+            Err(Error::VersionMismatch { client: PROTOCOL_VERSION, server }) if server == server_version
+        ));
+    }
+}